@@ -0,0 +1,136 @@
+// Resolution of third-party `uses:` references: SHA pinning and mirrored owners.
+use crate::types::config::Settings;
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+pub const CHECKOUT_ACTION: &str = "actions/checkout@v4";
+pub const CACHE_ACTION: &str = "actions/cache@v4";
+pub const TOOLCHAIN_ACTION: &str = "dtolnay/rust-toolchain";
+pub const COMMAND_GRADER_ACTION: &str = "classroom-resources/autograding-command-grader@v1";
+pub const IO_GRADER_ACTION: &str = "classroom-resources/autograding-io-grader@v1";
+pub const REPORTER_ACTION: &str = "classroom-resources/autograding-grading-reporter@v1";
+
+/// Bundled lock table: `owner/repo@tag` -> full commit SHA.
+///
+/// Keep this table small and verify each SHA against the upstream release when bumping it.
+/// The toolchain action is keyed per ref (`dtolnay/rust-toolchain@stable`, `@1.82.0`, ...).
+/// Anything else is supplied per course through `settings.action_pins`.
+pub const ACTION_LOCKS: &[(&str, &str)] = &[
+    (CHECKOUT_ACTION, "11bd71901bbe5b1630ceea73d27597364c9af683"), // v4.2.2
+    (CACHE_ACTION, "5a3ec84eff668545956fd18022155c47e93e2684"),    // v4.2.3
+];
+
+/// A `uses:` target after pinning/mirroring, remembering the tag it was pinned from
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedAction {
+    pub target: String,      // e.g., "actions/checkout@11bd7190..."
+    pub tag: Option<String>, // e.g., Some("v4") when pinned
+}
+
+impl ResolvedAction {
+    /// Render as a `uses:` value; a pinned action keeps its tag as a trailing comment
+    pub fn render(&self, quote: bool) -> String {
+        let target = if quote {
+            crate::utils::yaml_quote(&self.target)
+        } else {
+            self.target.clone()
+        };
+        match &self.tag {
+            Some(tag) => format!("{target} # {tag}"),
+            None => target,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ActionResolver {
+    pin: bool,
+    mirror: Option<String>,
+    mirror_names: BTreeMap<String, String>,
+    pins: BTreeMap<String, String>,
+}
+
+impl ActionResolver {
+    pub fn new(pin: bool, mirror: Option<String>, settings: &Settings) -> Self {
+        let mut pins: BTreeMap<String, String> = ACTION_LOCKS
+            .iter()
+            .map(|(action, sha)| (action.to_string(), sha.to_string()))
+            .collect();
+        // Config entries extend and override the bundled table
+        pins.extend(settings.action_pins.clone());
+        Self {
+            pin,
+            mirror,
+            mirror_names: settings.action_mirror_names.clone(),
+            pins,
+        }
+    }
+
+    /// With pinning on, fail once for every action in `actions` that has no pin, with the
+    /// `settings.action_pins` entries to add
+    pub fn check_pins(&self, actions: &[String]) -> Result<()> {
+        if !self.pin {
+            return Ok(());
+        }
+        let missing: Vec<&String> = actions
+            .iter()
+            .filter(|a| !self.pins.contains_key(*a))
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        let entries: Vec<String> = missing
+            .iter()
+            .map(|a| format!("    \"{a}\": \"<full commit sha>\""))
+            .collect();
+        anyhow::bail!(
+            "No pinned commit for {}. Add them to `settings.action_pins` in autograder.json:\n\n  \"action_pins\": {{\n{}\n  }}",
+            missing
+                .iter()
+                .map(|a| format!("`{a}`"))
+                .collect::<Vec<_>>()
+                .join(", "),
+            entries.join(",\n")
+        )
+    }
+
+    /// Resolve `owner/repo@tag` against the lock table and mirror settings
+    pub fn resolve(&self, action: &str) -> Result<ResolvedAction> {
+        let (repo, tag) = action
+            .split_once('@')
+            .ok_or_else(|| anyhow::anyhow!("Action `{}` has no @ref", action))?;
+
+        // Only the owner changes, like a plain fork; `action_mirror_names` renames the repo too
+        let repo = match &self.mirror {
+            Some(org) => {
+                let name = match self.mirror_names.get(repo) {
+                    Some(name) => name.as_str(),
+                    None => repo.split_once('/').map_or(repo, |(_, name)| name),
+                };
+                format!("{}/{}", org.trim_end_matches('/'), name)
+            }
+            None => repo.to_string(),
+        };
+
+        if !self.pin {
+            return Ok(ResolvedAction {
+                target: format!("{repo}@{tag}"),
+                tag: None,
+            });
+        }
+
+        // Pins are keyed by the upstream name; a mirror is expected to carry the same commits
+        let sha = self.pins.get(action).ok_or_else(|| {
+            anyhow::anyhow!(
+                "No pinned commit for `{}`. Add it to `settings.action_pins` in autograder.json, \
+                 e.g. \"action_pins\": {{ \"{}\": \"<full commit sha>\" }}",
+                action,
+                action
+            )
+        })?;
+        Ok(ResolvedAction {
+            target: format!("{repo}@{sha}"),
+            tag: Some(tag.to_string()),
+        })
+    }
+}
@@ -0,0 +1,65 @@
+// Forgejo / Gitea Actions workflow. The syntax follows GitHub's, minus the Classroom actions.
+use super::{BackendInput, GENERATED_MARKER, rustup_install_lines};
+use crate::cli::build::actions::{CACHE_ACTION, CHECKOUT_ACTION};
+use crate::cli::build::build_functions::{get_cache_step, get_checkout_step, get_yaml_triggers};
+use crate::cli::build::required_components;
+use crate::utils::scripts::SCRIPT_NAMES;
+use crate::utils::yaml_plain;
+use anyhow::Result;
+
+pub fn render_workflow(input: &BackendInput) -> Result<String> {
+    let mut out = format!(
+        "{GENERATED_MARKER} from autograder.json; re-run `build --target forgejo` instead of editing.\n\
+         name: {}\n{}\njobs:\n  autograding:\n    \
+         # Match a label your runner registered with\n    runs-on: ubuntu-latest\n    steps:\n",
+        yaml_plain(input.strings.text("workflow.name")),
+        // No Classroom here to send `repository_dispatch`
+        get_yaml_triggers(input.triggers, None)
+    );
+    out.push_str(&get_checkout_step(
+        &input.actions.resolve(CHECKOUT_ACTION)?,
+        input.strings,
+    ));
+
+    // Runner images rarely ship Rust, so bootstrap rustup when it's missing
+    out.push_str(&format!(
+        "      - name: {}\n        run: |\n",
+        yaml_plain(input.strings.text("workflow.toolchain"))
+    ));
+    let mut lines = vec![
+        "if ! command -v rustup >/dev/null 2>&1; then".to_string(),
+        "  curl --proto '=https' --tlsv1.2 -sSf https://sh.rustup.rs | sh -s -- -y --profile minimal --default-toolchain none".to_string(),
+        "  echo \"$HOME/.cargo/bin\" >> \"$GITHUB_PATH\"".to_string(),
+        "  export PATH=\"$HOME/.cargo/bin:$PATH\"".to_string(),
+        "fi".to_string(),
+    ];
+    lines.extend(rustup_install_lines(
+        input.toolchain,
+        &required_components(input.tests),
+    ));
+    for line in lines {
+        out.push_str(&format!("          {line}\n"));
+    }
+    out.push('\n');
+
+    if input.cache {
+        let uses = input.actions.resolve(CACHE_ACTION)?;
+        if let Some(step) = get_cache_step(
+            input.root,
+            input.tests,
+            input.toolchain,
+            uses,
+            input.strings,
+        ) {
+            step.write_to(&mut out, 3);
+            out.push('\n');
+        }
+    }
+
+    out.push_str(&format!(
+        "      - name: {}\n        run: bash .autograder/{}\n",
+        yaml_plain(input.strings.text("workflow.run_tests")),
+        SCRIPT_NAMES.grade
+    ));
+    Ok(out)
+}
@@ -0,0 +1,134 @@
+// .gitlab-ci.yml: one job on the official rust image that runs grade.sh.
+use super::{BackendInput, GENERATED_MARKER, rustup_install_lines};
+use crate::cli::build::build_functions::build_dirs;
+use crate::cli::build::required_components;
+use crate::utils::scripts::SCRIPT_NAMES;
+use crate::utils::yaml_quote;
+
+/// GitLab reads the job's coverage from the first capture group of this regex
+pub const COVERAGE_REGEX: &str = r"/Score: \d+\/\d+ \((\d+)%\)/";
+
+pub fn render_gitlab_ci(input: &BackendInput) -> String {
+    let mut out = format!(
+        "{GENERATED_MARKER} from autograder.json; re-run `build --target gitlab` instead of editing.\n\
+         stages:\n  - grade\n\nautograding:\n  stage: grade\n"
+    );
+
+    // Numbered releases have their own image tag; channels/dates are installed with rustup
+    let numbered = input.toolchain.starts_with(|c: char| c.is_ascii_digit());
+    let image = if numbered { input.toolchain } else { "latest" };
+    out.push_str(&format!("  image: rust:{image}\n"));
+
+    out.push_str("  variables:\n");
+    out.push_str("    # Full history for the commit and branch count checks\n");
+    out.push_str("    GIT_DEPTH: \"0\"\n");
+    if input.cache {
+        // GitLab only caches paths inside the project directory
+        out.push_str("    CARGO_HOME: \"$CI_PROJECT_DIR/.cargo\"\n");
+    }
+    if input.triggers.cancel_superseded {
+        out.push_str("  interruptible: true\n");
+    }
+    out.push_str(&get_rules(input));
+    if input.cache {
+        out.push_str(&get_cache(input));
+    }
+
+    let mut before = Vec::new();
+    let components = required_components(input.tests);
+    if numbered || input.toolchain == "stable" {
+        if !components.is_empty() {
+            before.push(format!("rustup component add {}", components.join(" ")));
+        }
+    } else {
+        before.extend(rustup_install_lines(input.toolchain, &components));
+    }
+    if !before.is_empty() {
+        out.push_str("  before_script:\n");
+        for line in before {
+            out.push_str(&format!("    - {line}\n"));
+        }
+    }
+
+    out.push_str(&format!(
+        "  script:\n    - bash .autograder/{}\n  coverage: '{}'\n",
+        SCRIPT_NAMES.grade, COVERAGE_REGEX
+    ));
+    out
+}
+
+/// Pipelines run on every push by default; rules are only needed to narrow pushes down.
+/// Scheduled and manual ("Run pipeline") sources are kept so they still grade.
+fn get_rules(input: &BackendInput) -> String {
+    let triggers = input.triggers;
+    let mut out = String::new();
+    if !triggers.schedule.is_empty() {
+        out.push_str("  # Schedules are created under Build > Pipeline schedules:\n");
+        for cron in &triggers.schedule {
+            out.push_str(&format!("  #   {cron}\n"));
+        }
+    }
+    if triggers.push_branches.is_empty() && triggers.push_paths.is_empty() {
+        return out;
+    }
+
+    let mut push = String::from("$CI_PIPELINE_SOURCE == \"push\"");
+    if !triggers.push_branches.is_empty() {
+        let branches: Vec<String> = triggers
+            .push_branches
+            .iter()
+            .map(|b| format!("$CI_COMMIT_BRANCH == \"{b}\""))
+            .collect();
+        push.push_str(&format!(" && ({})", branches.join(" || ")));
+    }
+
+    out.push_str("  rules:\n");
+    if !triggers.schedule.is_empty() {
+        out.push_str("    - if: '$CI_PIPELINE_SOURCE == \"schedule\"'\n");
+    }
+    if triggers.workflow_dispatch {
+        out.push_str("    - if: '$CI_PIPELINE_SOURCE == \"web\"'\n");
+    }
+    out.push_str(&format!("    - if: {}\n", yaml_quote(&push)));
+    if !triggers.push_paths.is_empty() {
+        out.push_str("      changes:\n");
+        for p in &triggers.push_paths {
+            out.push_str(&format!("        - {}\n", yaml_quote(p)));
+        }
+    }
+    out
+}
+
+/// Cache the registry and each build directory's target/, keyed on their lockfiles. GitLab hashes
+/// at most two files, so more lockfiles than that fall back to a key per toolchain.
+fn get_cache(input: &BackendInput) -> String {
+    let dirs = build_dirs(input.root, input.tests);
+    let mut out = String::from("  cache:\n");
+    if dirs.len() <= 2 {
+        out.push_str(&format!(
+            "    key:\n      prefix: cargo-{}\n      files:\n",
+            input.toolchain
+        ));
+        for dir in &dirs {
+            out.push_str(&format!("        - {dir}Cargo.lock\n"));
+        }
+    } else {
+        out.push_str(&format!(
+            "    # {} lockfiles; GitLab keys a cache on at most two files\n    key: cargo-{}\n",
+            dirs.len(),
+            input.toolchain
+        ));
+    }
+    out.push_str("    paths:\n");
+    for p in [
+        ".cargo/registry/index/",
+        ".cargo/registry/cache/",
+        ".cargo/git/db/",
+    ] {
+        out.push_str(&format!("      - {p}\n"));
+    }
+    for dir in &dirs {
+        out.push_str(&format!("      - {dir}target/\n"));
+    }
+    out
+}
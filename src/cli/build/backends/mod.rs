@@ -0,0 +1,84 @@
+// Non-GitHub build targets. Each one runs the same generated `.autograder/grade.sh`.
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
+
+use super::actions::ActionResolver;
+use super::create_and_write;
+use crate::types::AutoTest;
+use crate::types::config::Triggers;
+use crate::utils::i18n::Catalog;
+use crate::utils::make_executable;
+use crate::utils::scripts::SCRIPT_NAMES;
+
+mod forgejo;
+mod gitlab;
+mod shell;
+
+/// First line of every generated CI file; `reset` only deletes files that start with it
+pub const GENERATED_MARKER: &str = "# Generated by autograder-setup";
+pub const GITLAB_CI_FILE: &str = ".gitlab-ci.yml";
+pub const FORGEJO_WORKFLOW_FILE: &str = ".forgejo/workflows/autograding.yml";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+    /// GitHub Classroom workflow (.github/workflows/classroom.yml)
+    #[default]
+    Github,
+    /// GitLab CI pipeline (.gitlab-ci.yml)
+    Gitlab,
+    /// Forgejo / Gitea Actions workflow (.forgejo/workflows/autograding.yml)
+    Forgejo,
+    /// Only the standalone .autograder/grade.sh script
+    Shell,
+}
+
+/// Everything a backend needs, already resolved from flags and autograder.json settings
+pub struct BackendInput<'a> {
+    pub root: &'a Path,
+    pub tests: &'a [AutoTest],
+    pub triggers: &'a Triggers,
+    pub toolchain: &'a str,
+    pub cache: bool,
+    pub actions: &'a ActionResolver,
+    /// Step and workflow names
+    pub strings: &'a Catalog,
+}
+
+/// Write grade.sh plus the backend's CI file; returns the written paths
+pub fn write_backend(root: &Path, backend: Backend, input: &BackendInput) -> Result<Vec<PathBuf>> {
+    let script_path = root.join(".autograder").join(SCRIPT_NAMES.grade);
+    write_file(&script_path, &shell::render_grade_script(input.tests))?;
+    make_executable(&script_path)?;
+    let mut written = vec![script_path];
+
+    let ci_file = match backend {
+        Backend::Gitlab => Some((GITLAB_CI_FILE, gitlab::render_gitlab_ci(input))),
+        Backend::Forgejo => Some((FORGEJO_WORKFLOW_FILE, forgejo::render_workflow(input)?)),
+        Backend::Shell | Backend::Github => None,
+    };
+    if let Some((rel, contents)) = ci_file {
+        let path = root.join(rel);
+        write_file(&path, &contents)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Shell lines that install `toolchain` (plus components) with rustup, for runners without it
+fn rustup_install_lines(toolchain: &str, components: &[String]) -> Vec<String> {
+    let mut install = format!("rustup toolchain install {toolchain} --profile minimal");
+    for c in components {
+        install.push_str(&format!(" --component {c}"));
+    }
+    vec![install, format!("rustup default {toolchain}")]
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.to_string_lossy()))?;
+    }
+    create_and_write(path, contents)
+}
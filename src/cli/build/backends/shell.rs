@@ -0,0 +1,95 @@
+// Standalone grading script shared by every non-GitHub backend.
+use crate::cli::build::build_functions::{cargo_test_counts, shell_command};
+use crate::types::AutoTest;
+use crate::utils::shell_quote;
+
+const GRADE_SCRIPT_HEADER: &str = r#"#!/usr/bin/env bash
+# Generated by autograder-setup from autograder.json; re-run `build` instead of editing.
+#
+# Usage:
+#   bash .autograder/grade.sh
+#
+# Runs every graded test, prints a score and exits non-zero unless all points are earned.
+
+cd "$(dirname "$0")/.." || exit 1
+
+score=0
+max_score=0
+statuses=()
+names=()
+earned=()
+possible=()
+
+run_test() {
+  local name="$1" points="$2" minutes="$3" cmd="$4" status
+  max_score=$((max_score + points))
+  echo "=== ${name}"
+  if command -v timeout >/dev/null 2>&1; then
+    timeout "${minutes}m" bash -c "$cmd"
+  else
+    bash -c "$cmd"
+  fi
+  status=$?
+
+  names+=("$name")
+  possible+=("$points")
+  if [ "$status" -eq 0 ]; then
+    score=$((score + points))
+    statuses+=("PASS")
+    earned+=("$points")
+  else
+    # `timeout` exits with 124 when the limit is hit
+    if [ "$status" -eq 124 ]; then statuses+=("TIMEOUT"); else statuses+=("FAIL"); fi
+    earned+=(0)
+  fi
+}
+
+"#;
+
+const GRADE_SCRIPT_FOOTER: &str = r#"
+percent=0
+if [ "$max_score" -gt 0 ]; then
+  percent=$((score * 100 / max_score))
+fi
+
+echo
+for i in "${!names[@]}"; do
+  printf '%-8s %s (%s/%s)\n' "${statuses[$i]}" "${names[$i]}" "${earned[$i]}" "${possible[$i]}"
+done
+echo "Score: ${score}/${max_score} (${percent}%)"
+
+# Runners that support job summaries get a Markdown table as well
+if [ -n "${GITHUB_STEP_SUMMARY:-}" ]; then
+  {
+    echo "| Test | Result | Points |"
+    echo "| --- | --- | --- |"
+    for i in "${!names[@]}"; do
+      echo "| ${names[$i]} | ${statuses[$i]} | ${earned[$i]}/${possible[$i]} |"
+    done
+    echo
+    echo "**Score: ${score}/${max_score} (${percent}%)**"
+  } >> "$GITHUB_STEP_SUMMARY"
+fi
+
+[ "$score" -eq "$max_score" ]
+"#;
+
+/// Render `.autograder/grade.sh`: one `run_test` call per graded test, in autograder.json order
+pub fn render_grade_script(tests: &[AutoTest]) -> String {
+    let counts = cargo_test_counts(tests);
+    let mut out = String::from(GRADE_SCRIPT_HEADER);
+    for test in tests {
+        // Per-test env is exported inside the command so it can't leak into later tests
+        let cmd = shell_command(test, &counts);
+
+        out.push_str(&format!(
+            "run_test {} {} {} {}\n",
+            shell_quote(test.display_name()),
+            test.meta.points,
+            test.meta.timeout.max(1),
+            shell_quote(&cmd)
+        ));
+    }
+    out.push_str(GRADE_SCRIPT_FOOTER);
+    out
+}
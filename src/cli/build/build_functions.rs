@@ -1,51 +1,344 @@
+use super::actions::ResolvedAction;
+use super::steps::CacheStep;
+use crate::types::config::Triggers;
+use crate::types::{AutoTest, TestKind};
+use crate::utils::i18n::Catalog;
+use crate::utils::{
+    is_workspace_root, replace_double_hashtag, shell_quote, slug_id, yaml_plain, yaml_quote,
+};
 use anyhow::{Context, Result};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs::{create_dir_all, write};
-use std::path::Path;
-// Import all utility script constants
-use crate::utils::scripts::*;
-/// Generates the YAML preamble for the GitHub Actions workflow file.
-pub fn get_yaml_preamble(on_push: bool) -> String {
-    let mut triggers = vec!["repository_dispatch"];
+use std::path::{Path, PathBuf};
 
-    if on_push {
-        triggers.push("push");
-    }
-    let triggers_joined = triggers.join(", ");
+/// Job that runs the grading steps (single job) or the reporter (parallel jobs)
+pub const GRADING_JOB_ID: &str = "run-autograding-tests";
 
-    let preamble = format!(
-        r#"name: Autograding Tests
-on: [{}]
+// Import all utility script constants
+use crate::utils::scripts::*;
+/// Generates the YAML preamble (triggers, permissions, checkout) for the GitHub Actions workflow file.
+pub fn get_yaml_preamble(
+    triggers: &Triggers,
+    checkout: &ResolvedAction,
+    strings: &Catalog,
+) -> String {
+    let mut preamble = get_yaml_header(triggers, strings);
+    preamble.push_str(&get_yaml_job_header(GRADING_JOB_ID, &[], &BTreeMap::new()));
+    preamble.push_str(&get_checkout_step(checkout, strings));
+    preamble
+}
 
+/// Workflow name, triggers, permissions and concurrency, up to and including `jobs:`
+pub fn get_yaml_header(triggers: &Triggers, strings: &Catalog) -> String {
+    format!(
+        r#"name: {}
+{}
 permissions:
   checks: write
   actions: read
   contents: read
-
+{}
 jobs:
-  run-autograding-tests:
-    runs-on: ubuntu-latest
-    if: github.actor != 'github-classroom[bot]'
-    steps:
-      - name: Checkout code
-        uses: actions/checkout@v4
+"#,
+        yaml_plain(strings.text("workflow.name")),
+        get_yaml_triggers(triggers, Some("repository_dispatch")),
+        get_yaml_concurrency(triggers),
+    )
+}
+
+/// A job header. `needs` makes it an aggregation job, which must run even if a grading job fails.
+pub fn get_yaml_job_header(
+    job_id: &str,
+    needs: &[String],
+    outputs: &BTreeMap<String, String>,
+) -> String {
+    let mut out = format!("  {job_id}:\n    runs-on: ubuntu-latest\n");
+    if needs.is_empty() {
+        out.push_str("    if: github.actor != 'github-classroom[bot]'\n");
+    } else {
+        out.push_str(&format!("    needs: [{}]\n", needs.join(", ")));
+        out.push_str("    if: always() && github.actor != 'github-classroom[bot]'\n");
+    }
+    if !outputs.is_empty() {
+        out.push_str("    outputs:\n");
+        for (k, v) in outputs {
+            out.push_str(&format!("      {k}: {v}\n"));
+        }
+    }
+    out.push_str("    steps:\n");
+    out
+}
+
+pub fn get_checkout_step(checkout: &ResolvedAction, strings: &Catalog) -> String {
+    format!(
+        r#"      - name: {}
+        uses: {}
         with:
           # Checkout with fetch depth 0 to get a full git history for commit counting
           fetch-depth: 0
-    
-      - name: Install Rust toolchain
-        uses: dtolnay/rust-toolchain@stable
-        with:
-          components: clippy,rustfmt
 
 "#,
-        triggers_joined
-    );
+        yaml_plain(strings.text("workflow.checkout")),
+        checkout.render(false)
+    )
+}
 
-    preamble
+/// Renders the `on:` block. Stays in the compact list form unless filters or extra events are needed.
+/// `dispatch` is the always-on event (`repository_dispatch` on GitHub; None where unsupported).
+pub fn get_yaml_triggers(triggers: &Triggers, dispatch: Option<&str>) -> String {
+    if triggers.is_simple() {
+        let mut events: Vec<&str> = dispatch.into_iter().collect();
+        if triggers.on_push() {
+            events.push("push");
+        }
+        return format!("on: [{}]\n", events.join(", "));
+    }
+
+    let mut out = String::from("on:\n");
+    if let Some(event) = dispatch {
+        out.push_str(&format!("  {event}:\n"));
+    }
+    if triggers.workflow_dispatch {
+        out.push_str("  workflow_dispatch:\n");
+    }
+    if triggers.on_push() {
+        out.push_str("  push:\n");
+        for (key, values) in [
+            ("branches", &triggers.push_branches),
+            ("paths", &triggers.push_paths),
+        ] {
+            if values.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("    {key}:\n"));
+            for v in values {
+                out.push_str(&format!("      - {}\n", yaml_quote(v)));
+            }
+        }
+    }
+    if !triggers.schedule.is_empty() {
+        out.push_str("  schedule:\n");
+        for cron in &triggers.schedule {
+            out.push_str(&format!("    - cron: {}\n", yaml_quote(cron)));
+        }
+    }
+    out
+}
+
+/// One grading run per ref; a newer run cancels the one it supersedes.
+fn get_yaml_concurrency(triggers: &Triggers) -> String {
+    if !triggers.cancel_superseded {
+        return String::new();
+    }
+    r#"
+concurrency:
+  group: autograding-${{ github.ref }}
+  cancel-in-progress: true
+"#
+    .to_string()
+}
+
+/// GitHub cron syntax: exactly five whitespace-separated fields
+pub fn validate_cron(cron: &str) -> Result<()> {
+    let fields = cron.split_whitespace().count();
+    if fields != 5 {
+        anyhow::bail!(
+            "Invalid schedule `{}`: expected 5 cron fields (minute hour day month weekday), found {}",
+            cron,
+            fields
+        );
+    }
+    Ok(())
+}
+
+/// Build the cargo cache step for every manifest referenced in autograder.json.
+///
+/// Returns `None` when no test invokes cargo (e.g. only commit/branch checks).
+pub fn get_cache_step(
+    root: &Path,
+    tests: &[AutoTest],
+    toolchain: &str,
+    uses: ResolvedAction,
+    strings: &Catalog,
+) -> Option<CacheStep> {
+    if !tests.iter().any(|t| t.command().contains("cargo ")) {
+        return None;
+    }
+
+    let mut paths = vec![
+        "~/.cargo/registry/index/".to_string(),
+        "~/.cargo/registry/cache/".to_string(),
+        "~/.cargo/git/db/".to_string(),
+    ];
+    for dir in build_dirs(root, tests) {
+        paths.push(format!("{dir}target/"));
+    }
+    let mut hashed: Vec<String> = Vec::new();
+    for dir in manifest_dirs(tests) {
+        let lock = format!("'{}Cargo.lock'", build_dir(root, &dir));
+        if !hashed.contains(&lock) {
+            hashed.push(lock);
+        }
+        hashed.push(format!("'{dir}Cargo.toml'"));
+    }
+
+    let restore_key = format!("cargo-${{{{ runner.os }}}}-{toolchain}-");
+    let key = format!("{restore_key}${{{{ hashFiles({}) }}}}", hashed.join(", "));
+    Some(CacheStep {
+        name: strings.text("workflow.cache").to_string(),
+        uses,
+        paths,
+        key,
+        restore_key,
+    })
+}
+
+/// Directory of each manifest: "" for the root crate, "member/" for workspace members
+pub fn manifest_dirs(tests: &[AutoTest]) -> BTreeSet<String> {
+    tests
+        .iter()
+        .map(|t| match t.manifest_path().map(|p| p.trim()) {
+            Some(p) if !matches!(p, "" | "." | "Cargo.toml") => p
+                .strip_suffix("Cargo.toml")
+                .unwrap_or(p)
+                .trim_start_matches("./")
+                .to_string(),
+            _ => String::new(),
+        })
+        .collect()
+}
+
+/// Where cargo keeps `Cargo.lock` and `target/` for each manifest, in the same form as
+/// `manifest_dirs`
+pub fn build_dirs(root: &Path, tests: &[AutoTest]) -> BTreeSet<String> {
+    manifest_dirs(tests)
+        .iter()
+        .map(|dir| build_dir(root, dir))
+        .collect()
+}
+
+/// A workspace member builds in its workspace's root: the nearest directory up from `dir` (within
+/// `root`) whose Cargo.toml has `[workspace]`
+fn build_dir(root: &Path, dir: &str) -> String {
+    let mut current = PathBuf::from(dir);
+    loop {
+        if is_workspace_root(&root.join(&current).join("Cargo.toml")) {
+            return current
+                .components()
+                .map(|c| format!("{}/", c.as_os_str().to_string_lossy()))
+                .collect();
+        }
+        if !current.pop() {
+            return dir.to_string();
+        }
+    }
+}
+
+/// Cargo tests per manifest, used to fill the `##` baseline in test-count commands
+pub fn cargo_test_counts(tests: &[AutoTest]) -> HashMap<Option<String>, u32> {
+    let mut counts: HashMap<Option<String>, u32> = HashMap::new();
+    for t in tests {
+        if let TestKind::CargoTest { manifest_path } = &t.kind {
+            *counts.entry(manifest_path.clone()).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// A test whose step id isn't its slug because an earlier test already has that slug
+#[derive(Debug, PartialEq, Eq)]
+pub struct RenamedId {
+    pub test: String,
+    pub slug: String,
+    pub id: String,
+}
+
+/// The slug a test's step id starts from; `test` when its key has no letters or digits
+pub fn step_slug(test: &AutoTest) -> String {
+    match slug_id(test.stable_key()) {
+        s if s.is_empty() => "test".to_string(),
+        s => s,
+    }
+}
+
+/// Step ids (also the reporter's runner names), one per test in `tests` order. A test keeps the
+/// slug of its stable key unless an earlier test has it; then it gets the first `_2`, `_3`, ...
+/// suffix that no other test's slug or id uses, in config order.
+pub fn runner_ids(tests: &[AutoTest]) -> (Vec<String>, Vec<RenamedId>) {
+    let slugs: Vec<String> = tests.iter().map(step_slug).collect();
+    let mut taken: HashSet<String> = slugs.iter().cloned().collect();
+    let mut claimed: HashSet<&str> = HashSet::new();
+    let mut ids = Vec::with_capacity(tests.len());
+    let mut renamed = Vec::new();
+    for (test, slug) in tests.iter().zip(&slugs) {
+        let id = if claimed.insert(slug) {
+            slug.clone()
+        } else {
+            let id = (2..)
+                .map(|n| format!("{slug}_{n}"))
+                .find(|id| !taken.contains(id))
+                .expect("some suffix is free");
+            taken.insert(id.clone());
+            renamed.push(RenamedId {
+                test: test.meta.name.clone(),
+                slug: slug.clone(),
+                id: id.clone(),
+            });
+            id
+        };
+        ids.push(id);
+    }
+    (ids, renamed)
+}
+
+/// The exact command a test runs, shared by every backend
+pub fn graded_command(test: &AutoTest, counts: &HashMap<Option<String>, u32>) -> String {
+    match &test.kind {
+        TestKind::TestCount { manifest_path, .. } => {
+            let n = *counts.get(manifest_path).unwrap_or(&0);
+            replace_double_hashtag(&test.command(), n)
+        }
+        _ => test.command(),
+    }
+}
+
+/// `graded_command` with the test's env exported and its setup run first, for backends that run
+/// it through bash
+pub fn shell_command(test: &AutoTest, counts: &HashMap<Option<String>, u32>) -> String {
+    let mut cmd = String::new();
+    for (key, value) in &test.options.env {
+        cmd.push_str(&format!("export {}={}; ", key, shell_quote(value)));
+    }
+    if let Some(setup) = test
+        .options
+        .setup
+        .as_deref()
+        .filter(|s| !s.trim().is_empty())
+    {
+        cmd.push_str(&format!(
+            "{{ {setup}; }} && {{ {}; }}",
+            graded_command(test, counts)
+        ));
+        return cmd;
+    }
+    cmd.push_str(&graded_command(test, counts));
+    cmd
+}
+
+/// Write the helper scripts that commit/branch count commands call into
+pub fn write_support_scripts(root: &Path, tests: &[AutoTest]) -> Result<()> {
+    for test in tests {
+        match &test.kind {
+            TestKind::CommitCount { .. } => write_commit_count_shell(root)?,
+            TestKind::BranchCount { .. } => write_branch_count_shell(root)?,
+            _ => {}
+        }
+    }
+    Ok(())
 }
 
 /// Shared function to write any script to the .autograder directory
-fn write_script(root: &Path, script_name: &str, contents: &str) -> Result<()> {
+pub fn write_script(root: &Path, script_name: &str, contents: &str) -> Result<()> {
     let script_path = root.join(".autograder").join(script_name);
 
     // Bail early if the script already exists
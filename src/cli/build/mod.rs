@@ -1,19 +1,77 @@
 use anyhow::{Context, Result};
 use std::path::{Path, PathBuf};
 
+use super::BuildConfig;
+use crate::cli::validate;
+use crate::types::config::{Settings, Triggers};
 use crate::types::{AutoTest, TestKind};
-use crate::utils::{read_autograder_config, replace_double_hashtag, slug_id};
+use crate::utils::i18n::Catalog;
+use crate::utils::read_autograder_config;
+use crate::utils::toolchain::{DEFAULT_TOOLCHAIN, detect_toolchain, is_action_revision};
 
-use build_functions::{get_yaml_preamble, write_branch_count_shell, write_commit_count_shell};
-use std::collections::{BTreeMap, HashMap};
+use actions::{
+    ActionResolver, CACHE_ACTION, CHECKOUT_ACTION, COMMAND_GRADER_ACTION, IO_GRADER_ACTION,
+    REPORTER_ACTION, TOOLCHAIN_ACTION,
+};
+use backends::{Backend, BackendInput, write_backend};
+use build_functions::{
+    GRADING_JOB_ID, RenamedId, cargo_test_counts, get_cache_step, get_checkout_step,
+    get_yaml_header, get_yaml_job_header, get_yaml_preamble, graded_command, runner_ids,
+    validate_cron, write_support_scripts,
+};
+use partition::{Partition, partition_tests};
+use std::collections::BTreeMap;
 use std::fs::{File, create_dir_all};
-use steps::{CommandStep, CommandWith, ReporterStep};
+use steps::{CommandStep, CommandWith, IoWith, ReporterStep, ToolchainStep};
 
-mod build_functions;
+pub mod actions;
+pub mod backends;
+pub mod build_functions;
+pub mod partition;
 mod steps;
 
-pub fn run(root: &Path, grade_on_push: bool) -> Result<()> {
-    let tests = read_autograder_config(root)?;
+pub fn run(cfg: &BuildConfig) -> Result<()> {
+    let root = cfg.root.as_path();
+    let report = validate::check(root)?;
+    print!("{}", report);
+    if report.errors() > 0 {
+        anyhow::bail!(
+            "Not building: {} has {} error(s)",
+            report.path.to_string_lossy(),
+            report.errors()
+        );
+    }
+    let config = read_autograder_config(root)?;
+    if let Some(expected) = config.settings.total_points {
+        let actual = config.total_points();
+        if actual != expected {
+            println!(
+                "Warning: tests add up to {} points, but settings.total_points is {}",
+                actual, expected
+            );
+        }
+    }
+    let actions = ActionResolver::new(
+        cfg.pin_actions || config.settings.pin_actions,
+        cfg.action_mirror
+            .clone()
+            .or_else(|| config.settings.action_mirror.clone()),
+        &config.settings,
+    );
+    let triggers = resolve_triggers(cfg, &config.settings)?;
+    let toolchain = resolve_toolchain(cfg.toolchain.as_ref(), &config.settings, root)?;
+    let strings = Catalog::for_project(
+        root,
+        &config.settings,
+        cfg.lang.as_deref(),
+        cfg.translations.as_deref(),
+    )?;
+    let tests = apply_test_defaults(config.tests, &config.settings)?;
+
+    if cfg.target != Backend::Github {
+        return run_backend(cfg, triggers, &toolchain, &tests, &actions, &strings);
+    }
+
     let workflows_dir = root.join(".github").join("workflows");
     create_dir_all(&workflows_dir)
         .with_context(|| format!("Failed to create {}", workflows_dir.to_string_lossy()))?;
@@ -22,14 +80,22 @@ pub fn run(root: &Path, grade_on_push: bool) -> Result<()> {
     let workflow_path = workflows_dir.join("classroom.yml");
 
     let mut yaml_compiler = YAMLAutograder::new(root.to_path_buf());
-    yaml_compiler.set_preamble(get_yaml_preamble(grade_on_push));
+    yaml_compiler.set_triggers(triggers);
+    yaml_compiler.set_actions(actions);
+    yaml_compiler.set_jobs(cfg.jobs, cfg.partition);
+    yaml_compiler.set_toolchain(toolchain);
+    yaml_compiler.set_cache(cfg.cache);
+    yaml_compiler.set_strings(strings);
     yaml_compiler.set_tests(tests);
-    let workflow_content = yaml_compiler.compile();
+    let workflow_content = yaml_compiler.compile()?;
+    for r in yaml_compiler.renamed() {
+        println!(
+            "Note: test `{}` has step id `{}` because `{}` is already taken",
+            r.test, r.id, r.slug
+        );
+    }
 
-    create_and_write(
-        &workflow_path,
-        &workflow_content.expect("Unable to compile YAML"),
-    )?;
+    create_and_write(&workflow_path, &workflow_content)?;
     println!(
         "Wrote Configured autograder YAML to {}",
         workflow_path.to_string_lossy()
@@ -37,6 +103,97 @@ pub fn run(root: &Path, grade_on_push: bool) -> Result<()> {
     Ok(())
 }
 
+/// GitLab, Forgejo and shell targets all grade through `.autograder/grade.sh`
+fn run_backend(
+    cfg: &BuildConfig,
+    mut triggers: Triggers,
+    toolchain: &str,
+    tests: &[AutoTest],
+    actions: &ActionResolver,
+    strings: &Catalog,
+) -> Result<()> {
+    if cfg.jobs > 1 {
+        anyhow::bail!("--jobs is only supported with --target github");
+    }
+    // Without GitHub Classroom nothing sends `repository_dispatch`, so pushes are always graded
+    triggers.grade_on_push = true;
+
+    let graded: Vec<AutoTest> = tests
+        .iter()
+        .filter(|t| t.meta.points > 0)
+        .cloned()
+        .collect();
+    write_support_scripts(&cfg.root, &graded)?;
+    let input = BackendInput {
+        root: &cfg.root,
+        tests: &graded,
+        triggers: &triggers,
+        toolchain,
+        cache: cfg.cache,
+        actions,
+        strings,
+    };
+    for path in write_backend(&cfg.root, cfg.target, &input)? {
+        println!("Wrote {}", path.to_string_lossy());
+    }
+    Ok(())
+}
+
+/// Precedence: `--toolchain` flag, then autograder.json settings, then rust-toolchain file, then stable
+pub fn resolve_toolchain(
+    flag: Option<&String>,
+    settings: &Settings,
+    root: &Path,
+) -> Result<String> {
+    if let Some(t) = flag.or(settings.toolchain.as_ref()) {
+        return Ok(t.clone());
+    }
+    Ok(detect_toolchain(root)?.unwrap_or_else(|| DEFAULT_TOOLCHAIN.to_string()))
+}
+
+/// Flags are layered over `settings.triggers` so a config can hold the course-wide defaults
+fn resolve_triggers(cfg: &BuildConfig, settings: &Settings) -> Result<Triggers> {
+    let triggers = settings.triggers.merged_with(&cfg.triggers);
+    for cron in &triggers.schedule {
+        validate_cron(cron)?;
+    }
+    Ok(triggers)
+}
+
+/// Timeout (minutes) for tests without their own, when `settings.default_timeout` is unset
+pub const DEFAULT_TIMEOUT: u64 = 10;
+
+/// Fold `settings.defaults` and `settings.default_timeout` into each test and fill in its name and
+/// description templates, so commands and steps see the effective values
+pub fn apply_test_defaults(tests: Vec<AutoTest>, settings: &Settings) -> Result<Vec<AutoTest>> {
+    tests
+        .into_iter()
+        .map(|mut t| {
+            t.options = t.options.with_defaults(&settings.defaults);
+            if t.meta.timeout == 0 {
+                t.meta.timeout = settings.default_timeout.unwrap_or(DEFAULT_TIMEOUT);
+            }
+            t.render_templates()
+        })
+        .collect()
+}
+
+/// Only install the rustup components that some test command actually invokes
+pub fn required_components(tests: &[AutoTest]) -> Vec<String> {
+    let commands: Vec<String> = tests.iter().map(|t| t.command()).collect();
+    let mut components = Vec::new();
+    if commands.iter().any(|c| c.contains("cargo clippy")) {
+        components.push("clippy".to_string());
+    }
+    if commands
+        .iter()
+        .any(|c| c.contains("cargo fmt") || c.contains("rustfmt"))
+    {
+        components.push("rustfmt".to_string());
+    }
+    components
+}
+
 pub fn create_and_write(path: &Path, content: &str) -> Result<()> {
     let mut f = File::create(path)
         .with_context(|| format!("Failed to create {}", path.to_string_lossy()))?;
@@ -46,113 +203,293 @@ pub fn create_and_write(path: &Path, content: &str) -> Result<()> {
     Ok(())
 }
 
+/// A graded step's id and, with parallel jobs, the job that produces its result
+struct Runner {
+    id: String,
+    job: Option<String>,
+}
+
 pub struct YAMLAutograder {
-    pub preamble: String,
     pub autograder_content: String,
+    triggers: Triggers,
+    toolchain: String,
+    cache: bool,
+    actions: ActionResolver,
+    jobs: usize,
+    partition: Partition,
     tests: Vec<AutoTest>,
+    runners: Vec<Runner>,
+    /// Step id per test, in `tests` order and unique across the workflow
     ids: Vec<String>,
+    renamed: Vec<RenamedId>,
     root: PathBuf,
+    strings: Catalog,
 }
 impl YAMLAutograder {
     fn new(root: PathBuf) -> Self {
         Self {
-            preamble: String::new(),
             autograder_content: String::new(),
+            triggers: Triggers::default(),
+            toolchain: DEFAULT_TOOLCHAIN.to_string(),
+            cache: false,
+            actions: ActionResolver::default(),
+            jobs: 1,
+            partition: Partition::default(),
             tests: Vec::new(),
+            runners: Vec::new(),
             ids: Vec::new(),
+            renamed: Vec::new(),
             root,
+            strings: Catalog::english(),
         }
     }
 
-    fn set_preamble(&mut self, preamble: String) {
-        self.preamble = preamble;
+    fn set_triggers(&mut self, triggers: Triggers) {
+        self.triggers = triggers;
+    }
+
+    fn set_toolchain(&mut self, toolchain: String) {
+        self.toolchain = toolchain;
+    }
+
+    fn set_cache(&mut self, cache: bool) {
+        self.cache = cache;
+    }
+
+    fn set_strings(&mut self, strings: Catalog) {
+        self.strings = strings;
+    }
+
+    fn set_actions(&mut self, actions: ActionResolver) {
+        self.actions = actions;
+    }
+
+    fn set_jobs(&mut self, jobs: usize, partition: Partition) {
+        self.jobs = jobs.max(1);
+        self.partition = partition;
     }
 
     fn set_tests(&mut self, tests: Vec<AutoTest>) {
         self.tests = tests.into_iter().filter(|t| t.meta.points > 0).collect();
-        self.ids = Vec::with_capacity(self.tests.len());
+        self.runners = Vec::with_capacity(self.tests.len());
+        (self.ids, self.renamed) = runner_ids(&self.tests);
     }
 
-    fn compile_test_step(&mut self, test: &AutoTest, cmd: &str) {
-        let name = test.meta.name.trim().to_string();
-        let id = slug_id(&name);
-        self.ids.push(id.clone());
+    /// Tests whose step id got a suffix to stay unique
+    fn renamed(&self) -> &[RenamedId] {
+        &self.renamed
+    }
+
+    fn compile_test_step(
+        &mut self,
+        test: &AutoTest,
+        id: String,
+        cmd: &str,
+        job: Option<&str>,
+    ) -> Result<()> {
+        let name = test.display_name().to_string();
+        self.runners.push(Runner {
+            id: id.clone(),
+            job: job.map(str::to_string),
+        });
+
+        // IO tests go to Classroom's IO grader, which does the comparison itself
+        let (action, command, io) = match &test.kind {
+            TestKind::Io {
+                command,
+                input,
+                expected_output,
+                comparison,
+            } => (
+                IO_GRADER_ACTION,
+                command.clone(),
+                Some(IoWith {
+                    input: input.clone(),
+                    expected_output: expected_output.clone(),
+                    comparison_method: comparison.as_str().to_string(),
+                }),
+            ),
+            _ => (COMMAND_GRADER_ACTION, cmd.to_string(), None),
+        };
 
         let step = CommandStep {
             name: name.clone(),
             id,
-            uses: "classroom-resources/autograding-command-grader@v1".into(),
+            uses: self.actions.resolve(action)?,
+            env: test.options.env.clone(),
             with: CommandWith {
                 test_name: name,
-                setup_command: "".into(),
-                command: cmd.into(),
+                setup_command: test.options.setup.clone().unwrap_or_default(),
+                command,
                 timeout: test.meta.timeout,
                 max_score: test.meta.points,
             },
+            io,
         };
 
         // write it at the same indent (3) as before
         step.write_to(&mut self.autograder_content, 3);
         self.autograder_content.push('\n');
+        Ok(())
     }
 
-    fn compile_test_steps(&mut self) -> anyhow::Result<()> {
-        let tests = self.tests.clone();
+    /// Branch-style revisions go in the ref; everything else needs the `toolchain` input
+    fn toolchain_action(&self) -> (String, Option<String>) {
+        if is_action_revision(&self.toolchain) {
+            (format!("{TOOLCHAIN_ACTION}@{}", self.toolchain), None)
+        } else {
+            (
+                format!("{TOOLCHAIN_ACTION}@master"),
+                Some(self.toolchain.clone()),
+            )
+        }
+    }
 
-        // Count cargo tests per manifest
-        let mut counts_by_manifest: HashMap<Option<String>, u32> = HashMap::new();
-        for t in &tests {
-            if let TestKind::CargoTest { manifest_path } = &t.kind {
-                *counts_by_manifest.entry(manifest_path.clone()).or_insert(0) += 1;
-            }
+    /// Every `uses:` the workflow will have, before pinning and mirroring
+    fn actions_used(&self) -> Vec<String> {
+        let mut actions = vec![CHECKOUT_ACTION.to_string(), self.toolchain_action().0];
+        if self.cache {
+            actions.push(CACHE_ACTION.to_string());
+        }
+        let io = self
+            .tests
+            .iter()
+            .filter(|t| matches!(t.kind, TestKind::Io { .. }))
+            .count();
+        if io < self.tests.len() {
+            actions.push(COMMAND_GRADER_ACTION.to_string());
         }
+        if io > 0 {
+            actions.push(IO_GRADER_ACTION.to_string());
+        }
+        actions.push(REPORTER_ACTION.to_string());
+        actions
+    }
 
-        for test in &tests {
-            match &test.kind {
-                TestKind::TestCount { manifest_path, .. } => {
-                    let base = test.command();
-                    let n = *counts_by_manifest.get(manifest_path).unwrap_or(&0);
-                    self.compile_test_step(test, &replace_double_hashtag(&base, n));
-                }
-                TestKind::CommitCount { .. } => {
-                    write_commit_count_shell(&self.root)?;
-                    self.compile_test_step(test, &test.command());
-                }
-                TestKind::BranchCount { .. } => {
-                    write_branch_count_shell(&self.root)?;
-                    self.compile_test_step(test, &test.command());
-                }
-                _ => self.compile_test_step(test, &test.command()),
-            }
+    fn compile_toolchain_step(&mut self, tests: &[AutoTest]) -> Result<()> {
+        let (action, toolchain) = self.toolchain_action();
+        let step = ToolchainStep {
+            name: self.strings.text("workflow.toolchain").to_string(),
+            uses: self.actions.resolve(&action)?,
+            toolchain,
+            components: required_components(tests),
+        };
+        step.write_to(&mut self.autograder_content, 3);
+        self.autograder_content.push('\n');
+        Ok(())
+    }
+
+    /// Cache goes after the toolchain install so the key can include it, and before any cargo step
+    fn compile_cache_step(&mut self, tests: &[AutoTest]) -> Result<()> {
+        if !self.cache {
+            return Ok(());
+        }
+        let uses = self.actions.resolve(CACHE_ACTION)?;
+        if let Some(step) = get_cache_step(&self.root, tests, &self.toolchain, uses, &self.strings)
+        {
+            step.write_to(&mut self.autograder_content, 3);
+            self.autograder_content.push('\n');
+        }
+        Ok(())
+    }
+
+    /// `ids` holds each test's step id, in `tests` order
+    fn compile_test_steps(
+        &mut self,
+        tests: &[AutoTest],
+        ids: &[String],
+        job: Option<&str>,
+    ) -> Result<()> {
+        // Count cargo tests per manifest across the whole assignment, not just this job
+        let counts = cargo_test_counts(&self.tests);
+        write_support_scripts(&self.root, tests)?;
+        for (test, id) in tests.iter().zip(ids) {
+            self.compile_test_step(test, id.clone(), &graded_command(test, &counts), job)?;
         }
         Ok(())
     }
 
-    fn compile_test_reporter(&mut self) {
+    fn compile_test_reporter(&mut self) -> Result<()> {
         let mut env = BTreeMap::new(); // stable order for clean diffs
-        for id in &self.ids {
-            env.insert(
-                format!("{}_RESULTS", id.to_uppercase()),
-                format!("${{{{steps.{id}.outputs.result}}}}"),
-            );
+        for Runner { id, job } in &self.runners {
+            let value = match job {
+                Some(job) => format!("${{{{needs.{job}.outputs.{id}}}}}"),
+                None => format!("${{{{steps.{id}.outputs.result}}}}"),
+            };
+            env.insert(format!("{}_RESULTS", id.to_uppercase()), value);
         }
-        let runners_csv = self.ids.join(",");
+        let ids: Vec<&str> = self.runners.iter().map(|r| r.id.as_str()).collect();
+        let runners_csv = ids.join(",");
 
         let reporter = ReporterStep {
-            name: "Autograding Reporter".into(),
-            uses: "classroom-resources/autograding-grading-reporter@v1".into(),
+            name: self.strings.text("workflow.reporter").to_string(),
+            uses: self.actions.resolve(REPORTER_ACTION)?,
             env,
             runners_csv,
         };
 
         reporter.write_to(&mut self.autograder_content, 3);
+        Ok(())
+    }
+
+    /// One grading job per partition, each exporting its step results as job outputs
+    fn compile_grading_job(
+        &mut self,
+        job_id: &str,
+        tests: &[AutoTest],
+        ids: &[String],
+    ) -> Result<()> {
+        let outputs: BTreeMap<String, String> = ids
+            .iter()
+            .map(|id| (id.clone(), format!("${{{{ steps.{id}.outputs.result }}}}")))
+            .collect();
+
+        self.autograder_content
+            .push_str(&get_yaml_job_header(job_id, &[], &outputs));
+        self.autograder_content.push_str(&get_checkout_step(
+            &self.actions.resolve(CHECKOUT_ACTION)?,
+            &self.strings,
+        ));
+        self.compile_toolchain_step(tests)?;
+        self.compile_cache_step(tests)?;
+        self.compile_test_steps(tests, ids, Some(job_id))
     }
 
     fn compile(&mut self) -> Result<String> {
+        self.actions.check_pins(&self.actions_used())?;
         self.autograder_content.clear();
-        self.autograder_content.push_str(&self.preamble);
-        self.compile_test_steps()?;
-        self.compile_test_reporter();
+        let tests = self.tests.clone();
+        let ids = self.ids.clone();
+        let partitions = partition_tests(&tests, self.jobs, self.partition);
+
+        if partitions.len() <= 1 {
+            let checkout = self.actions.resolve(CHECKOUT_ACTION)?;
+            self.autograder_content.push_str(&get_yaml_preamble(
+                &self.triggers,
+                &checkout,
+                &self.strings,
+            ));
+            self.compile_toolchain_step(&tests)?;
+            self.compile_cache_step(&tests)?;
+            self.compile_test_steps(&tests, &ids, None)?;
+        } else {
+            self.autograder_content
+                .push_str(&get_yaml_header(&self.triggers, &self.strings));
+            let mut job_ids = Vec::with_capacity(partitions.len());
+            for (i, part) in partitions.iter().enumerate() {
+                let job_id = format!("grade-{}", i + 1);
+                let part_tests: Vec<AutoTest> = part.iter().map(|&t| tests[t].clone()).collect();
+                let part_ids: Vec<String> = part.iter().map(|&t| ids[t].clone()).collect();
+                self.compile_grading_job(&job_id, &part_tests, &part_ids)?;
+                job_ids.push(job_id);
+            }
+            self.autograder_content.push_str(&get_yaml_job_header(
+                GRADING_JOB_ID,
+                &job_ids,
+                &BTreeMap::new(),
+            ));
+        }
+        self.compile_test_reporter()?;
         Ok(self.autograder_content.to_string())
     }
 }
@@ -0,0 +1,63 @@
+// Splitting tests across parallel grading jobs.
+use crate::types::AutoTest;
+use clap::ValueEnum;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Partition {
+    /// Keep each manifest's tests together (one crate per job where possible)
+    Manifest,
+    /// Keep tests sharing a `meta.group` together
+    Group,
+    /// Spread tests so every job has a similar total timeout
+    #[default]
+    Balanced,
+}
+
+/// Split `tests` into at most `jobs` non-empty partitions, as indices into `tests`.
+///
+/// Tests are first bucketed by the strategy key, then buckets are packed largest-first onto the
+/// job with the smallest total timeout. Within a job, tests keep their autograder.json order.
+pub fn partition_tests(tests: &[AutoTest], jobs: usize, strategy: Partition) -> Vec<Vec<usize>> {
+    let jobs = jobs.max(1);
+
+    // Bucket key -> indices into `tests`, in first-seen order for deterministic output
+    let mut keys: Vec<String> = Vec::new();
+    let mut buckets: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (i, t) in tests.iter().enumerate() {
+        let key = match strategy {
+            Partition::Manifest => t.manifest_path().unwrap_or("Cargo.toml").to_string(),
+            Partition::Group => t.meta.group.clone().unwrap_or_default(),
+            Partition::Balanced => i.to_string(),
+        };
+        if !buckets.contains_key(&key) {
+            keys.push(key.clone());
+        }
+        buckets.entry(key).or_default().push(i);
+    }
+
+    let weight = |idx: &[usize]| -> u64 { idx.iter().map(|&i| tests[i].meta.timeout.max(1)).sum() };
+    let mut ordered: Vec<Vec<usize>> = keys.iter().map(|k| buckets[k].clone()).collect();
+    // Stable sort keeps first-seen order among equal weights
+    ordered.sort_by_key(|b| std::cmp::Reverse(weight(b)));
+
+    let mut assigned: Vec<Vec<usize>> = vec![Vec::new(); jobs.min(ordered.len())];
+    let mut loads: Vec<u64> = vec![0; assigned.len()];
+    for bucket in ordered {
+        let (slot, _) = loads
+            .iter()
+            .enumerate()
+            .min_by_key(|(i, load)| (**load, *i))
+            .expect("at least one job");
+        loads[slot] += weight(&bucket);
+        assigned[slot].extend(bucket);
+    }
+
+    assigned
+        .into_iter()
+        .map(|mut idx| {
+            idx.sort_unstable();
+            idx
+        })
+        .collect()
+}
@@ -1,5 +1,6 @@
 // Minimal, serde-free emitters for GitHub Classroom steps.
-use crate::utils::{YAML_INDENT, yaml_quote};
+use super::actions::ResolvedAction;
+use crate::utils::{YAML_INDENT, yaml_plain, yaml_quote};
 use std::collections::BTreeMap; // stable key order in YAML env
 
 pub struct CommandWith {
@@ -10,20 +11,44 @@ pub struct CommandWith {
     pub max_score: u32,
 }
 
+/// Extra inputs for Classroom's IO grader (`io` tests)
+pub struct IoWith {
+    pub input: String,
+    pub expected_output: String,
+    pub comparison_method: String, // "included" | "exact" | "regex"
+}
+
 pub struct CommandStep {
     pub name: String,
     pub id: String,
-    pub uses: String, // e.g., "classroom-resources/autograding-command-grader@v1"
+    pub uses: ResolvedAction, // e.g., "classroom-resources/autograding-command-grader@v1"
+    pub env: BTreeMap<String, String>,
     pub with: CommandWith,
+    pub io: Option<IoWith>,
 }
 
 pub struct ReporterStep {
-    pub name: String, // "Autograding Reporter"
-    pub uses: String, // "classroom-resources/autograding-grading-reporter@v1"
+    pub name: String,         // "Autograding Reporter"
+    pub uses: ResolvedAction, // "classroom-resources/autograding-grading-reporter@v1"
     pub env: BTreeMap<String, String>,
     pub runners_csv: String, // "id-a,id-b,id-c"
 }
 
+pub struct ToolchainStep {
+    pub name: String,              // "Install Rust toolchain"
+    pub uses: ResolvedAction,      // e.g., "dtolnay/rust-toolchain@1.82.0"
+    pub toolchain: Option<String>, // `toolchain:` input, for toolchains without their own ref
+    pub components: Vec<String>,   // e.g., ["clippy"]
+}
+
+pub struct CacheStep {
+    pub name: String,         // "Cache cargo registry and build output"
+    pub uses: ResolvedAction, // "actions/cache@v4"
+    pub paths: Vec<String>,   // e.g., ["~/.cargo/registry/index/", "target/"]
+    pub key: String,          // full cache key, including the hashFiles(...) expression
+    pub restore_key: String,  // key prefix used as a fallback
+}
+
 // --------- helpers ---------
 fn indent(s: &mut String, level: usize, line: impl AsRef<str>) {
     for _ in 0..level {
@@ -50,8 +75,14 @@ impl CommandStep {
         indent(
             buf,
             indent_level + 1,
-            format!("uses: {}", yaml_quote(&self.uses)),
+            format!("uses: {}", self.uses.render(true)),
         );
+        if !self.env.is_empty() {
+            indent(buf, indent_level + 1, "env:");
+            for (k, v) in &self.env {
+                indent(buf, indent_level + 2, format!("{k}: {}", yaml_quote(v)));
+            }
+        }
         indent(buf, indent_level + 1, "with:");
         indent(
             buf,
@@ -68,6 +99,23 @@ impl CommandStep {
             indent_level + 2,
             format!("command: {}", yaml_quote(&self.with.command)),
         );
+        if let Some(io) = &self.io {
+            indent(
+                buf,
+                indent_level + 2,
+                format!("input: {}", yaml_quote(&io.input)),
+            );
+            indent(
+                buf,
+                indent_level + 2,
+                format!("expected-output: {}", yaml_quote(&io.expected_output)),
+            );
+            indent(
+                buf,
+                indent_level + 2,
+                format!("comparison-method: {}", io.comparison_method),
+            );
+        }
         indent(
             buf,
             indent_level + 2,
@@ -83,8 +131,16 @@ impl CommandStep {
 
 impl ReporterStep {
     pub fn write_to(&self, buf: &mut String, indent_level: usize) {
-        indent(buf, indent_level, format!("- name: {}", self.name));
-        indent(buf, indent_level + 1, format!("uses: {}", self.uses));
+        indent(
+            buf,
+            indent_level,
+            format!("- name: {}", yaml_plain(&self.name)),
+        );
+        indent(
+            buf,
+            indent_level + 1,
+            format!("uses: {}", self.uses.render(false)),
+        );
         indent(buf, indent_level + 1, "env:");
         for (k, v) in &self.env {
             // env values in reporter are already ${{steps.id.outputs.result}}
@@ -98,3 +154,60 @@ impl ReporterStep {
         );
     }
 }
+
+impl ToolchainStep {
+    pub fn write_to(&self, buf: &mut String, indent_level: usize) {
+        indent(
+            buf,
+            indent_level,
+            format!("- name: {}", yaml_plain(&self.name)),
+        );
+        indent(
+            buf,
+            indent_level + 1,
+            format!("uses: {}", self.uses.render(false)),
+        );
+
+        if self.toolchain.is_none() && self.components.is_empty() {
+            return;
+        }
+        indent(buf, indent_level + 1, "with:");
+        if let Some(toolchain) = &self.toolchain {
+            indent(
+                buf,
+                indent_level + 2,
+                format!("toolchain: {}", yaml_quote(toolchain)),
+            );
+        }
+        if !self.components.is_empty() {
+            indent(
+                buf,
+                indent_level + 2,
+                format!("components: {}", self.components.join(",")),
+            );
+        }
+    }
+}
+
+impl CacheStep {
+    pub fn write_to(&self, buf: &mut String, indent_level: usize) {
+        indent(
+            buf,
+            indent_level,
+            format!("- name: {}", yaml_plain(&self.name)),
+        );
+        indent(
+            buf,
+            indent_level + 1,
+            format!("uses: {}", self.uses.render(false)),
+        );
+        indent(buf, indent_level + 1, "with:");
+        indent(buf, indent_level + 2, "path: |");
+        for p in &self.paths {
+            indent(buf, indent_level + 3, p);
+        }
+        indent(buf, indent_level + 2, format!("key: {}", self.key));
+        indent(buf, indent_level + 2, "restore-keys: |");
+        indent(buf, indent_level + 3, &self.restore_key);
+    }
+}
@@ -1,6 +1,53 @@
 use super::*;
 use crate::types::*;
 
+fn push_cfg(root: &Path) -> BuildConfig {
+    BuildConfig {
+        root: root.to_path_buf(),
+        triggers: crate::types::config::Triggers {
+            grade_on_push: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn write_config(root: &Path, tests: &[AutoTest]) -> anyhow::Result<()> {
+    let autograder = root.join(".autograder");
+    std::fs::create_dir_all(&autograder)?;
+    std::fs::write(
+        autograder.join("autograder.json"),
+        serde_json::to_string_pretty(tests)?,
+    )?;
+    Ok(())
+}
+
+/// `build` validates first, so manifests named by tests must exist
+fn write_member_manifest(root: &Path) -> anyhow::Result<()> {
+    std::fs::create_dir_all(root.join("member"))?;
+    std::fs::write(
+        root.join("member/Cargo.toml"),
+        "[package]\nname = \"member\"\nversion = \"0.1.0\"\n",
+    )?;
+    Ok(())
+}
+
+fn cargo_test(name: &str) -> AutoTest {
+    AutoTest {
+        meta: TestMeta {
+            name: name.into(),
+            timeout: 10,
+            points: 1,
+            description: "".into(),
+            ..Default::default()
+        },
+        kind: TestKind::CargoTest {
+            manifest_path: None,
+        },
+        options: TestOptions::default(),
+    }
+}
+
 // Ensures a plain cargo test emits the expected step with quoted fields and no -- --exact
 #[test]
 fn yaml_includes_basic_cargo_test_step() -> anyhow::Result<()> {
@@ -14,10 +61,12 @@ fn yaml_includes_basic_cargo_test_step() -> anyhow::Result<()> {
             timeout: 10,
             points: 1,
             description: "".into(),
+            ..Default::default()
         },
         kind: TestKind::CargoTest {
             manifest_path: None,
         },
+        options: TestOptions::default(),
     }];
     // helper identical to your other tests
     let autograder = root.join(".autograder");
@@ -28,12 +77,12 @@ fn yaml_includes_basic_cargo_test_step() -> anyhow::Result<()> {
     )?;
 
     // Act
-    run(root, true)?;
+    run(&push_cfg(root))?;
     let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
 
     // Assert (quoted fields, no -- --exact)
     assert!(yaml.contains(r#"- name: "basic_add_small_numbers""#));
-    assert!(yaml.contains(r#"id: "basic-add-small-numbers""#));
+    assert!(yaml.contains(r#"id: "basic_add_small_numbers""#));
     assert!(yaml.contains(r#"uses: "classroom-resources/autograding-command-grader@v1""#));
     assert!(yaml.contains(r#"test-name: "basic_add_small_numbers""#));
     assert!(yaml.contains(r#"command: "cargo test basic_add_small_numbers""#));
@@ -55,10 +104,12 @@ fn yaml_orders_manifest_flag_after_name() -> anyhow::Result<()> {
             timeout: 10,
             points: 1,
             description: "".into(),
+            ..Default::default()
         },
         kind: TestKind::CargoTest {
             manifest_path: Some("member/Cargo.toml".into()),
         },
+        options: TestOptions::default(),
     }];
     let autograder = root.join(".autograder");
     std::fs::create_dir_all(&autograder)?;
@@ -66,8 +117,9 @@ fn yaml_orders_manifest_flag_after_name() -> anyhow::Result<()> {
         autograder.join("autograder.json"),
         serde_json::to_string_pretty(&tests)?,
     )?;
+    write_member_manifest(root)?;
 
-    run(root, true)?;
+    run(&push_cfg(root))?;
     let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
 
     // Exact order we want
@@ -88,10 +140,12 @@ fn yaml_includes_clippy_and_commit_count_and_reporter_env() -> anyhow::Result<()
                 timeout: 10,
                 points: 1,
                 description: "".into(),
+                ..Default::default()
             },
             kind: TestKind::Clippy {
                 manifest_path: None,
             },
+            options: TestOptions::default(),
         },
         AutoTest {
             meta: TestMeta {
@@ -99,8 +153,10 @@ fn yaml_includes_clippy_and_commit_count_and_reporter_env() -> anyhow::Result<()
                 timeout: 10,
                 points: 1,
                 description: "".into(),
+                ..Default::default()
             },
             kind: TestKind::CommitCount { min_commits: 1 },
+            options: TestOptions::default(),
         },
     ];
     let autograder = root.join(".autograder");
@@ -110,38 +166,39 @@ fn yaml_includes_clippy_and_commit_count_and_reporter_env() -> anyhow::Result<()
         serde_json::to_string_pretty(&tests)?,
     )?;
 
-    run(root, true)?;
+    run(&push_cfg(root))?;
     let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
 
     // Clippy
     assert!(yaml.contains(r#"- name: "CLIPPY_STYLE_CHECK""#));
-    assert!(yaml.contains(r#"id: "clippy-style-check""#));
+    assert!(yaml.contains(r#"id: "clippy_style_check""#));
     assert!(yaml.contains(r#"command: "cargo clippy -- -D warnings""#));
 
     // Commit count (script path name depends on your helper; adjust if needed)
     assert!(yaml.contains(r#"- name: "COMMIT_COUNT_1""#));
-    assert!(yaml.contains(r#"id: "commit-count-1""#));
+    assert!(yaml.contains(r#"id: "commit_count_1""#));
     assert!(yaml.contains(r#"command: "bash ./.autograder/"#)); // broad match
     assert!(yaml.contains(r#"max-score: 1"#));
 
     // Reporter: double-curly GitHub Actions expressions + runners CSV
     assert!(
         yaml.contains(
-            r#"CLIPPY-STYLE-CHECK_RESULTS: "${{steps.clippy-style-check.outputs.result}}""#
+            r#"CLIPPY_STYLE_CHECK_RESULTS: "${{steps.clippy_style_check.outputs.result}}""#
         ),
         "yaml was:\n{}",
         yaml
     );
-    assert!(yaml.contains(r#"COMMIT-COUNT-1_RESULTS: "${{steps.commit-count-1.outputs.result}}""#));
-    assert!(yaml.contains("runners: clippy-style-check,commit-count-1"));
+    assert!(yaml.contains(r#"COMMIT_COUNT_1_RESULTS: "${{steps.commit_count_1.outputs.result}}""#));
+    assert!(yaml.contains("runners: clippy_style_check,commit_count_1"));
     Ok(())
 }
 
 // Guards against accidentally adding -- --exact later unintentionally
 #[test]
 fn cargo_test_cmd_does_not_append_exact() {
-    let out_root = crate::types::command_makers::cargo_test_cmd("foo", None);
-    let out_mp = crate::types::command_makers::cargo_test_cmd("foo", Some("x/Cargo.toml"));
+    let opts = TestOptions::default();
+    let out_root = crate::types::command_makers::cargo_test_cmd("foo", None, &opts);
+    let out_mp = crate::types::command_makers::cargo_test_cmd("foo", Some("x/Cargo.toml"), &opts);
     assert_eq!(out_root, "cargo test foo");
     assert_eq!(out_mp, "cargo test foo --manifest-path x/Cargo.toml");
     assert!(
@@ -153,3 +210,1077 @@ fn cargo_test_cmd_does_not_append_exact() {
         "Did you mean to add --exact?"
     );
 }
+
+// Ensures the toolchain defaults to stable and skips components no test needs
+#[test]
+fn yaml_defaults_to_stable_without_unused_components() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    write_config(root, &[cargo_test("a")])?;
+
+    run(&push_cfg(root))?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+
+    assert!(yaml.contains("uses: dtolnay/rust-toolchain@stable"));
+    assert!(!yaml.contains("components:"), "yaml was:\n{}", yaml);
+    Ok(())
+}
+
+// Ensures rust-toolchain.toml is picked up and clippy is installed only for clippy checks
+#[test]
+fn yaml_uses_rust_toolchain_file_and_clippy_component() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    let mut clippy = cargo_test("CLIPPY_STYLE_CHECK");
+    clippy.kind = TestKind::Clippy {
+        manifest_path: None,
+    };
+    write_config(root, &[cargo_test("a"), clippy])?;
+    std::fs::write(
+        root.join("rust-toolchain.toml"),
+        "[toolchain]\nchannel = \"1.82.0\"\n",
+    )?;
+
+    run(&push_cfg(root))?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+
+    assert!(yaml.contains("uses: dtolnay/rust-toolchain@1.82.0"));
+    assert!(yaml.contains("components: clippy\n"));
+    assert!(!yaml.contains("rustfmt"));
+    Ok(())
+}
+
+// Ensures --toolchain beats the config, and non-branch toolchains go through @master
+#[test]
+fn yaml_toolchain_flag_overrides_config_settings() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    let mut config = crate::types::config::AutograderConfig::new(vec![cargo_test("a")]);
+    config.settings.toolchain = Some("1.80.0".into());
+    std::fs::create_dir_all(root.join(".autograder"))?;
+    crate::utils::write_autograder_config(root, &config)?;
+
+    run(&push_cfg(root))?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+    assert!(yaml.contains("uses: dtolnay/rust-toolchain@1.80.0"));
+
+    let cfg = BuildConfig {
+        toolchain: Some("nightly-2024-05-01".into()),
+        ..push_cfg(root)
+    };
+    run(&cfg)?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+    assert!(yaml.contains("uses: dtolnay/rust-toolchain@master"));
+    assert!(yaml.contains(r#"toolchain: "nightly-2024-05-01""#));
+    Ok(())
+}
+
+// Ensures --cache emits one cache step after the toolchain and before the first test step
+#[test]
+fn yaml_cache_step_is_ordered_and_keyed_on_every_manifest() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    let mut member = cargo_test("member_test");
+    member.kind = TestKind::CargoTest {
+        manifest_path: Some("member/Cargo.toml".into()),
+    };
+    write_config(root, &[cargo_test("root_test"), member])?;
+    write_member_manifest(root)?;
+
+    let cfg = BuildConfig {
+        cache: true,
+        ..push_cfg(root)
+    };
+    run(&cfg)?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+
+    let toolchain_at = yaml.find("dtolnay/rust-toolchain").unwrap();
+    let cache_at = yaml.find("uses: actions/cache@v4").expect("cache step");
+    let first_test_at = yaml.find("autograding-command-grader").unwrap();
+    assert!(toolchain_at < cache_at && cache_at < first_test_at);
+
+    assert!(yaml.contains("            target/\n"));
+    assert!(yaml.contains("            member/target/\n"));
+    assert!(yaml.contains(
+        "key: cargo-${{ runner.os }}-stable-${{ hashFiles('Cargo.lock', 'Cargo.toml', 'member/Cargo.lock', 'member/Cargo.toml') }}"
+    ));
+    assert!(yaml.contains("restore-keys: |\n            cargo-${{ runner.os }}-stable-\n"));
+    Ok(())
+}
+
+// Ensures workspace members share the workspace root's lockfile and target/
+#[test]
+fn yaml_cache_step_uses_the_workspace_root_for_members() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    let mut member = cargo_test("member_test");
+    member.kind = TestKind::CargoTest {
+        manifest_path: Some("crates/member/Cargo.toml".into()),
+    };
+    write_config(root, &[member])?;
+    std::fs::create_dir_all(root.join("crates/member"))?;
+    std::fs::write(
+        root.join("Cargo.toml"),
+        "[workspace]\nmembers = [\"crates/member\"]\n",
+    )?;
+    std::fs::write(
+        root.join("crates/member/Cargo.toml"),
+        "[package]\nname = \"member\"\nversion = \"0.1.0\"\n",
+    )?;
+
+    let cfg = BuildConfig {
+        cache: true,
+        ..push_cfg(root)
+    };
+    run(&cfg)?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+
+    assert!(yaml.contains("            target/\n"));
+    assert!(!yaml.contains("crates/member/target/"));
+    assert!(yaml.contains(
+        "key: cargo-${{ runner.os }}-stable-${{ hashFiles('Cargo.lock', 'crates/member/Cargo.toml') }}"
+    ));
+    Ok(())
+}
+
+// Ensures no cache step is emitted unless requested, or when nothing runs cargo
+#[test]
+fn yaml_cache_step_skipped_without_flag_or_cargo_steps() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    write_config(root, &[cargo_test("a")])?;
+    run(&push_cfg(root))?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+    assert!(!yaml.contains("actions/cache"));
+
+    let mut commits = cargo_test("COMMIT_COUNT_1");
+    commits.kind = TestKind::CommitCount { min_commits: 1 };
+    write_config(root, &[commits])?;
+    let cfg = BuildConfig {
+        cache: true,
+        ..push_cfg(root)
+    };
+    run(&cfg)?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+    assert!(!yaml.contains("actions/cache"));
+    Ok(())
+}
+
+// Ensures the compact `on:` list is kept when there is nothing to filter
+#[test]
+fn yaml_triggers_stay_compact_by_default() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    write_config(root, &[cargo_test("a")])?;
+
+    run(&BuildConfig {
+        root: root.to_path_buf(),
+        ..Default::default()
+    })?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+    assert!(yaml.contains("on: [repository_dispatch]\n"));
+    assert!(!yaml.contains("concurrency:"));
+
+    run(&push_cfg(root))?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+    assert!(yaml.contains("on: [repository_dispatch, push]\n"));
+    Ok(())
+}
+
+// Ensures dispatch, schedules, push filters and concurrency render as a mapping
+#[test]
+fn yaml_triggers_render_filters_schedule_and_concurrency() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    write_config(root, &[cargo_test("a")])?;
+
+    let cfg = BuildConfig {
+        root: root.to_path_buf(),
+        triggers: crate::types::config::Triggers {
+            workflow_dispatch: true,
+            schedule: vec!["0 3 * * *".into()],
+            push_paths: vec!["src/**".into(), "tests/**".into()],
+            cancel_superseded: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    run(&cfg)?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+
+    assert!(
+        yaml.contains(concat!(
+            "on:\n",
+            "  repository_dispatch:\n",
+            "  workflow_dispatch:\n",
+            "  push:\n",
+            "    paths:\n",
+            "      - \"src/**\"\n",
+            "      - \"tests/**\"\n",
+            "  schedule:\n",
+            "    - cron: \"0 3 * * *\"\n",
+        )),
+        "yaml was:\n{}",
+        yaml
+    );
+    assert!(!yaml.contains("branches:"));
+    assert!(yaml.contains("  group: autograding-${{ github.ref }}\n  cancel-in-progress: true\n"));
+    Ok(())
+}
+
+// Ensures trigger settings in autograder.json are honored and flags layer on top
+#[test]
+fn yaml_triggers_from_settings_merge_with_flags() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    std::fs::create_dir_all(root.join(".autograder"))?;
+    std::fs::write(
+        root.join(".autograder/autograder.json"),
+        r#"{
+            "settings": { "triggers": { "push_branches": ["main"], "schedule": ["0 0 * * 1"] } },
+            "tests": [
+                { "meta": { "name": "a", "description": "", "points": 1, "timeout": 10 }, "type": "cargo_test" }
+            ]
+        }"#,
+    )?;
+
+    let cfg = BuildConfig {
+        root: root.to_path_buf(),
+        triggers: crate::types::config::Triggers {
+            schedule: vec!["0 3 * * *".into()],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    run(&cfg)?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+    assert!(yaml.contains("    branches:\n      - \"main\"\n"));
+    assert!(yaml.contains(r#"- cron: "0 3 * * *""#));
+    assert!(
+        !yaml.contains("0 0 * * 1"),
+        "flag list replaces the config list"
+    );
+    Ok(())
+}
+
+// Ensures malformed cron expressions are rejected before writing the workflow
+#[test]
+fn invalid_cron_is_rejected() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    write_config(root, &[cargo_test("a")])?;
+
+    let cfg = BuildConfig {
+        root: root.to_path_buf(),
+        triggers: crate::types::config::Triggers {
+            schedule: vec!["0 3 * *".into()],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let err = run(&cfg).unwrap_err().to_string();
+    assert!(err.contains("expected 5 cron fields"), "got: {err}");
+    assert!(!root.join(".github/workflows/classroom.yml").exists());
+    Ok(())
+}
+
+fn write_settings_config(root: &Path, settings: serde_json::Value) -> anyhow::Result<()> {
+    std::fs::create_dir_all(root.join(".autograder"))?;
+    let config = serde_json::json!({
+        "settings": settings,
+        "tests": [cargo_test("a")],
+    });
+    std::fs::write(
+        root.join(".autograder/autograder.json"),
+        serde_json::to_string_pretty(&config)?,
+    )?;
+    Ok(())
+}
+
+// Ensures --pin-actions pins every uses: line and keeps the tag as a comment
+#[test]
+fn yaml_pins_every_action_by_sha() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    let grader_sha = "a".repeat(40);
+    let reporter_sha = "b".repeat(40);
+    let toolchain_sha = "c".repeat(40);
+    write_settings_config(
+        root,
+        serde_json::json!({
+            "action_pins": {
+                "classroom-resources/autograding-command-grader@v1": grader_sha,
+                "classroom-resources/autograding-grading-reporter@v1": reporter_sha,
+                "dtolnay/rust-toolchain@stable": toolchain_sha,
+            }
+        }),
+    )?;
+
+    let cfg = BuildConfig {
+        pin_actions: true,
+        cache: true,
+        ..push_cfg(root)
+    };
+    run(&cfg)?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+
+    assert!(
+        yaml.contains("uses: actions/checkout@11bd71901bbe5b1630ceea73d27597364c9af683 # v4\n")
+    );
+    assert!(yaml.contains("uses: actions/cache@5a3ec84eff668545956fd18022155c47e93e2684 # v4\n"));
+    assert!(yaml.contains(&format!(
+        "uses: dtolnay/rust-toolchain@{toolchain_sha} # stable\n"
+    )));
+    assert!(yaml.contains(&format!(
+        "uses: \"classroom-resources/autograding-command-grader@{grader_sha}\" # v1\n"
+    )));
+    assert!(yaml.contains(&format!(
+        "uses: classroom-resources/autograding-grading-reporter@{reporter_sha} # v1\n"
+    )));
+    assert!(!yaml.contains("@v1\""), "no unpinned tags left:\n{yaml}");
+    Ok(())
+}
+
+// Ensures pinning names every action without a pin at once, and that supplying them pins every
+// uses: line of a default workflow by full SHA
+#[test]
+fn yaml_pinning_reports_every_missing_pin_then_pins_everything() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    let mut io = cargo_test("greets");
+    io.kind = TestKind::Io {
+        command: "cargo run".into(),
+        input: String::new(),
+        expected_output: "hi".into(),
+        comparison: Comparison::Included,
+    };
+    let tests = [cargo_test("a"), io];
+    write_config(root, &tests)?;
+    let cfg = BuildConfig {
+        pin_actions: true,
+        cache: true,
+        ..push_cfg(root)
+    };
+
+    let err = run(&cfg).unwrap_err().to_string();
+    let missing = [
+        "dtolnay/rust-toolchain@stable",
+        "classroom-resources/autograding-command-grader@v1",
+        "classroom-resources/autograding-io-grader@v1",
+        "classroom-resources/autograding-grading-reporter@v1",
+    ];
+    for action in missing {
+        assert!(
+            err.contains(&format!("\"{action}\": \"<full commit sha>\"")),
+            "got: {err}"
+        );
+    }
+    assert!(!err.contains("actions/checkout"), "bundled: {err}");
+
+    let pins: serde_json::Map<String, serde_json::Value> = missing
+        .iter()
+        .enumerate()
+        .map(|(i, a)| (a.to_string(), i.to_string().repeat(40).into()))
+        .collect();
+    let config = serde_json::json!({
+        "settings": { "action_pins": pins },
+        "tests": tests,
+    });
+    std::fs::write(
+        root.join(".autograder/autograder.json"),
+        serde_json::to_string_pretty(&config)?,
+    )?;
+    run(&cfg)?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+
+    let uses: Vec<&str> = yaml
+        .lines()
+        .filter_map(|l| {
+            l.trim()
+                .strip_prefix("- uses: ")
+                .or(l.trim().strip_prefix("uses: "))
+        })
+        .collect();
+    assert_eq!(uses.len(), 6, "{yaml}");
+    for u in uses {
+        let sha = u
+            .trim_matches('"')
+            .split(['@', '"', ' '])
+            .nth(1)
+            .unwrap_or_default();
+        assert!(
+            sha.len() == 40 && sha.chars().all(|c| c.is_ascii_hexdigit()),
+            "not pinned: {u}"
+        );
+    }
+    Ok(())
+}
+
+// Ensures a mirror org replaces the owner of every action, from settings or the flag
+#[test]
+fn yaml_rewrites_action_owners_to_mirror() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    write_settings_config(
+        root,
+        serde_json::json!({
+            "action_mirror": "bu-actions",
+            "action_mirror_names": { "dtolnay/rust-toolchain": "dtolnay-rust-toolchain" },
+        }),
+    )?;
+
+    run(&push_cfg(root))?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+    assert!(yaml.contains("uses: bu-actions/checkout@v4\n"));
+    assert!(yaml.contains("uses: bu-actions/dtolnay-rust-toolchain@stable\n"));
+    assert!(yaml.contains(r#"uses: "bu-actions/autograding-command-grader@v1""#));
+    assert!(yaml.contains("uses: bu-actions/autograding-grading-reporter@v1\n"));
+
+    let cfg = BuildConfig {
+        action_mirror: Some("other-mirror".into()),
+        ..push_cfg(root)
+    };
+    run(&cfg)?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+    assert!(yaml.contains("uses: other-mirror/checkout@v4\n"));
+    assert!(!yaml.contains("bu-actions"));
+    Ok(())
+}
+
+// Ensures per-test cargo options land in the command and env lands in the step's env: block
+#[test]
+fn yaml_renders_per_test_options_and_env() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    let mut perf = cargo_test("perf_large_input");
+    perf.options = TestOptions {
+        env: [("RUST_BACKTRACE".to_string(), "1".to_string())].into(),
+        features: vec!["extra".into(), "fast".into()],
+        profile: Some("release".into()),
+        test_threads: Some(1),
+        target: Some("performance".into()),
+        extra_args: vec!["--locked".into()],
+        setup: None,
+    };
+    write_config(root, &[perf])?;
+
+    run(&push_cfg(root))?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+
+    assert!(yaml.contains(
+        r#"command: "cargo test perf_large_input --release --features extra,fast --test performance --locked -- --test-threads=1""#
+    ));
+    assert!(
+        yaml.contains("        env:\n          RUST_BACKTRACE: \"1\"\n        with:\n"),
+        "yaml was:\n{yaml}"
+    );
+    Ok(())
+}
+
+// Ensures settings.defaults apply to every test, with per-test values taking precedence
+#[test]
+fn yaml_applies_config_level_test_defaults() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    let mut own_threads = cargo_test("b");
+    own_threads.options.test_threads = Some(4);
+    own_threads.options.env = [("RUST_BACKTRACE".to_string(), "full".to_string())].into();
+    let mut clippy = cargo_test("CLIPPY_STYLE_CHECK");
+    clippy.kind = TestKind::Clippy {
+        manifest_path: None,
+    };
+
+    std::fs::create_dir_all(root.join(".autograder"))?;
+    let config = serde_json::json!({
+        "settings": {
+            "defaults": {
+                "env": { "RUST_BACKTRACE": "1", "CI": "true" },
+                "features": ["extra"],
+                "test_threads": 1,
+                "target": "integration",
+                "extra_args": ["--no-fail-fast"]
+            }
+        },
+        "tests": [cargo_test("a"), own_threads, clippy],
+    });
+    std::fs::write(
+        root.join(".autograder/autograder.json"),
+        serde_json::to_string_pretty(&config)?,
+    )?;
+
+    run(&push_cfg(root))?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+
+    assert!(yaml.contains(
+        r#"command: "cargo test a --features extra --test integration --no-fail-fast -- --test-threads=1""#
+    ));
+    assert!(yaml.contains(
+        r#"command: "cargo test b --features extra --test integration --no-fail-fast -- --test-threads=4""#
+    ));
+    // Only cargo test takes the target and extra arguments
+    assert!(yaml.contains(r#"command: "cargo clippy --features extra -- -D warnings""#));
+    assert!(yaml.contains(r#"RUST_BACKTRACE: "full""#));
+    assert_eq!(yaml.matches(r#"CI: "true""#).count(), 3);
+    Ok(())
+}
+
+// Ensures options round-trip through autograder.json as flat, optional fields
+#[test]
+fn test_options_are_flat_and_omitted_when_unset() -> anyhow::Result<()> {
+    let plain = serde_json::to_value(cargo_test("a"))?;
+    assert!(plain.get("env").is_none() && plain.get("features").is_none());
+
+    let parsed: AutoTest = serde_json::from_str(
+        r#"{ "meta": { "name": "a", "description": "", "points": 1, "timeout": 10 },
+             "type": "cargo_test", "manifest_path": "m/Cargo.toml",
+             "profile": "bench", "extra_args": ["--no-fail-fast"] }"#,
+    )?;
+    assert_eq!(parsed.options.profile.as_deref(), Some("bench"));
+    assert_eq!(
+        parsed.command(),
+        "cargo test a --manifest-path m/Cargo.toml --profile bench --no-fail-fast"
+    );
+    Ok(())
+}
+
+fn timed_test(name: &str, timeout: u64, manifest_path: Option<&str>) -> AutoTest {
+    let mut t = cargo_test(name);
+    t.meta.timeout = timeout;
+    t.kind = TestKind::CargoTest {
+        manifest_path: manifest_path.map(str::to_string),
+    };
+    t
+}
+
+fn partition_names(tests: &[AutoTest], parts: &[Vec<usize>]) -> Vec<Vec<String>> {
+    parts
+        .iter()
+        .map(|p| p.iter().map(|&i| tests[i].meta.name.clone()).collect())
+        .collect()
+}
+
+// Ensures balanced partitioning evens out total timeouts and keeps config order within a job
+#[test]
+fn partition_balanced_by_timeout() {
+    use super::partition::{Partition, partition_tests};
+    let tests = vec![
+        timed_test("a", 30, None),
+        timed_test("b", 10, None),
+        timed_test("c", 10, None),
+        timed_test("d", 10, None),
+        timed_test("e", 20, None),
+    ];
+    let parts = partition_tests(&tests, 2, Partition::Balanced);
+    assert_eq!(
+        partition_names(&tests, &parts),
+        vec![vec!["a", "c"], vec!["b", "d", "e"]]
+    );
+}
+
+// Ensures manifest/group partitioning never splits a bucket and never emits empty jobs
+#[test]
+fn partition_by_manifest_and_group_keeps_buckets_together() {
+    use super::partition::{Partition, partition_tests};
+    let tests = vec![
+        timed_test("root_1", 10, None),
+        timed_test("m_1", 10, Some("member/Cargo.toml")),
+        timed_test("root_2", 10, None),
+        timed_test("m_2", 10, Some("member/Cargo.toml")),
+    ];
+    let parts = partition_tests(&tests, 4, Partition::Manifest);
+    assert_eq!(
+        partition_names(&tests, &parts),
+        vec![vec!["root_1", "root_2"], vec!["m_1", "m_2"]]
+    );
+
+    let mut grouped = tests.clone();
+    grouped[0].meta.group = Some("part-1".into());
+    grouped[1].meta.group = Some("part-1".into());
+    let parts = partition_tests(&grouped, 3, Partition::Group);
+    assert_eq!(
+        partition_names(&grouped, &parts),
+        vec![vec!["root_1", "m_1"], vec!["root_2", "m_2"]]
+    );
+}
+
+// Ensures --jobs emits grading jobs with outputs and one aggregating reporter job
+#[test]
+fn yaml_parallel_jobs_feed_a_single_reporter() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    let mut clippy = timed_test("CLIPPY_STYLE_CHECK", 10, None);
+    clippy.kind = TestKind::Clippy {
+        manifest_path: None,
+    };
+    write_config(
+        root,
+        &[timed_test("a", 10, None), timed_test("b", 10, None), clippy],
+    )?;
+
+    let cfg = BuildConfig {
+        jobs: 2,
+        ..push_cfg(root)
+    };
+    run(&cfg)?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+
+    assert_eq!(yaml.matches("name: Checkout code").count(), 2);
+    assert!(
+        yaml.contains(concat!(
+            "  grade-1:\n",
+            "    runs-on: ubuntu-latest\n",
+            "    if: github.actor != 'github-classroom[bot]'\n",
+            "    outputs:\n",
+            "      a: ${{ steps.a.outputs.result }}\n",
+            "      clippy_style_check: ${{ steps.clippy_style_check.outputs.result }}\n",
+            "    steps:\n",
+        )),
+        "yaml was:\n{yaml}"
+    );
+    assert!(yaml.contains("  grade-2:\n"));
+    assert!(yaml.contains(concat!(
+        "  run-autograding-tests:\n",
+        "    runs-on: ubuntu-latest\n",
+        "    needs: [grade-1, grade-2]\n",
+        "    if: always() && github.actor != 'github-classroom[bot]'\n",
+        "    steps:\n",
+        "      - name: Autograding Reporter\n",
+    )));
+    assert!(yaml.contains(r#"A_RESULTS: "${{needs.grade-1.outputs.a}}""#));
+    assert!(yaml.contains(r#"B_RESULTS: "${{needs.grade-2.outputs.b}}""#));
+    assert!(yaml.contains("runners: a,clippy_style_check,b"));
+    // Only the job running clippy installs it
+    assert_eq!(yaml.matches("components: clippy").count(), 1);
+    Ok(())
+}
+
+// Ensures --jobs 1 (the default) keeps the single-job layout
+#[test]
+fn yaml_single_job_when_only_one_partition() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    write_config(root, &[cargo_test("a")])?;
+
+    let cfg = BuildConfig {
+        jobs: 4,
+        ..push_cfg(root)
+    };
+    run(&cfg)?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+    assert!(!yaml.contains("needs:"));
+    assert!(yaml.contains(r#"A_RESULTS: "${{steps.a.outputs.result}}""#));
+    Ok(())
+}
+
+fn target_cfg(root: &Path, target: Backend) -> BuildConfig {
+    BuildConfig {
+        root: root.to_path_buf(),
+        target,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn shell_target_writes_grade_script_only() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    let mut with_env = cargo_test("it's_env");
+    with_env
+        .options
+        .env
+        .insert("RUST_LOG".into(), "debug".into());
+    let mut ungraded = cargo_test("ungraded");
+    ungraded.meta.points = 0;
+    let count = AutoTest {
+        meta: TestMeta {
+            name: "TEST_COUNT".into(),
+            points: 2,
+            timeout: 5,
+            ..Default::default()
+        },
+        kind: TestKind::TestCount {
+            min_tests: 1,
+            manifest_path: None,
+        },
+        options: TestOptions::default(),
+    };
+    write_config(root, &[cargo_test("adds"), with_env, ungraded, count])?;
+
+    run(&target_cfg(root, Backend::Shell))?;
+    let script = std::fs::read_to_string(root.join(".autograder/grade.sh"))?;
+
+    assert!(script.starts_with("#!/usr/bin/env bash\n"));
+    assert!(script.contains("run_test 'adds' 1 10 'cargo test adds'\n"));
+    assert!(script.contains(
+        r#"run_test 'it'\''s_env' 1 10 'export RUST_LOG='\''debug'\''; cargo test it'\''s_env'"#
+    ));
+    assert!(!script.contains("ungraded"), "0-point tests are skipped");
+    // `##` is filled with the number of graded cargo tests, as in the GitHub workflow
+    assert!(script.contains("< 1+2)"));
+    assert!(script.contains(r#"echo "Score: ${score}/${max_score} (${percent}%)""#));
+    assert!(!root.join(".github").exists());
+    assert!(!root.join(".gitlab-ci.yml").exists());
+    Ok(())
+}
+
+#[test]
+fn gitlab_target_renders_pipeline_around_grade_script() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    let mut clippy = cargo_test("clippy");
+    clippy.kind = TestKind::Clippy {
+        manifest_path: None,
+    };
+    write_config(root, &[cargo_test("adds"), clippy])?;
+
+    let cfg = BuildConfig {
+        toolchain: Some("1.82.0".into()),
+        cache: true,
+        triggers: Triggers {
+            push_branches: vec!["main".into()],
+            cancel_superseded: true,
+            ..Default::default()
+        },
+        ..target_cfg(root, Backend::Gitlab)
+    };
+    run(&cfg)?;
+    let ci = std::fs::read_to_string(root.join(".gitlab-ci.yml"))?;
+
+    assert!(ci.starts_with("# Generated by autograder-setup"));
+    assert!(ci.contains("  image: rust:1.82.0\n"));
+    assert!(ci.contains("    GIT_DEPTH: \"0\"\n"));
+    assert!(ci.contains("  interruptible: true\n"));
+    assert!(ci.contains(
+        r#"    - if: "$CI_PIPELINE_SOURCE == \"push\" && ($CI_COMMIT_BRANCH == \"main\")""#
+    ));
+    assert!(ci.contains("      prefix: cargo-1.82.0\n"));
+    assert!(ci.contains("    - rustup component add clippy\n"));
+    assert!(ci.contains("    - bash .autograder/grade.sh\n"));
+    assert!(ci.contains(r"coverage: '/Score: \d+\/\d+ \((\d+)%\)/'"));
+    assert!(root.join(".autograder/grade.sh").exists());
+    Ok(())
+}
+
+// Ensures every lockfile is accounted for in the GitLab cache, which hashes at most two files
+#[test]
+fn gitlab_cache_never_drops_lockfiles() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    let crates = ["a", "b", "c"];
+    let tests: Vec<AutoTest> = crates
+        .iter()
+        .map(|c| {
+            let mut t = cargo_test(c);
+            t.kind = TestKind::CargoTest {
+                manifest_path: Some(format!("{c}/Cargo.toml")),
+            };
+            t
+        })
+        .collect();
+    for c in crates {
+        std::fs::create_dir_all(root.join(c))?;
+        std::fs::write(
+            root.join(c).join("Cargo.toml"),
+            format!("[package]\nname = \"{c}\"\nversion = \"0.1.0\"\n"),
+        )?;
+    }
+
+    write_config(root, &tests[..2])?;
+    let cfg = BuildConfig {
+        cache: true,
+        ..target_cfg(root, Backend::Gitlab)
+    };
+    run(&cfg)?;
+    let ci = std::fs::read_to_string(root.join(".gitlab-ci.yml"))?;
+    assert!(ci.contains("      files:\n        - a/Cargo.lock\n        - b/Cargo.lock\n"));
+
+    write_config(root, &tests)?;
+    run(&cfg)?;
+    let ci = std::fs::read_to_string(root.join(".gitlab-ci.yml"))?;
+    assert!(!ci.contains("files:"), "{ci}");
+    assert!(ci.contains("    key: cargo-stable\n"));
+    for c in crates {
+        assert!(ci.contains(&format!("      - {c}/target/\n")));
+    }
+    Ok(())
+}
+
+#[test]
+fn forgejo_target_installs_toolchain_and_grades_on_push() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    write_config(root, &[cargo_test("adds")])?;
+
+    let cfg = BuildConfig {
+        toolchain: Some("nightly-2024-05-01".into()),
+        pin_actions: true,
+        ..target_cfg(root, Backend::Forgejo)
+    };
+    run(&cfg)?;
+    let wf = std::fs::read_to_string(root.join(".forgejo/workflows/autograding.yml"))?;
+
+    assert!(wf.contains("on: [push]\n"));
+    assert!(!wf.contains("repository_dispatch"));
+    assert!(!wf.contains("classroom-resources"));
+    assert!(wf.contains("uses: actions/checkout@11bd71901bbe5b1630ceea73d27597364c9af683 # v4"));
+    assert!(wf.contains("rustup toolchain install nightly-2024-05-01 --profile minimal\n"));
+    assert!(wf.contains("run: bash .autograder/grade.sh\n"));
+    Ok(())
+}
+
+#[test]
+fn non_github_targets_reject_parallel_jobs() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    write_config(tmp.path(), &[cargo_test("a"), cargo_test("b")])?;
+    let cfg = BuildConfig {
+        jobs: 2,
+        ..target_cfg(tmp.path(), Backend::Gitlab)
+    };
+    let err = run(&cfg).unwrap_err().to_string();
+    assert!(err.contains("--jobs"), "{err}");
+    Ok(())
+}
+
+#[test]
+fn yaml_io_tests_use_the_io_grader_with_setup() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    let mut io = cargo_test("greets");
+    io.kind = TestKind::Io {
+        command: "cargo run -q".into(),
+        input: "Ada".into(),
+        expected_output: "Hello, Ada".into(),
+        comparison: Comparison::Exact,
+    };
+    io.options.setup = Some("cargo build".into());
+    write_config(root, &[io])?;
+
+    run(&push_cfg(root))?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+    assert!(yaml.contains(r#"uses: "classroom-resources/autograding-io-grader@v1""#));
+    assert!(yaml.contains(r#"setup-command: "cargo build""#));
+    assert!(yaml.contains(r#"command: "cargo run -q""#));
+    assert!(yaml.contains(r#"input: "Ada""#));
+    assert!(yaml.contains(r#"expected-output: "Hello, Ada""#));
+    assert!(yaml.contains("comparison-method: exact"));
+    Ok(())
+}
+
+// Ensures tests without a timeout pick up settings.default_timeout
+#[test]
+fn yaml_applies_default_timeout_to_tests_without_one() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    std::fs::create_dir_all(root.join(".autograder"))?;
+    std::fs::write(
+        root.join(".autograder/autograder.json"),
+        r#"{
+          "version": 2,
+          "settings": { "default_timeout": 3, "total_points": 5 },
+          "tests": [
+            { "meta": { "name": "a", "description": "", "points": 1 }, "type": "cargo_test" },
+            { "meta": { "name": "b", "description": "", "points": 1, "timeout": 7 }, "type": "cargo_test" }
+          ]
+        }"#,
+    )?;
+
+    run(&push_cfg(root))?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+    assert!(yaml.contains("timeout: 3"));
+    assert!(yaml.contains("timeout: 7"));
+    Ok(())
+}
+
+// Ensures settings.lang renames the workflow and its fixed steps, but not the graded tests
+#[test]
+fn yaml_step_names_follow_settings_lang() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    write_settings_config(root, serde_json::json!({ "lang": "es" }))?;
+
+    let cfg = BuildConfig {
+        cache: true,
+        ..push_cfg(root)
+    };
+    run(&cfg)?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+    assert!(yaml.starts_with("name: Pruebas de autocorrección\n"));
+    assert!(yaml.contains("- name: Obtener el código\n"));
+    assert!(yaml.contains("- name: Instalar la toolchain de Rust\n"));
+    assert!(yaml.contains("- name: Caché del registro de cargo y la compilación\n"));
+    assert!(yaml.contains("- name: Informe de autocorrección\n"));
+    assert!(yaml.contains(r#"- name: "a""#));
+
+    // The flag wins over the config
+    let cfg = BuildConfig {
+        lang: Some("en".into()),
+        ..push_cfg(root)
+    };
+    run(&cfg)?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+    assert!(yaml.starts_with("name: Autograding Tests\n"));
+    Ok(())
+}
+
+// Ensures names and descriptions are templates over the test's own fields, and `##` still works
+#[test]
+fn templates_fill_names_and_descriptions() -> anyhow::Result<()> {
+    let tests: Vec<AutoTest> = serde_json::from_str(
+        r#"[
+          { "meta": { "name": "At least {min_commits} commits", "description": "{name}: ## of them, {points} points, {{literal}}, `{ \"a\": 1 }`", "points": 2 }, "type": "commit_count", "min_commits": 4 },
+          { "meta": { "name": "lint", "description": "Lints {manifest_path} within {timeout} minutes", "points": 1, "timeout": 3 }, "type": "clippy" },
+          { "meta": { "name": "greets", "description": "Runs `{command}` and expects {expected_output} ({comparison})", "points": 1 }, "type": "io", "command": "cargo run", "expected_output": "Hi", "comparison": "exact" }
+        ]"#,
+    )?;
+    let tests = apply_test_defaults(tests, &Settings::default())?;
+
+    assert_eq!(tests[0].meta.name, "At least 4 commits");
+    assert_eq!(
+        tests[0].meta.description,
+        "At least 4 commits: 4 of them, 2 points, {literal}, `{ \"a\": 1 }`"
+    );
+    assert_eq!(
+        tests[1].meta.description,
+        "Lints Cargo.toml within 3 minutes"
+    );
+    assert_eq!(
+        tests[2].meta.description,
+        "Runs `cargo run` and expects Hi (exact)"
+    );
+
+    let mut bad = tests[1].clone();
+    bad.meta.name = "lint {name}".into();
+    let err = format!(
+        "{:#}",
+        apply_test_defaults(vec![bad], &Settings::default()).unwrap_err()
+    );
+    assert!(
+        err.contains("`{name}` isn't available for clippy tests (available: {points}, {timeout}, {manifest_path})"),
+        "{err}"
+    );
+    Ok(())
+}
+
+// Ensures students see the display name while the command and the reporter wiring use the filter
+#[test]
+fn display_names_leave_commands_and_ids_alone() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+
+    let mut filtered = cargo_test("Parses negative numbers");
+    filtered.meta.filter = Some("parse_negative".into());
+    let mut exact = cargo_test("adds");
+    exact.meta.display_name = Some("Adds {points} numbers".into());
+    exact.meta.path = Some("calc::tests::adds".into());
+    exact.options.test_threads = Some(1);
+    write_config(root, &[filtered, exact.clone()])?;
+    run(&push_cfg(root))?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+
+    assert!(yaml.contains(r#"- name: "Parses negative numbers""#));
+    assert!(yaml.contains(r#"id: "parse_negative""#));
+    assert!(yaml.contains(r#"command: "cargo test parse_negative""#));
+    assert!(yaml.contains(r#"test-name: "Adds 1 numbers""#));
+    assert!(yaml.contains(r#"id: "calc_tests_adds""#));
+    assert!(
+        yaml.contains(r#"command: "cargo test calc::tests::adds -- --exact --test-threads=1""#)
+    );
+    assert!(yaml.contains("runners: parse_negative,calc_tests_adds"));
+
+    // Renaming what students see doesn't rewire the reporter
+    exact.meta.display_name = Some("Addition".into());
+    write_config(root, &[exact])?;
+    run(&push_cfg(root))?;
+    let renamed = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+    assert!(renamed.contains(r#"- name: "Addition""#));
+    assert!(
+        renamed.contains(r#"CALC_TESTS_ADDS_RESULTS: "${{steps.calc_tests_adds.outputs.result}}""#)
+    );
+    Ok(())
+}
+
+// Ensures tests whose slugs collide get unique, stable ids and identifier-safe reporter variables
+#[test]
+fn colliding_slugs_get_numbered_ids() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    let tests = [
+        cargo_test("test_a"),
+        cargo_test("test-a"),
+        cargo_test("test a 2"),
+        cargo_test("1st"),
+    ];
+
+    let (ids, renamed) = build_functions::runner_ids(&tests);
+    // `test a 2` keeps its own slug, so the second `test_a` skips to `_3`
+    assert_eq!(ids, ["test_a", "test_a_3", "test_a_2", "test_1st"]);
+    assert_eq!(
+        renamed,
+        vec![build_functions::RenamedId {
+            test: "test-a".into(),
+            slug: "test_a".into(),
+            id: "test_a_3".into(),
+        }]
+    );
+
+    write_config(root, &tests)?;
+    let cfg = BuildConfig {
+        jobs: 2,
+        ..push_cfg(root)
+    };
+    run(&cfg)?;
+    let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+    assert!(yaml.contains(r#"id: "test_a_3""#));
+    assert!(yaml.contains("      test_a_3: ${{ steps.test_a_3.outputs.result }}\n"));
+    let var = yaml
+        .lines()
+        .find(|l| l.contains("TEST_A_3_RESULTS:"))
+        .unwrap();
+    assert!(var.ends_with(r#".outputs.test_a_3}}""#), "{var}");
+    let runners = yaml
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("runners: "));
+    let mut runners: Vec<&str> = runners.unwrap().split(',').collect();
+    runners.sort();
+    assert_eq!(runners, ["test_1st", "test_a", "test_a_2", "test_a_3"]);
+    for line in yaml.lines().filter(|l| l.contains("_RESULTS:")) {
+        let var = line.trim().split(':').next().unwrap();
+        assert!(
+            var.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                && !var.starts_with(|c: char| c.is_ascii_digit()),
+            "{var}"
+        );
+    }
+    Ok(())
+}
+
+// Ensures two templated names that render to the same string still get separate step ids
+#[test]
+fn names_that_render_alike_get_separate_ids() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    let mut by_points = cargo_test("grade_{points}");
+    by_points.meta.points = 10;
+    let mut by_timeout = cargo_test("grade_{timeout}");
+    by_timeout.meta.timeout = 10;
+    by_timeout.meta.points = 1;
+    write_config(root, &[by_points, by_timeout])?;
+
+    for jobs in [1, 2] {
+        let cfg = BuildConfig {
+            jobs,
+            ..push_cfg(root)
+        };
+        run(&cfg)?;
+        let yaml = std::fs::read_to_string(root.join(".github/workflows/classroom.yml"))?;
+        let runners = yaml
+            .lines()
+            .find_map(|l| l.trim().strip_prefix("runners: "))
+            .unwrap();
+        let mut runners: Vec<&str> = runners.split(',').collect();
+        runners.sort();
+        assert_eq!(runners, ["grade_10", "grade_10_2"], "{yaml}");
+        assert!(yaml.contains(r#"id: "grade_10_2""#), "{yaml}");
+    }
+    Ok(())
+}
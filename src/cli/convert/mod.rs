@@ -0,0 +1,32 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+use crate::utils::config_format::ConfigFormat;
+use crate::utils::{
+    autograder_config_path, ensure_exists, load_autograder_config, write_autograder_config_as,
+};
+
+/// Rewrite the config in another format and remove the old file. Comments are not carried over.
+pub fn run(root: &Path, to: ConfigFormat) -> Result<()> {
+    let from = autograder_config_path(root);
+    ensure_exists(&from)?;
+    if ConfigFormat::from_path(&from) == Some(to) {
+        println!("{} is already {:?}", from.to_string_lossy(), to);
+        return Ok(());
+    }
+
+    let config = load_autograder_config(root)?;
+    let out_path = write_autograder_config_as(root, &config, to)?;
+    fs::remove_file(&from)
+        .with_context(|| format!("Failed to remove {}", from.to_string_lossy()))?;
+    println!(
+        "Converted {} to {}",
+        from.to_string_lossy(),
+        out_path.to_string_lossy()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests;
@@ -0,0 +1,104 @@
+use super::*;
+use crate::types::config::AutograderConfig;
+use crate::types::{AutoTest, Comparison, SourceLocation, TestKind, TestMeta, TestOptions};
+use crate::utils::write_autograder_config;
+
+fn sample() -> AutograderConfig {
+    let mut config = AutograderConfig::new(vec![
+        AutoTest {
+            meta: TestMeta {
+                name: "adds".into(),
+                display_name: Some("Adds numbers".into()),
+                filter: None,
+                path: None,
+                description: "Adds two numbers".into(),
+                points: 2,
+                timeout: 10,
+                group: Some("part-1".into()),
+                source: Some(SourceLocation {
+                    file: "calc/src/lib.rs".into(),
+                    line: 12,
+                }),
+            },
+            kind: TestKind::CargoTest {
+                manifest_path: Some("calc/Cargo.toml".into()),
+            },
+            options: TestOptions {
+                env: [("RUST_BACKTRACE".to_string(), "1".to_string())].into(),
+                ..Default::default()
+            },
+        },
+        AutoTest {
+            meta: TestMeta {
+                name: "GREETS".into(),
+                description: "Prints a greeting".into(),
+                points: 1,
+                ..Default::default()
+            },
+            kind: TestKind::Io {
+                command: "cargo run -q".into(),
+                input: "Ada\n".into(),
+                expected_output: "Hello, Ada".into(),
+                comparison: Comparison::Exact,
+            },
+            options: TestOptions::default(),
+        },
+    ]);
+    config.schema = Some("./autograder.schema.json".into());
+    config.extends = vec!["../course/base.toml".into()];
+    config.settings.toolchain = Some("1.82.0".into());
+    config.settings.triggers.push_branches = vec!["main".into()];
+    config
+}
+
+fn as_json(config: &AutograderConfig) -> serde_json::Value {
+    serde_json::to_value(config).unwrap()
+}
+
+#[test]
+fn round_trips_through_every_format() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir_all(root.join(".autograder")).unwrap();
+    write_autograder_config(root, &sample()).unwrap();
+
+    for to in [ConfigFormat::Toml, ConfigFormat::Yaml, ConfigFormat::Json] {
+        run(root, to).unwrap();
+        let path = autograder_config_path(root);
+        assert_eq!(ConfigFormat::from_path(&path), Some(to));
+        assert_eq!(crate::utils::existing_config_paths(root).len(), 1);
+        assert_eq!(
+            as_json(&load_autograder_config(root).unwrap()),
+            as_json(&sample())
+        );
+    }
+}
+
+#[test]
+fn toml_keeps_each_test_in_one_block() {
+    let toml = ConfigFormat::Toml.render(&sample()).unwrap();
+    assert!(
+        toml.starts_with(
+            "\"$schema\" = \"./autograder.schema.json\"\nextends = \"../course/base.toml\"\nversion = 2\n"
+        ),
+        "{toml}"
+    );
+    assert!(toml.contains("[settings.triggers]"), "{toml}");
+    assert!(
+        toml.contains("[[tests]]\nmeta = { name = \"adds\""),
+        "{toml}"
+    );
+    assert!(!toml.contains("[tests.meta]"), "{toml}");
+}
+
+#[test]
+fn refuses_to_guess_between_two_configs() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir_all(root.join(".autograder")).unwrap();
+    write_autograder_config(root, &sample()).unwrap();
+    crate::utils::write_autograder_config_as(root, &sample(), ConfigFormat::Yaml).unwrap();
+
+    let err = format!("{:#}", load_autograder_config(root).unwrap_err());
+    assert!(err.contains("more than one autograder config"), "{err}");
+}
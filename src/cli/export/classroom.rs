@@ -0,0 +1,40 @@
+// GitHub Classroom's legacy `.github/classroom/autograding.json`, for the Classroom web UI.
+use anyhow::{Context, Result};
+use std::fs::create_dir_all;
+use std::path::Path;
+
+use crate::cli::build::build_functions::{cargo_test_counts, shell_command};
+use crate::cli::build::{apply_test_defaults, create_and_write};
+use crate::types::classroom::{ClassroomConfig, ClassroomTest};
+use crate::utils::read_autograder_config;
+
+pub fn run(root: &Path, out: Option<&Path>) -> Result<()> {
+    let config = read_autograder_config(root)?;
+    let tests = apply_test_defaults(config.tests, &config.settings)?;
+    let counts = cargo_test_counts(&tests);
+
+    let classroom = ClassroomConfig {
+        tests: tests
+            .iter()
+            .map(|t| {
+                // Classroom has its own setup field, so keep it out of `run`
+                let mut bare = t.clone();
+                bare.options.setup = None;
+                ClassroomTest::from_auto_test(t, shell_command(&bare, &counts))
+            })
+            .collect(),
+    };
+
+    let path = out.map(Path::to_path_buf).unwrap_or_else(|| {
+        root.join(".github")
+            .join("classroom")
+            .join("autograding.json")
+    });
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.to_string_lossy()))?;
+    }
+    create_and_write(&path, &serde_json::to_string_pretty(&classroom)?)?;
+    println!("Wrote {}", path.to_string_lossy());
+    Ok(())
+}
@@ -0,0 +1,194 @@
+// Gradescope autograder bundle: setup.sh, run_autograder and a results.json writer.
+//
+// Gradescope unpacks the bundle into /autograder/source, runs setup.sh once while building the
+// image, then runs run_autograder for every submission (found in /autograder/submission).
+use anyhow::{Context, Result};
+use serde_json::json;
+use std::fs::create_dir_all;
+use std::path::Path;
+
+use crate::cli::build::build_functions::{cargo_test_counts, shell_command, write_support_scripts};
+use crate::cli::build::{
+    apply_test_defaults, create_and_write, required_components, resolve_toolchain,
+};
+use crate::types::AutoTest;
+use crate::utils::{make_executable, read_autograder_config, shell_quote};
+
+pub const SETUP_SCRIPT: &str = "setup.sh";
+pub const RUN_SCRIPT: &str = "run_autograder";
+pub const RESULTS_WRITER: &str = "write_results.py";
+pub const TESTS_FILE: &str = "tests.json";
+
+const RUN_HEADER: &str = r#"#!/usr/bin/env bash
+# Generated by autograder-setup from autograder.json; re-run `export gradescope` instead of editing.
+
+SOURCE=/autograder/source
+RESULTS=/autograder/results
+LOGS="$RESULTS/logs"
+
+[ -f "$HOME/.cargo/env" ] && . "$HOME/.cargo/env"
+mkdir -p "$LOGS"
+cd /autograder/submission || exit 1
+
+# Helper scripts used by the commit and branch count checks
+if [ -d "$SOURCE/.autograder" ]; then
+  mkdir -p .autograder
+  cp "$SOURCE"/.autograder/*.sh .autograder/
+fi
+
+run_test() {
+  local index="$1" minutes="$2" cmd="$3"
+  if command -v timeout >/dev/null 2>&1; then
+    timeout "${minutes}m" bash -c "$cmd" > "$LOGS/$index.log" 2>&1
+  else
+    bash -c "$cmd" > "$LOGS/$index.log" 2>&1
+  fi
+  echo "$?" > "$LOGS/$index.status"
+}
+
+"#;
+
+const RESULTS_WRITER_CONTENTS: &str = r#"#!/usr/bin/env python3
+# Generated by autograder-setup: turns per-test logs into Gradescope's results.json.
+#
+# Usage:
+#   python3 write_results.py tests.json <log dir> > results.json
+import json
+import os
+import sys
+
+# Keep the tail of long logs; Gradescope truncates very large outputs anyway
+MAX_OUTPUT = 8000
+
+
+def read(path, default):
+    try:
+        with open(path, errors="replace") as f:
+            return f.read()
+    except OSError:
+        return default
+
+
+def main():
+    tests_path, log_dir = sys.argv[1], sys.argv[2]
+    with open(tests_path) as f:
+        tests = json.load(f)
+
+    results = []
+    for i, test in enumerate(tests):
+        try:
+            status = int(read(os.path.join(log_dir, "%d.status" % i), "1").strip())
+        except ValueError:
+            status = 1
+        log = read(os.path.join(log_dir, "%d.log" % i), "").strip()
+        if len(log) > MAX_OUTPUT:
+            log = "...\n" + log[-MAX_OUTPUT:]
+
+        passed = status == 0
+        # `timeout` exits with 124 when the limit is hit
+        verdict = "Passed" if passed else ("Timed out" if status == 124 else "Failed")
+        output = "\n\n".join(p for p in (test["description"], verdict, log) if p)
+        results.append({
+            "name": test["name"],
+            "score": test["max_score"] if passed else 0,
+            "max_score": test["max_score"],
+            "status": "passed" if passed else "failed",
+            "output": output,
+        })
+
+    json.dump({"tests": results}, sys.stdout, indent=2)
+
+
+if __name__ == "__main__":
+    main()
+"#;
+
+pub fn run(root: &Path, out: Option<&Path>, toolchain: Option<&String>) -> Result<()> {
+    let config = read_autograder_config(root)?;
+    let toolchain = resolve_toolchain(toolchain, &config.settings, root)?;
+    let tests: Vec<AutoTest> = apply_test_defaults(config.tests, &config.settings)?
+        .into_iter()
+        .filter(|t| t.meta.points > 0)
+        .collect();
+
+    let out_dir = out
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| root.join(".autograder").join("gradescope"));
+    create_dir_all(&out_dir)
+        .with_context(|| format!("Failed to create {}", out_dir.to_string_lossy()))?;
+
+    write_executable(
+        &out_dir.join(SETUP_SCRIPT),
+        &render_setup(&toolchain, &required_components(&tests)),
+    )?;
+    write_executable(&out_dir.join(RUN_SCRIPT), &render_run_autograder(&tests))?;
+    write_executable(&out_dir.join(RESULTS_WRITER), RESULTS_WRITER_CONTENTS)?;
+    create_and_write(&out_dir.join(TESTS_FILE), &render_tests_json(&tests)?)?;
+    write_support_scripts(&out_dir, &tests)?;
+
+    println!(
+        "Wrote Gradescope autograder to {}\nZip its contents (not the folder) and upload: cd {} && zip -r ../autograder.zip .",
+        out_dir.to_string_lossy(),
+        out_dir.to_string_lossy()
+    );
+    Ok(())
+}
+
+/// Runs once while Gradescope builds the image: system packages plus the grading toolchain
+pub fn render_setup(toolchain: &str, components: &[String]) -> String {
+    let mut rustup = format!(
+        "curl --proto '=https' --tlsv1.2 -sSf https://sh.rustup.rs | sh -s -- -y --profile minimal --default-toolchain {toolchain}"
+    );
+    if !components.is_empty() {
+        rustup.push_str(&format!(" --component {}", components.join(",")));
+    }
+    format!(
+        r#"#!/usr/bin/env bash
+# Generated by autograder-setup from autograder.json; re-run `export gradescope` instead of editing.
+set -euo pipefail
+
+apt-get update
+apt-get install -y --no-install-recommends build-essential ca-certificates curl git python3
+{rustup}
+"#
+    )
+}
+
+/// One `run_test` per graded test (indexed like tests.json), then the results writer
+pub fn render_run_autograder(tests: &[AutoTest]) -> String {
+    let counts = cargo_test_counts(tests);
+    let mut out = String::from(RUN_HEADER);
+    for (i, test) in tests.iter().enumerate() {
+        let cmd = shell_command(test, &counts);
+        out.push_str(&format!(
+            "run_test {} {} {}\n",
+            i,
+            test.meta.timeout.max(1),
+            shell_quote(&cmd)
+        ));
+    }
+    out.push_str(&format!(
+        "\npython3 \"$SOURCE/{RESULTS_WRITER}\" \"$SOURCE/{TESTS_FILE}\" \"$LOGS\" > \"$RESULTS/results.json\"\n"
+    ));
+    out
+}
+
+/// Name, points and description per test; the results writer pairs them with the logs by index
+pub fn render_tests_json(tests: &[AutoTest]) -> Result<String> {
+    let entries: Vec<_> = tests
+        .iter()
+        .map(|t| {
+            json!({
+                "name": t.display_name(),
+                "max_score": t.meta.points,
+                "description": t.plain_description(),
+            })
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&entries)?)
+}
+
+fn write_executable(path: &Path, contents: &str) -> Result<()> {
+    create_and_write(path, contents)?;
+    make_executable(path)
+}
@@ -0,0 +1,18 @@
+use anyhow::Result;
+
+use super::{ExportArgs, ExportTarget};
+
+mod classroom;
+mod gradescope;
+
+pub fn run(args: &ExportArgs) -> Result<()> {
+    match &args.target {
+        ExportTarget::Gradescope(a) => {
+            gradescope::run(&a.root, a.out.as_deref(), a.toolchain.as_ref())
+        }
+        ExportTarget::Classroom(a) => classroom::run(&a.root, a.out.as_deref()),
+    }
+}
+
+#[cfg(test)]
+pub mod tests;
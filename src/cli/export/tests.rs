@@ -0,0 +1,167 @@
+use super::gradescope::*;
+use crate::types::*;
+use std::path::Path;
+
+fn write_config(root: &Path, tests: &[AutoTest]) -> anyhow::Result<()> {
+    let autograder = root.join(".autograder");
+    std::fs::create_dir_all(&autograder)?;
+    std::fs::write(
+        autograder.join("autograder.json"),
+        serde_json::to_string_pretty(tests)?,
+    )?;
+    Ok(())
+}
+
+fn test(name: &str, points: u32, kind: TestKind) -> AutoTest {
+    AutoTest {
+        meta: TestMeta {
+            name: name.into(),
+            description: "Ensure at least ## commits.".into(),
+            points,
+            timeout: 10,
+            ..Default::default()
+        },
+        kind,
+        options: TestOptions::default(),
+    }
+}
+
+#[test]
+fn gradescope_bundle_contains_scripts_and_test_metadata() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    let cargo = test(
+        "adds",
+        2,
+        TestKind::CargoTest {
+            manifest_path: None,
+        },
+    );
+    let clippy = test(
+        "CLIPPY_STYLE_CHECK",
+        1,
+        TestKind::Clippy {
+            manifest_path: None,
+        },
+    );
+    let commits = test(
+        "COMMIT_COUNT_3",
+        1,
+        TestKind::CommitCount { min_commits: 3 },
+    );
+    let ungraded = test(
+        "ungraded",
+        0,
+        TestKind::CargoTest {
+            manifest_path: None,
+        },
+    );
+    write_config(root, &[cargo, clippy, commits, ungraded])?;
+
+    run(root, None, Some(&"1.82.0".to_string()))?;
+    let out = root.join(".autograder/gradescope");
+
+    let setup = std::fs::read_to_string(out.join(SETUP_SCRIPT))?;
+    assert!(setup.contains("--default-toolchain 1.82.0 --component clippy\n"));
+
+    let run_script = std::fs::read_to_string(out.join(RUN_SCRIPT))?;
+    assert!(run_script.contains("cd /autograder/submission"));
+    assert!(run_script.contains("run_test 0 10 'cargo test adds'\n"));
+    assert!(run_script.contains("run_test 2 10 'bash ./.autograder/commit_count.sh 3'\n"));
+    assert!(!run_script.contains("ungraded"));
+    assert!(run_script.contains(r#"> "$RESULTS/results.json""#));
+
+    let meta: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(out.join(TESTS_FILE))?)?;
+    assert_eq!(meta.as_array().unwrap().len(), 3);
+    assert_eq!(meta[0]["max_score"], 2);
+    assert_eq!(meta[2]["description"], "Ensure at least 3 commits.");
+
+    assert!(out.join(RESULTS_WRITER).exists());
+    assert!(out.join(".autograder/commit_count.sh").exists());
+    Ok(())
+}
+
+#[test]
+fn gradescope_bundle_respects_out_dir() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    write_config(
+        root,
+        &[test(
+            "adds",
+            1,
+            TestKind::CargoTest {
+                manifest_path: None,
+            },
+        )],
+    )?;
+
+    let out = root.join("bundle");
+    run(root, Some(&out), None)?;
+    assert!(out.join(RUN_SCRIPT).exists());
+    let setup = std::fs::read_to_string(out.join(SETUP_SCRIPT))?;
+    assert!(setup.contains("--default-toolchain stable\n"));
+    Ok(())
+}
+
+#[test]
+fn classroom_export_round_trips_through_import() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    let mut cargo = test(
+        "adds",
+        2,
+        TestKind::CargoTest {
+            manifest_path: None,
+        },
+    );
+    cargo
+        .options
+        .env
+        .insert("RUST_BACKTRACE".into(), "1".into());
+    let mut io = test(
+        "greets",
+        3,
+        TestKind::Io {
+            command: "cargo run -q".into(),
+            input: "Ada".into(),
+            expected_output: "Hello, Ada".into(),
+            comparison: Comparison::Regex,
+        },
+    );
+    io.options.setup = Some("cargo build".into());
+    let commits = test(
+        "COMMIT_COUNT_2",
+        1,
+        TestKind::CommitCount { min_commits: 2 },
+    );
+    write_config(root, &[cargo, io, commits])?;
+
+    super::run(&crate::cli::ExportArgs {
+        target: crate::cli::ExportTarget::Classroom(crate::cli::ClassroomExportArgs {
+            root: root.to_path_buf(),
+            out: None,
+        }),
+    })?;
+    let file = root.join(".github/classroom/autograding.json");
+    let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&file)?)?;
+    let tests = json["tests"].as_array().unwrap();
+    assert_eq!(
+        tests[0]["run"],
+        "export RUST_BACKTRACE='1'; cargo test adds"
+    );
+    assert_eq!(tests[1]["run"], "cargo run -q");
+    assert_eq!(tests[1]["setup"], "cargo build");
+    assert_eq!(tests[1]["output"], "Hello, Ada");
+    assert_eq!(tests[1]["comparison"], "regex");
+    assert_eq!(tests[2]["points"], 1);
+
+    let imported = crate::cli::import::read_classroom_tests(&file)?;
+    assert!(matches!(imported[1].kind, TestKind::Io { .. }));
+    assert!(matches!(
+        imported[2].kind,
+        TestKind::CommitCount { min_commits: 2 }
+    ));
+    Ok(())
+}
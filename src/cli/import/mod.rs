@@ -0,0 +1,97 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+use super::{ImportArgs, ImportSource};
+
+use crate::types::AutoTest;
+use crate::types::classroom::ClassroomConfig;
+use crate::types::config::AutograderConfig;
+use crate::utils::{autograder_config_path, load_autograder_config, write_autograder_config};
+
+mod workflow;
+
+pub fn run(args: &ImportArgs) -> Result<()> {
+    match &args.source {
+        ImportSource::Classroom(a) => {
+            let file = a.file.clone().unwrap_or_else(|| {
+                a.root
+                    .join(".github")
+                    .join("classroom")
+                    .join("autograding.json")
+            });
+            let imported = AutograderConfig::new(read_classroom_tests(&file)?);
+            save_and_report(&a.root, &file, imported, a.force)
+        }
+        ImportSource::Workflow(a) => {
+            let file = a.file.clone().unwrap_or_else(|| {
+                a.root
+                    .join(".github")
+                    .join("workflows")
+                    .join("classroom.yml")
+            });
+            let imported = workflow::read_workflow(&file)?;
+            save_and_report(&a.root, &file, imported, a.force)
+        }
+    }
+}
+
+fn save_and_report(
+    root: &Path,
+    file: &Path,
+    imported: AutograderConfig,
+    force: bool,
+) -> Result<()> {
+    let count = imported.tests.len();
+    let path = save_imported(root, imported, force)?;
+    println!(
+        "Imported {} tests from {} into {}",
+        count,
+        file.to_string_lossy(),
+        path.to_string_lossy()
+    );
+    Ok(())
+}
+
+/// Convert each Classroom UI test into the matching `AutoTest`
+pub fn read_classroom_tests(file: &Path) -> Result<Vec<AutoTest>> {
+    let contents = fs::read_to_string(file)
+        .with_context(|| format!("Failed to read {}", file.to_string_lossy()))?;
+    let config: ClassroomConfig = serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse {}", file.to_string_lossy()))?;
+    Ok(config.tests.iter().map(|t| t.to_auto_test()).collect())
+}
+
+/// Write imported tests, keeping the settings of an existing autograder.json when overwriting.
+/// A toolchain recovered from the import replaces the recorded one.
+pub fn save_imported(
+    root: &Path,
+    imported: AutograderConfig,
+    force: bool,
+) -> Result<std::path::PathBuf> {
+    let mut config = if autograder_config_path(root).exists() {
+        if !force {
+            anyhow::bail!(
+                "{} already exists; pass --force to replace its tests",
+                autograder_config_path(root).to_string_lossy()
+            );
+        }
+        load_autograder_config(root)?
+    } else {
+        AutograderConfig::default()
+    };
+    config.tests = imported.tests;
+    if imported.settings.toolchain.is_some() {
+        config.settings.toolchain = imported.settings.toolchain;
+    }
+    fs::create_dir_all(root.join(".autograder")).with_context(|| {
+        format!(
+            "Failed to create {}",
+            root.join(".autograder").to_string_lossy()
+        )
+    })?;
+    write_autograder_config(root, &config)
+}
+
+#[cfg(test)]
+pub mod tests;
@@ -0,0 +1,266 @@
+use super::*;
+use crate::types::classroom::{Selection, parse_run};
+use crate::types::{Comparison, TestKind};
+use crate::utils::read_autograder_config;
+use crate::utils::write_autograder_config;
+
+const CLASSROOM_JSON: &str = r#"{
+  "tests": [
+    { "name": "Adds small numbers", "setup": "", "run": "cargo test adds_small --release",
+      "input": "", "output": "", "comparison": "included", "timeout": 5, "points": 2 },
+    { "name": "Style", "setup": "", "run": "cargo clippy --manifest-path core/Cargo.toml -- -D warnings",
+      "input": "", "output": "", "comparison": "included", "timeout": 10, "points": 1 },
+    { "name": "Commits", "setup": "", "run": "bash ./.autograder/commit_count.sh 3",
+      "input": "", "output": "", "comparison": "included", "timeout": 10, "points": 1 },
+    { "name": "Greets", "setup": "cargo build", "run": "cargo run -q",
+      "input": "Ada\n", "output": "Hello, Ada", "comparison": "exact", "timeout": 10, "points": 3 },
+    { "name": "Lints docs", "setup": "sudo apt-get install -y aspell", "run": "./check_docs.sh | grep OK",
+      "input": "", "output": "", "comparison": "included", "timeout": 10, "points": null }
+  ]
+}"#;
+
+fn import(root: &Path) -> anyhow::Result<Vec<AutoTest>> {
+    let file = root.join("autograding.json");
+    fs::write(&file, CLASSROOM_JSON)?;
+    read_classroom_tests(&file)
+}
+
+#[test]
+fn classroom_entries_map_to_matching_kinds() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let tests = import(tmp.path())?;
+
+    // cargo test: the filter becomes the name, the UI label the display name
+    assert_eq!(tests[0].meta.name, "adds_small");
+    assert_eq!(
+        tests[0].meta.display_name.as_deref(),
+        Some("Adds small numbers")
+    );
+    assert_eq!((tests[0].meta.points, tests[0].meta.timeout), (2, 5));
+    assert!(matches!(
+        tests[0].kind,
+        TestKind::CargoTest {
+            manifest_path: None
+        }
+    ));
+    assert_eq!(tests[0].options.profile.as_deref(), Some("release"));
+    assert_eq!(tests[0].command(), "cargo test adds_small --release");
+
+    assert!(matches!(
+        &tests[1].kind,
+        TestKind::Clippy { manifest_path: Some(p) } if p == "core/Cargo.toml"
+    ));
+    assert!(matches!(
+        tests[2].kind,
+        TestKind::CommitCount { min_commits: 3 }
+    ));
+
+    match &tests[3].kind {
+        TestKind::Io {
+            command,
+            input,
+            expected_output,
+            comparison,
+        } => {
+            assert_eq!(command, "cargo run -q");
+            assert_eq!(input, "Ada\n");
+            assert_eq!(expected_output, "Hello, Ada");
+            assert_eq!(*comparison, Comparison::Exact);
+        }
+        other => panic!("expected io test, got {other:?}"),
+    }
+    assert_eq!(tests[3].options.setup.as_deref(), Some("cargo build"));
+
+    // Unrecognized commands are kept verbatim; unscored tests get 0 points
+    assert!(
+        matches!(&tests[4].kind, TestKind::Command { command } if command == "./check_docs.sh | grep OK")
+    );
+    assert_eq!(tests[4].meta.points, 0);
+    Ok(())
+}
+
+#[test]
+fn parse_run_rejects_shell_syntax_and_unknown_flags() {
+    assert!(parse_run("cargo test a && cargo test b").is_none());
+    assert!(parse_run("cargo test a --no-fail-fast").is_none());
+    assert!(parse_run("bash ./.autograder/other.sh 2").is_none());
+    let (_, selection, options) =
+        parse_run("cargo test a --features x,y -- --test-threads=1").unwrap();
+    assert_eq!(selection.map(|s| s.filter).as_deref(), Some("a"));
+    assert_eq!(options.features, vec!["x", "y"]);
+    assert_eq!(options.test_threads, Some(1));
+
+    let (_, selection, _) = parse_run("cargo test calc::tests::adds -- --exact").unwrap();
+    assert_eq!(
+        selection,
+        Some(Selection {
+            filter: "calc::tests::adds".into(),
+            exact: true
+        })
+    );
+}
+
+#[test]
+fn import_keeps_settings_and_requires_force_to_overwrite() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    fs::create_dir_all(root.join(".autograder"))?;
+    fs::write(
+        autograder_config_path(root),
+        r#"{ "settings": { "toolchain": "1.82.0" }, "tests": [] }"#,
+    )?;
+
+    let tests = import(root)?;
+    let err = save_imported(root, AutograderConfig::new(tests.clone()), false).unwrap_err();
+    assert!(err.to_string().contains("--force"), "{err}");
+
+    save_imported(root, AutograderConfig::new(tests), true)?;
+    let config = read_autograder_config(root)?;
+    assert_eq!(config.settings.toolchain.as_deref(), Some("1.82.0"));
+    assert_eq!(config.tests.len(), 5);
+    Ok(())
+}
+
+const HAND_WRITTEN: &str = r#"
+name: Autograding Tests
+on: [push]
+jobs:
+  grade:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+      - uses: dtolnay/rust-toolchain@1.80.0
+      - name: Part 1
+        id: part-1
+        uses: classroom-resources/autograding-command-grader@v1
+        env:
+          RUST_BACKTRACE: 1
+        with:
+          test-name: Part 1
+          command: cargo test part_one --manifest-path hw/Cargo.toml
+          timeout: 5
+          max-score: 4
+      - name: Count
+        uses: classroom-resources/autograding-command-grader@v1
+        with:
+          test-name: TEST_COUNT
+          command: "cargo test -- --list | tail -1 | awk '{print $1}' | awk '{if ($1 < 3+7) {print \"Too few tests (\"$1-7\") expected 3\"; exit 1}}'"
+          timeout: 10
+          max-score: 1
+      - name: Docs
+        uses: classroom-resources/autograding-command-grader@v1
+        with:
+          test-name: Docs
+          setup-command: cargo install mdbook
+          command: mdbook test
+          max-score: 2
+      - name: Echo
+        uses: classroom-resources/autograding-io-grader@v1
+        with:
+          test-name: Echo
+          command: cargo run -q
+          input: hi
+          expected-output: hi
+          comparison-method: exact
+          timeout: 10
+          max-score: 1
+"#;
+
+#[test]
+fn workflow_import_recovers_kinds_env_and_toolchain() -> anyhow::Result<()> {
+    let config = workflow::parse_workflow(HAND_WRITTEN)?;
+    let tests = &config.tests;
+    assert_eq!(config.settings.toolchain.as_deref(), Some("1.80.0"));
+    assert_eq!(tests.len(), 4);
+
+    assert_eq!(tests[0].meta.name, "part_one");
+    assert_eq!(tests[0].meta.display_name.as_deref(), Some("Part 1"));
+    assert_eq!((tests[0].meta.points, tests[0].meta.timeout), (4, 5));
+    assert_eq!(tests[0].manifest_path(), Some("hw/Cargo.toml"));
+    assert_eq!(tests[0].options.env["RUST_BACKTRACE"], "1");
+
+    assert!(matches!(
+        tests[1].kind,
+        TestKind::TestCount {
+            min_tests: 3,
+            manifest_path: None
+        }
+    ));
+    assert!(matches!(&tests[2].kind, TestKind::Command { command } if command == "mdbook test"));
+    assert_eq!(
+        tests[2].options.setup.as_deref(),
+        Some("cargo install mdbook")
+    );
+    assert!(matches!(
+        tests[3].kind,
+        TestKind::Io {
+            comparison: Comparison::Exact,
+            ..
+        }
+    ));
+    Ok(())
+}
+
+#[test]
+fn workflow_import_round_trips_a_generated_workflow() -> anyhow::Result<()> {
+    use crate::types::TestMeta;
+    let tmp = tempfile::tempdir()?;
+    let root = tmp.path();
+    let meta = |name: &str| TestMeta {
+        name: name.into(),
+        points: 1,
+        timeout: 10,
+        ..Default::default()
+    };
+    let tests = vec![
+        AutoTest {
+            meta: meta("adds"),
+            kind: TestKind::CargoTest {
+                manifest_path: None,
+            },
+            options: Default::default(),
+        },
+        AutoTest {
+            meta: meta("CLIPPY_STYLE_CHECK"),
+            kind: TestKind::Clippy {
+                manifest_path: None,
+            },
+            options: Default::default(),
+        },
+        AutoTest {
+            meta: meta("BRANCH_COUNT_2"),
+            kind: TestKind::BranchCount { min_branches: 2 },
+            options: Default::default(),
+        },
+    ];
+    fs::create_dir_all(root.join(".autograder"))?;
+    write_autograder_config(root, &AutograderConfig::new(tests))?;
+    crate::cli::build::run(&crate::cli::BuildConfig {
+        root: root.to_path_buf(),
+        ..Default::default()
+    })?;
+
+    let config = workflow::read_workflow(&root.join(".github/workflows/classroom.yml"))?;
+    let kinds: Vec<String> = config
+        .tests
+        .iter()
+        .map(|t| format!("{}:{:?}", t.meta.name, t.kind))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            "adds:CargoTest { manifest_path: None }",
+            "CLIPPY_STYLE_CHECK:Clippy { manifest_path: None }",
+            "BRANCH_COUNT_2:BranchCount { min_branches: 2 }",
+        ]
+    );
+    assert_eq!(config.settings.toolchain.as_deref(), Some("stable"));
+    Ok(())
+}
+
+#[test]
+fn workflow_without_grader_steps_is_an_error() {
+    let err =
+        workflow::parse_workflow("jobs:\n  a:\n    steps:\n      - run: echo hi\n").unwrap_err();
+    assert!(err.to_string().contains("No autograding"), "{err}");
+}
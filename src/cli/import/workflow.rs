@@ -0,0 +1,121 @@
+// Recover autograder.json from a (possibly hand-written) GitHub Classroom workflow.
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+use crate::cli::build::actions::TOOLCHAIN_ACTION;
+use crate::types::classroom::ClassroomTest;
+use crate::types::config::AutograderConfig;
+use crate::types::{AutoTest, Comparison};
+use crate::utils::yaml;
+
+const COMMAND_GRADER: &str = "autograding-command-grader";
+const IO_GRADER: &str = "autograding-io-grader";
+
+/// Parse every grader step in the workflow, in job then step order
+pub fn read_workflow(file: &Path) -> Result<AutograderConfig> {
+    let contents = fs::read_to_string(file)
+        .with_context(|| format!("Failed to read {}", file.to_string_lossy()))?;
+    parse_workflow(&contents).with_context(|| format!("Failed to parse {}", file.to_string_lossy()))
+}
+
+pub fn parse_workflow(contents: &str) -> Result<AutograderConfig> {
+    let doc = yaml::from_str(contents)?;
+    let jobs = doc
+        .get("jobs")
+        .and_then(Value::as_object)
+        .ok_or_else(|| anyhow::anyhow!("Workflow has no `jobs`"))?;
+
+    let mut config = AutograderConfig::default();
+    for job in jobs.values() {
+        let Some(steps) = job.get("steps").and_then(Value::as_array) else {
+            continue;
+        };
+        for step in steps {
+            let uses = str_field(step, "uses");
+            if uses.contains(COMMAND_GRADER) || uses.contains(IO_GRADER) {
+                config.tests.push(grader_step_to_test(step)?);
+            } else if let Some(toolchain) = toolchain_of(step, &uses) {
+                config.settings.toolchain = Some(toolchain);
+            }
+        }
+    }
+
+    if config.tests.is_empty() {
+        anyhow::bail!("No autograding-command-grader or autograding-io-grader steps found");
+    }
+    Ok(config)
+}
+
+/// Reuse the Classroom mapping: a grader step carries the same fields as a UI-configured test
+fn grader_step_to_test(step: &Value) -> Result<AutoTest> {
+    let with = step.get("with").ok_or_else(|| {
+        anyhow::anyhow!("Grader step `{}` has no `with:`", str_field(step, "name"))
+    })?;
+    let name = match str_field(with, "test-name") {
+        n if n.is_empty() => str_field(step, "name"),
+        n => n,
+    };
+    let comparison = match str_field(with, "comparison-method").as_str() {
+        "exact" => Comparison::Exact,
+        "regex" => Comparison::Regex,
+        _ => Comparison::Included,
+    };
+    let entry = ClassroomTest {
+        name,
+        setup: str_field(with, "setup-command"),
+        run: str_field(with, "command"),
+        input: str_field(with, "input"),
+        output: str_field(with, "expected-output"),
+        comparison,
+        timeout: num_field(with, "timeout").unwrap_or(10),
+        points: num_field(with, "max-score").map(|p| p as u32),
+    };
+
+    let mut test = entry.to_auto_test();
+    if let Some(env) = step.get("env").and_then(Value::as_object) {
+        for (k, v) in env {
+            if let Some(v) = scalar(v) {
+                test.options.env.insert(k.clone(), v);
+            }
+        }
+    }
+    Ok(test)
+}
+
+/// `dtolnay/rust-toolchain@<rev>`, or its `toolchain:` input when installed from `@master`
+fn toolchain_of(step: &Value, uses: &str) -> Option<String> {
+    let (action, rev) = uses.split_once('@')?;
+    if !action.ends_with(TOOLCHAIN_ACTION.split('/').next_back()?) {
+        return None;
+    }
+    let input = step.get("with").map(|w| str_field(w, "toolchain"));
+    match input.filter(|t| !t.is_empty()) {
+        Some(t) => Some(t),
+        // A pinned SHA says nothing about the toolchain
+        None if rev == "master" || rev.len() == 40 => None,
+        None => Some(rev.split_whitespace().next()?.to_string()),
+    }
+}
+
+fn scalar(v: &Value) -> Option<String> {
+    match v {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn str_field(v: &Value, key: &str) -> String {
+    v.get(key).and_then(scalar).unwrap_or_default()
+}
+
+fn num_field(v: &Value, key: &str) -> Option<u64> {
+    match v.get(key)? {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
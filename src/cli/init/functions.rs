@@ -1,92 +1,177 @@
-use crate::types::{AutoTest, TestKind, TestMeta};
+use crate::types::{AutoTest, TestKind, TestMeta, TestOptions, template};
+use crate::utils::i18n::Catalog;
+use crate::utils::package_name;
+use anyhow::{Context, Result};
 /// A helper module for individual AutoTest Creation
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+/// Kinds `init` generates, with their default name templates for the root crate and for members
+pub const GENERATED_CHECKS: &[(&str, &str, &str)] = &[
+    (
+        "clippy",
+        "CLIPPY_STYLE_CHECK",
+        "CLIPPY_STYLE_CHECK_{member}",
+    ),
+    ("commit_count", "COMMIT_COUNT_{min}", "COMMIT_COUNT_{min}"),
+    ("branch_count", "BRANCH_COUNT_{min}", "BRANCH_COUNT_{min}"),
+    (
+        "test_count",
+        "TEST_COUNT_{min}",
+        "TEST_COUNT_{member}_{min}",
+    ),
+];
+
+/// What every generated check shares
+pub struct CheckContext<'a> {
+    pub root: &'a Path,
+    pub points: u32,
+    /// Descriptions, in the chosen language
+    pub strings: &'a Catalog,
+    /// `settings.name_templates`: kind -> name template, replacing the defaults above
+    pub names: &'a BTreeMap<String, String>,
+}
+
+impl CheckContext<'_> {
+    /// Name `test` from its kind's template. `{min}` is the threshold and `{member}` the package
+    /// name of the crate it checks; the test's own fields (`{min_commits}`, ...) work too.
+    fn named(&self, mut test: AutoTest, min: Option<u32>) -> Result<AutoTest> {
+        let kind = test.kind.type_name();
+        let manifest_path = test.manifest_path().map(str::to_string);
+        let template = match self.names.get(kind) {
+            Some(t) => t.as_str(),
+            None => GENERATED_CHECKS
+                .iter()
+                .find(|(k, _, _)| *k == kind)
+                .map(|(_, root, member)| match manifest_path {
+                    Some(_) => *member,
+                    None => *root,
+                })
+                .unwrap_or_default(),
+        };
+
+        let mut vars = test.template_vars();
+        if let Some(min) = min {
+            vars.push(("min", min.to_string()));
+        }
+        if matches!(kind, "clippy" | "test_count") {
+            vars.push(("member", self.member_name(manifest_path.as_deref())));
+        }
+        test.meta.name = template::render(template, &vars, kind)
+            .with_context(|| format!("In the name template for {kind} checks"))?;
+        Ok(test)
+    }
+
+    /// `[package] name` of the crate, else its directory name
+    fn member_name(&self, manifest_path: Option<&str>) -> String {
+        let manifest = self.root.join(manifest_path.unwrap_or("Cargo.toml"));
+        package_name(&manifest).unwrap_or_else(|| {
+            let dir = manifest.parent().unwrap_or(self.root);
+            let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+            dir.file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| manifest_dir_label(manifest_path.unwrap_or("Cargo.toml")))
+        })
+    }
+}
 
 /// Collects manifest_paths into different clippy checks
-pub fn clippy_autotests(manifest_paths: &HashSet<String>, points: u32) -> Vec<AutoTest> {
-    manifest_paths
-        .iter()
-        .map(|mp| clippy_autotest_for(mp, points))
+pub fn clippy_autotests(
+    manifest_paths: &HashSet<String>,
+    ctx: &CheckContext,
+) -> Result<Vec<AutoTest>> {
+    // Stable order for deterministic output
+    let mut mps: Vec<&String> = manifest_paths.iter().collect();
+    mps.sort();
+    mps.into_iter()
+        .map(|mp| ctx.named(clippy_autotest_for(mp, ctx), None))
         .collect()
 }
 
-fn clippy_autotest_for(manifest_path: &str, points: u32) -> AutoTest {
+fn clippy_autotest_for(manifest_path: &str, ctx: &CheckContext) -> AutoTest {
     let dir = manifest_dir_label(manifest_path); // "." | "Cargo.toml" | "member"
 
-    let name: String;
     let doc: String;
     let manifest_path_opt: Option<String>;
 
     if matches!(dir.as_str(), "." | "Cargo.toml") {
-        name = "CLIPPY_STYLE_CHECK".to_string();
-        doc = "`cargo clippy` style check".to_string();
+        doc = ctx.strings.text("init.clippy").to_string();
         manifest_path_opt = None;
     } else {
-        name = format!("CLIPPY_STYLE_CHECK_{}", dir);
-        doc = format!("`cargo clippy` style check for `{}`", dir);
+        doc = ctx.strings.fmt("init.clippy_member", &[("dir", &dir)]);
         manifest_path_opt = Some(manifest_path.to_string());
     }
 
     AutoTest {
         meta: TestMeta {
-            name,
-            points,
+            points: ctx.points,
             timeout: 10,
             description: doc.clone(),
+            ..Default::default()
         },
         kind: TestKind::Clippy {
             manifest_path: manifest_path_opt,
         },
+        options: TestOptions::default(),
     }
 }
 
 /// A generic helper to create threshold-based autotests
 fn threshold_autotests<I>(
     iterator: I,
-    points: u32,
-    prefix: &str,
-    description: &str,
+    ctx: &CheckContext,
+    description: impl Fn(u32) -> String,
     mk_kind: impl Fn(u32) -> TestKind,
-) -> Vec<AutoTest>
+) -> Result<Vec<AutoTest>>
 where
     I: Iterator<Item = u32>,
 {
     iterator
-        .map(|i| AutoTest {
-            meta: TestMeta {
-                name: format!("{}_{}", prefix, i),
-                timeout: 10,
-                points,
-                description: format!("Ensures at least {} {}.", i, description),
-            },
-            kind: mk_kind(i),
+        .map(|i| {
+            let test = AutoTest {
+                meta: TestMeta {
+                    timeout: 10,
+                    points: ctx.points,
+                    description: description(i),
+                    ..Default::default()
+                },
+                kind: mk_kind(i),
+                options: TestOptions::default(),
+            };
+            ctx.named(test, Some(i))
         })
         .collect()
 }
 
-pub fn commit_count_autotests<I>(iterator: I, points: u32) -> Vec<AutoTest>
+pub fn commit_count_autotests<I>(iterator: I, ctx: &CheckContext) -> Result<Vec<AutoTest>>
 where
     I: Iterator<Item = u32>,
 {
-    threshold_autotests(iterator, points, "COMMIT_COUNT", "commits", |i| {
-        TestKind::CommitCount { min_commits: i }
-    })
+    threshold_autotests(
+        iterator,
+        ctx,
+        |i| ctx.strings.fmt("init.commit_count", &[("count", &i)]),
+        |i| TestKind::CommitCount { min_commits: i },
+    )
 }
 
-pub fn branch_count_autotests<I>(iterator: I, points: u32) -> Vec<AutoTest>
+pub fn branch_count_autotests<I>(iterator: I, ctx: &CheckContext) -> Result<Vec<AutoTest>>
 where
     I: Iterator<Item = u32>,
 {
-    threshold_autotests(iterator, points, "BRANCH_COUNT", "branches", |i| {
-        TestKind::BranchCount { min_branches: i }
-    })
+    threshold_autotests(
+        iterator,
+        ctx,
+        |i| ctx.strings.fmt("init.branch_count", &[("count", &i)]),
+        |i| TestKind::BranchCount { min_branches: i },
+    )
 }
 
 pub fn test_count_autotests<I>(
     iterator: I,
-    points: u32,
     manifest_paths: &HashSet<String>,
-) -> Vec<AutoTest>
+    ctx: &CheckContext,
+) -> Result<Vec<AutoTest>>
 where
     I: Iterator<Item = u32>,
 {
@@ -100,47 +185,62 @@ where
     let mut out = Vec::new();
     for mp in mps {
         for req in &required {
-            out.push(test_count_autotest_for(&mp, points, *req));
+            out.push(ctx.named(test_count_autotest_for(&mp, *req, ctx), Some(*req))?);
         }
     }
-    out
+    Ok(out)
 }
 
-/// Likely needs to be refactored for iterator based solution
-fn test_count_autotest_for(manifest_path: &str, points: u32, required_tests: u32) -> AutoTest {
+fn test_count_autotest_for(
+    manifest_path: &str,
+    required_tests: u32,
+    ctx: &CheckContext,
+) -> AutoTest {
     let dir = manifest_dir_label(manifest_path);
 
-    let name: String;
     let docstring: String;
     let manifest_path_opt: Option<String>;
 
-    // THis can go in a function
     if matches!(dir.as_str(), "." | "Cargo.toml") {
-        docstring = format!("Submission has at least {} tests", required_tests);
+        docstring = ctx
+            .strings
+            .fmt("init.test_count", &[("count", &required_tests)]);
         manifest_path_opt = None;
-        name = format!("TEST_COUNT_{}", required_tests);
     } else {
-        docstring = format!("{} submission has at least {} tests", dir, required_tests);
-        manifest_path_opt = Some(manifest_path.to_string());
-        name = format!(
-            "TEST_COUNT_{}_{}",
-            manifest_path.to_uppercase(),
-            required_tests
+        docstring = ctx.strings.fmt(
+            "init.test_count_member",
+            &[("dir", &dir), ("count", &required_tests)],
         );
+        manifest_path_opt = Some(manifest_path.to_string());
     }
 
     AutoTest {
         meta: TestMeta {
-            name,
-            points,
+            points: ctx.points,
             timeout: 10,
             description: docstring.clone(),
+            ..Default::default()
         },
         kind: TestKind::TestCount {
             min_tests: required_tests,
             manifest_path: manifest_path_opt,
         },
+        options: TestOptions::default(),
+    }
+}
+
+/// A name template without `{member}` or `{min}` gives several checks the same name
+pub fn ensure_unique_names(generated: &[AutoTest]) -> Result<()> {
+    for (i, test) in generated.iter().enumerate() {
+        if generated[..i].iter().any(|t| t.meta.name == test.meta.name) {
+            anyhow::bail!(
+                "Several {} checks are named `{}`; add {{member}} or {{min}} to --name-template",
+                test.kind.type_name(),
+                test.meta.name
+            );
+        }
     }
+    Ok(())
 }
 
 /// Turn ".../Cargo.toml" into "member" or "." for workspace root.
@@ -1,15 +1,28 @@
 use super::RunConfig;
+use super::schema;
 use crate::types::AutoTest;
-use crate::utils::{collect_rs_files_with_manifest, ensure_exists, get_tests_dir};
+use crate::types::config::{AutograderConfig, Settings};
+use crate::utils::config_format::ConfigFormat;
+use crate::utils::extends;
+use crate::utils::i18n::Catalog;
+use crate::utils::toolchain::detect_toolchain;
+use crate::utils::{
+    autograder_config_format, autograder_config_path, collect_rs_files_with_manifest,
+    ensure_exists, existing_config_paths, get_tests_dir, load_autograder_config, to_rel_unix_path,
+    write_autograder_config_as,
+};
 use anyhow::{Context, Result};
-use std::{fs, io::Write};
+use std::fs;
 
+pub use functions::GENERATED_CHECKS;
 use functions::*;
 use scan::{TestWithManifest, find_all_tests};
 mod functions;
-mod scan;
+pub mod scan;
 #[cfg(test)]
+#[allow(clippy::empty_line_after_doc_comments)] // `///` section headers in the tests
 mod tests;
+pub mod update;
 
 // This many function args are necessary for flag parsing
 #[allow(clippy::too_many_arguments)]
@@ -36,9 +49,17 @@ pub fn run(cfg: &RunConfig) -> Result<()> {
     let out_dir = &cfg.root.join(".autograder");
     fs::create_dir_all(out_dir)
         .with_context(|| format!("Failed to create {}", out_dir.to_string_lossy()))?;
-    let out_path = out_dir.join("autograder.json");
 
     // ---- Convert discovered tests to AutoTests -------------------------------
+    let settings = init_settings(cfg)?;
+    let strings = Catalog::for_project(&cfg.root, &settings, None, None)?;
+    let ctx = CheckContext {
+        root: &cfg.root,
+        points: cfg.num_points,
+        strings: &strings,
+        names: &settings.name_templates,
+    };
+    let mut generated = Vec::new();
     let manifest_paths = TestWithManifest::get_distinct_manifest_paths(&tests, &cfg.root);
 
     let mut items: Vec<AutoTest> = tests
@@ -47,39 +68,133 @@ pub fn run(cfg: &RunConfig) -> Result<()> {
         .collect();
 
     if cfg.style_check {
-        items.extend(clippy_autotests(&manifest_paths, cfg.num_points));
+        generated.extend(clippy_autotests(&manifest_paths, &ctx)?);
     }
 
     let commit_thresholds = cfg.resolve_commit_thresholds();
     if !commit_thresholds.is_empty() {
-        items.extend(commit_count_autotests(
-            commit_thresholds.into_iter(),
-            cfg.num_points,
-        ))
+        generated.extend(commit_count_autotests(commit_thresholds.into_iter(), &ctx)?)
     }
 
     // ---- Branch counting logic ----------------------------------------------
     if !cfg.require_branches.is_empty() {
-        items.extend(branch_count_autotests(
+        generated.extend(branch_count_autotests(
             cfg.require_branches.iter().copied(),
-            cfg.num_points,
-        ));
+            &ctx,
+        )?);
     }
     // ---- Test count steps ----------------------------------------------------
     if !cfg.require_tests.is_empty() {
-        items.extend(test_count_autotests(
+        generated.extend(test_count_autotests(
             cfg.require_tests.iter().copied(),
-            cfg.num_points,
             &manifest_paths,
-        ));
+            &ctx,
+        )?);
     }
+    ensure_unique_names(&generated)?;
+    items.extend(generated);
+
+    if cfg.update {
+        return update_existing(cfg, items);
+    }
+
+    // ---- Record the grading toolchain ----------------------------------------
+    let mut config = AutograderConfig::new(items);
+    config.schema = Some(schema::SCHEMA_REF.to_string());
+    config.settings.toolchain = match &cfg.toolchain {
+        Some(t) => Some(t.clone()),
+        None => detect_toolchain(&cfg.root)?,
+    };
+    if let Some(t) = &config.settings.toolchain {
+        println!("Recording toolchain {}", t);
+    }
+    config.settings.lang = settings.lang;
+    config.settings.translations = settings.translations;
+    config.settings.name_templates = settings.name_templates;
 
     // ---- Write config --------------------------------------------------------
-    let json = serde_json::to_string_pretty(&items)?;
-    let mut f = fs::File::create(&out_path)
-        .with_context(|| format!("Failed to create {}", out_path.to_string_lossy()))?;
-    f.write_all(json.as_bytes())?;
+    let format = cfg
+        .format
+        .unwrap_or_else(|| autograder_config_format(&cfg.root));
+    let out_path = write_autograder_config_as(&cfg.root, &config, format)?;
+
+    // A config in another format would now be ambiguous
+    for stale in existing_config_paths(&cfg.root) {
+        if stale != out_path {
+            fs::remove_file(&stale)
+                .with_context(|| format!("Failed to remove {}", stale.to_string_lossy()))?;
+            println!("Removed {}", stale.to_string_lossy());
+        }
+    }
 
+    schema::write_schema_file(&cfg.root)?;
     println!("Wrote {}", out_path.to_string_lossy());
     Ok(())
 }
+
+/// The language and name templates to generate with: the flags, on top of the existing config's
+/// settings for `--update`
+fn init_settings(cfg: &RunConfig) -> Result<Settings> {
+    let mut settings = if cfg.update {
+        load_autograder_config(&cfg.root)
+            .map(|c| c.settings)
+            .unwrap_or_default()
+    } else {
+        Settings::default()
+    };
+    if let Some(lang) = &cfg.lang {
+        settings.lang = Some(lang.clone());
+    }
+    if let Some(path) = &cfg.translations {
+        settings.translations = Some(to_rel_unix_path(&cfg.root, path));
+    }
+    settings.name_templates.extend(cfg.name_templates.clone());
+    Ok(settings)
+}
+
+/// Merge the scanned tests into the existing config instead of replacing it
+fn update_existing(cfg: &RunConfig, scanned: Vec<AutoTest>) -> Result<()> {
+    let path = autograder_config_path(&cfg.root);
+    if !path.exists() {
+        anyhow::bail!(
+            "Nothing to update: {} does not exist. Run `autograder-setup init` first.",
+            path.to_string_lossy()
+        );
+    }
+    if let Some(format) = cfg.format
+        && ConfigFormat::from_path(&path) != Some(format)
+    {
+        anyhow::bail!(
+            "{} is not {:?}; use `autograder-setup convert` to change formats",
+            path.to_string_lossy(),
+            format
+        );
+    }
+
+    let existing = load_autograder_config(&cfg.root)?;
+    let resolved = extends::resolve(&existing, &path)?;
+    let toolchain = match &cfg.toolchain {
+        Some(t) => Some(t.clone()),
+        None if resolved.settings.toolchain.is_none() => detect_toolchain(&cfg.root)?,
+        None => None,
+    };
+    let merge = update::merge(&existing, &resolved, &scanned, toolchain);
+    if merge.is_empty() {
+        println!("{} is up to date", path.to_string_lossy());
+        return Ok(());
+    }
+
+    for i in &merge.remove {
+        println!("Removed {} (no longer found)", existing.tests[*i].meta.name);
+    }
+    for t in &merge.add {
+        println!("Added {}", t.meta.name);
+    }
+    if let Some(t) = &merge.toolchain {
+        println!("Recording toolchain {}", t);
+    }
+    let out_path = update::write_merge(&cfg.root, &existing, &merge)?;
+    schema::write_schema_file(&cfg.root)?;
+    println!("Updated {}", out_path.to_string_lossy());
+    Ok(())
+}
@@ -8,7 +8,7 @@ use syn::parse::Parser;
 use syn::punctuated::Punctuated;
 use syn::{Attribute, Expr, File, Item, ItemFn, Lit, Meta, visit::Visit};
 
-use crate::types::{AutoTest, TestKind, TestMeta};
+use crate::types::{AutoTest, SourceLocation, TestKind, TestMeta, TestOptions, template};
 use crate::utils::{RustFile, to_rel_unix_path};
 
 #[cfg(test)]
@@ -28,6 +28,7 @@ pub fn find_all_tests(files: &[RustFile]) -> Result<Vec<TestWithManifest>> {
             .into_iter()
             .map(|t| TestWithManifest {
                 test: t,
+                file: file.path.clone(),
                 manifest_path: file.manifest_path.clone(),
             })
             .collect();
@@ -58,6 +59,8 @@ pub fn extract_tests(src: &str) -> Result<Vec<Test>> {
 #[derive(Clone)]
 pub struct TestWithManifest {
     pub test: Test,
+    /// The .rs file the test was found in
+    pub file: PathBuf,
     pub manifest_path: Option<PathBuf>,
 }
 impl TestWithManifest {
@@ -95,9 +98,16 @@ impl TestWithManifest {
                 name,
                 timeout: 10,
                 points: num_points,
-                description: self.test.docstring.clone(),
+                // Doc comments are plain text, not templates
+                description: template::escape(&self.test.docstring),
+                source: Some(SourceLocation {
+                    file: to_rel_unix_path(root, &self.file),
+                    line: self.test.line,
+                }),
+                ..Default::default()
             },
             kind: TestKind::CargoTest { manifest_path },
+            options: TestOptions::default(),
         }
     }
 }
@@ -106,6 +116,8 @@ impl TestWithManifest {
 pub struct Test {
     pub name: String,
     pub docstring: String,
+    /// 1-based line of the function name
+    pub line: usize,
 }
 
 #[derive(Default)]
@@ -134,7 +146,12 @@ impl<'ast> Visit<'ast> for TestFinder {
         if has_test_attr(&f.attrs) {
             let name = f.sig.ident.to_string();
             let docstring = collect_docstring(&f.attrs);
-            self.tests.push(Test { name, docstring });
+            let line = f.sig.ident.span().start().line;
+            self.tests.push(Test {
+                name,
+                docstring,
+                line,
+            });
         }
         // No need to recurse into fn bodies for this task
     }
@@ -363,3 +363,14 @@ fn doc_attribute_form_is_supported() {
     let t = by_name(&tests, "explicit_attr");
     assert_eq!(t.docstring, "first\nsecond");
 }
+
+/// ------------------- source locations -------------------
+
+#[test]
+fn records_the_line_of_each_function_name() {
+    let src = "mod tests {\n    /// doc\n    #[test]\n    fn first() {}\n\n    #[test]\n    #[should_panic]\n    fn second() {}\n}\n";
+
+    let tests = extract_tests(src).expect("parse error");
+    assert_eq!(by_name(&tests, "first").line, 4);
+    assert_eq!(by_name(&tests, "second").line, 8);
+}
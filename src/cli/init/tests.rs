@@ -1,6 +1,9 @@
 // Tests and test harness for init cli
-use crate::cli::RunConfig;
+use crate::cli::build;
+use crate::cli::{BuildConfig, RunConfig};
+use crate::types::config::Triggers;
 use crate::types::{AutoTest, TestKind};
+use crate::utils::config_format::ConfigFormat;
 use crate::utils::read_autograder_config;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -77,7 +80,9 @@ impl Harness {
         build(&mut cfg);
 
         super::run(&cfg).expect("run() failed");
-        read_autograder_config(self.root()).expect("read_autograder_config failed")
+        read_autograder_config(self.root())
+            .expect("read_autograder_config failed")
+            .tests
     }
 }
 
@@ -209,7 +214,341 @@ fn creates_test_count_steps_for_each_manifest_in_workspace() {
         vec![(None, 5), (Some("member/Cargo.toml".into()), 5)]
     );
 
-    // Names: root is "TEST_COUNT", member includes uppercased path
-    assert!(items.iter().any(|t| t.meta.name.contains("TEST_COUNT")));
-    assert!(has_member_named(&items, "MEMBER/CARGO.TOML"));
+    // Names: root is "TEST_COUNT_5", the member's includes its package name
+    assert!(items.iter().any(|t| t.meta.name == "TEST_COUNT_5"));
+    assert!(has_member_named(&items, "TEST_COUNT_member_5"));
+    assert!(has_member_named(&items, "CLIPPY_STYLE_CHECK_member"));
+}
+
+/// ------------------- name templates -------------------
+
+#[test]
+fn name_templates_use_the_package_name_and_are_recorded() {
+    let h = Harness::new();
+    h.write_workspace_root(&["crates/core"]);
+    h.write_member_crate("crates/core", "#[test] fn t() {}");
+    fs::write(
+        h.root().join("crates/core/Cargo.toml"),
+        "[package]\nname=\"shapes-core\"\nversion=\"0.1.0\"\n",
+    )
+    .unwrap();
+
+    let items = h.run(|c| {
+        c.style_check = true;
+        c.commit_counts_flag = true;
+        c.require_commits = vec![2, 4];
+        c.require_tests = vec![3];
+        c.name_templates = vec![
+            ("clippy".into(), "Style: {member}".into()),
+            ("commit_count".into(), "At least {min} commits".into()),
+        ];
+    });
+
+    let names: Vec<&str> = items.iter().map(|t| t.meta.name.as_str()).collect();
+    assert!(names.contains(&"Style: shapes-core"));
+    assert!(names.contains(&"At least 2 commits"));
+    assert!(names.contains(&"At least 4 commits"));
+    assert!(names.contains(&"TEST_COUNT_shapes-core_3"));
+
+    let settings = read_autograder_config(h.root()).unwrap().settings;
+    assert_eq!(
+        settings.name_templates.get("clippy").map(String::as_str),
+        Some("Style: {member}")
+    );
+}
+
+#[test]
+fn name_templates_that_collide_are_rejected() {
+    let h = Harness::new();
+    h.write_root_crate("#[test] fn a() {}", "root");
+    let cfg = RunConfig {
+        root: h.root().to_path_buf(),
+        tests_dir_name: h.root().to_path_buf(),
+        commit_counts_flag: true,
+        require_commits: vec![1, 2],
+        name_templates: vec![("commit_count".into(), "Commits".into())],
+        ..Default::default()
+    };
+
+    let err = super::run(&cfg).unwrap_err().to_string();
+    assert!(err.contains("Several commit_count checks are named `Commits`"));
+}
+
+#[test]
+fn update_matches_renamed_generated_checks() {
+    let h = Harness::new();
+    h.write_root_crate("#[test] fn a() {}", "root");
+    h.run(|c| {
+        c.commit_counts_flag = true;
+        c.require_commits = vec![3];
+        c.name_templates = vec![("commit_count".into(), "At least {min} commits".into())];
+    });
+
+    let items = h.run(|c| {
+        c.update = true;
+        c.commit_counts_flag = true;
+        c.require_commits = vec![3];
+    });
+    assert_eq!(commit_mins(&items), vec![3]);
+    assert!(has_member_named(&items, "At least 3 commits"));
+}
+
+#[test]
+fn update_keeps_cargo_tests_selected_by_path_or_filter() {
+    let h = Harness::new();
+    h.write_root_crate(
+        "#[test] fn adds() {}\n#[test] fn subtracts() {}\n#[test] fn gone() {}",
+        "root",
+    );
+    h.run(|_| {});
+
+    let path = h.root().join(".autograder/autograder.json");
+    let raw = fs::read_to_string(&path)
+        .unwrap()
+        .replace(
+            r#""name": "adds""#,
+            r#""name": "Adds small numbers", "path": "tests::adds""#,
+        )
+        .replace(
+            r#""name": "subtracts""#,
+            r#""name": "Subtraction", "filter": "subtracts""#,
+        );
+    fs::write(&path, raw).unwrap();
+
+    h.write_root_crate("#[test] fn adds() {}\n#[test] fn subtracts() {}", "root");
+    let items = h.run(|c| c.update = true);
+    let names: Vec<_> = items
+        .iter()
+        .filter(|t| matches!(t.kind, TestKind::CargoTest { .. }))
+        .map(|t| t.meta.name.as_str())
+        .collect();
+    assert_eq!(names, ["Adds small numbers", "Subtraction"]);
+}
+
+/// ------------------- doc comments into descriptions -------------------
+
+#[test]
+fn braces_in_doc_comments_survive_init_then_build() {
+    let h = Harness::new();
+    h.write_root_crate(
+        "/// Formats a point as `{value}` for {name}\n#[test] fn fmt_point() {}",
+        "root",
+    );
+    let items = h.run(|_| {});
+    assert_eq!(
+        items[0].meta.description,
+        "Formats a point as `{{value}}` for {{name}}"
+    );
+
+    let cfg = BuildConfig {
+        root: h.root().to_path_buf(),
+        triggers: Triggers {
+            grade_on_push: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    build::run(&cfg).expect("build should accept a scanned doc comment");
+    let config = read_autograder_config(h.root()).unwrap();
+    let built = build::apply_test_defaults(config.tests, &config.settings).unwrap();
+    assert_eq!(
+        built[0].meta.description,
+        "Formats a point as `{value}` for {name}"
+    );
+}
+
+/// ------------------- toolchain recording -------------------
+
+#[test]
+fn records_toolchain_from_rust_toolchain_file() {
+    let h = Harness::new();
+    h.write_root_crate("#[test] fn a() {}", "root");
+    fs::write(
+        h.root().join("rust-toolchain.toml"),
+        "[toolchain]\nchannel = \"1.82.0\"\ncomponents = [\"clippy\"]\n",
+    )
+    .unwrap();
+
+    h.run(|_| {});
+    let config = read_autograder_config(h.root()).unwrap();
+    assert_eq!(config.settings.toolchain.as_deref(), Some("1.82.0"));
+}
+
+#[test]
+fn toolchain_flag_wins_and_config_is_versioned() {
+    let h = Harness::new();
+    h.write_root_crate("#[test] fn a() {}", "root");
+
+    h.run(|_| {});
+    let raw = fs::read_to_string(h.root().join(".autograder/autograder.json")).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&raw).unwrap();
+    assert_eq!(json["version"], 2);
+    assert!(json["tests"].is_array());
+
+    h.run(|c| c.toolchain = Some("1.79.0".into()));
+    let config = read_autograder_config(h.root()).unwrap();
+    assert_eq!(config.settings.toolchain.as_deref(), Some("1.79.0"));
+}
+
+/// ------------------- formats and --update -------------------
+
+#[test]
+fn writes_the_requested_format_and_replaces_the_old_file() {
+    let h = Harness::new();
+    h.write_root_crate("#[test] fn a() {}", "root");
+
+    h.run(|_| {});
+    let items = h.run(|c| c.format = Some(ConfigFormat::Toml));
+    assert_eq!(items.len(), 1);
+    assert!(h.root().join(".autograder/autograder.toml").exists());
+    assert!(!h.root().join(".autograder/autograder.json").exists());
+
+    // Without --format the existing format is kept
+    h.run(|_| {});
+    assert!(h.root().join(".autograder/autograder.toml").exists());
+}
+
+#[test]
+fn update_keeps_toml_comments_and_edits() {
+    let h = Harness::new();
+    h.write_root_crate("#[test] fn a() {}\n#[test] fn b() {}", "root");
+    h.run(|c| c.format = Some(ConfigFormat::Toml));
+
+    let path = h.root().join(".autograder/autograder.toml");
+    let raw = fs::read_to_string(&path)
+        .unwrap()
+        .replace(
+            "[[tests]]\nmeta = { name = \"a\"",
+            "# worth the most\n[[tests]]\nmeta = { name = \"a\"",
+        )
+        .replace("points = 1, timeout = 10,", "points = 5, timeout = 10,");
+    let raw = format!("# Lab 3 grading\n{raw}");
+    fs::write(&path, &raw).unwrap();
+
+    // `b` is gone, `c` is new
+    h.write_root_crate("#[test] fn a() {}\n#[test] fn c() {}", "root");
+    let items = h.run(|c| {
+        c.update = true;
+        c.toolchain = Some("1.82.0".into());
+    });
+
+    let names: Vec<_> = items.iter().map(|t| t.meta.name.as_str()).collect();
+    assert_eq!(names, ["a", "c"]);
+    assert_eq!(items[0].meta.points, 5);
+    let updated = fs::read_to_string(&path).unwrap();
+    assert!(updated.starts_with("# Lab 3 grading\n"), "{updated}");
+    assert!(updated.contains("# worth the most\n[[tests]]"), "{updated}");
+    assert!(
+        updated.find("[settings]") < updated.find("[[tests]]"),
+        "{updated}"
+    );
+    let config = read_autograder_config(h.root()).unwrap();
+    assert_eq!(config.settings.toolchain.as_deref(), Some("1.82.0"));
+}
+
+#[test]
+fn update_keeps_yaml_comments() {
+    let h = Harness::new();
+    h.write_root_crate("#[test] fn a() {}\n#[test] fn b() {}", "root");
+    h.run(|c| {
+        c.format = Some(ConfigFormat::Yaml);
+        c.toolchain = Some("1.80.0".into());
+    });
+
+    let path = h.root().join(".autograder/autograder.yaml");
+    let raw = fs::read_to_string(&path).unwrap();
+    let raw = format!(
+        "# Lab 3 grading\n{}",
+        raw.replacen("  - meta:\n", "  # first test\n  - meta:\n", 1)
+    );
+    let raw = raw
+        .replacen("  - meta:\n", "  # second test\n  - meta:\n", 2)
+        .replacen("  # second test\n", "", 1);
+    fs::write(&path, &raw).unwrap();
+
+    h.write_root_crate("#[test] fn b() {}\n#[test] fn c() {}", "root");
+    let items = h.run(|c| {
+        c.update = true;
+        c.toolchain = Some("1.82.0".into());
+    });
+
+    let names: Vec<_> = items.iter().map(|t| t.meta.name.as_str()).collect();
+    assert_eq!(names, ["b", "c"]);
+    let updated = fs::read_to_string(&path).unwrap();
+    assert!(updated.starts_with("# Lab 3 grading\n"), "{updated}");
+    assert!(
+        !updated.contains("# first test"),
+        "removed with its test: {updated}"
+    );
+    assert!(
+        updated.contains("  # second test\n  - meta:\n      name: b"),
+        "{updated}"
+    );
+    assert!(updated.contains("toolchain: \"1.82.0\""), "{updated}");
+}
+
+#[test]
+fn update_without_a_config_fails() {
+    let h = Harness::new();
+    h.write_root_crate("#[test] fn a() {}", "root");
+    let cfg = RunConfig {
+        root: h.root().to_path_buf(),
+        tests_dir_name: h.root().to_path_buf(),
+        update: true,
+        ..Default::default()
+    };
+    let err = format!("{:#}", super::run(&cfg).unwrap_err());
+    assert!(err.contains("Nothing to update"), "{err}");
+}
+
+#[test]
+fn config_references_a_local_schema() {
+    let h = Harness::new();
+    h.write_root_crate("#[test] fn a() {}", "root");
+    h.run(|_| {});
+
+    let raw = fs::read_to_string(h.root().join(".autograder/autograder.json")).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&raw).unwrap();
+    assert_eq!(json["$schema"], crate::cli::schema::SCHEMA_REF);
+    let schema = h.root().join(".autograder/autograder.schema.json");
+    assert!(
+        fs::read_to_string(schema)
+            .unwrap()
+            .contains("\"definitions\"")
+    );
+}
+
+#[test]
+fn lang_translates_generated_descriptions_and_is_recorded() {
+    let h = Harness::new();
+    h.write_root_crate("#[test] fn a() {}", "root");
+    let items = h.run(|c| {
+        c.style_check = true;
+        c.commit_counts_flag = true;
+        c.require_commits = vec![3];
+        c.require_branches = vec![2];
+        c.lang = Some("es".into());
+    });
+
+    let description = |name: &str| {
+        items
+            .iter()
+            .find(|t| t.meta.name == name)
+            .map(|t| t.meta.description.clone())
+            .unwrap()
+    };
+    assert_eq!(
+        description("CLIPPY_STYLE_CHECK"),
+        "Revisión de estilo con `cargo clippy`"
+    );
+    assert_eq!(
+        description("COMMIT_COUNT_3"),
+        "Comprueba que haya al menos 3 commits."
+    );
+    assert_eq!(
+        description("BRANCH_COUNT_2"),
+        "Comprueba que haya al menos 2 ramas."
+    );
+    let settings = read_autograder_config(h.root()).unwrap().settings;
+    assert_eq!(settings.lang.as_deref(), Some("es"));
 }
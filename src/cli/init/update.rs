@@ -0,0 +1,297 @@
+// `init --update`: merge a fresh scan into the existing config, editing it in place where the
+// format allows so instructor comments survive.
+use anyhow::{Context, Result};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fs;
+use std::mem::Discriminant;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, Table};
+
+use crate::types::config::AutograderConfig;
+use crate::types::{AutoTest, TestKind};
+use crate::utils::config_format::{ConfigFormat, toml_test_table};
+use crate::utils::{autograder_config_path, parse_autograder_config, write_autograder_config};
+
+/// What an update changes: indices of existing tests to drop and newly scanned tests to append
+#[derive(Debug, Default)]
+pub struct Merge {
+    pub remove: Vec<usize>,
+    pub add: Vec<AutoTest>,
+    pub toolchain: Option<String>,
+}
+
+impl Merge {
+    pub fn is_empty(&self) -> bool {
+        self.remove.is_empty() && self.add.is_empty() && self.toolchain.is_none()
+    }
+
+    /// The merged config, for formats that are rewritten rather than edited
+    pub fn apply(&self, config: &AutograderConfig) -> AutograderConfig {
+        let mut merged = config.clone();
+        merged.tests = config
+            .tests
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !self.remove.contains(i))
+            .map(|(_, t)| t.clone())
+            .chain(self.add.iter().cloned())
+            .collect();
+        if let Some(t) = &self.toolchain {
+            merged.settings.toolchain = Some(t.clone());
+        }
+        merged
+    }
+}
+
+type TestKey<'a> = (Discriminant<TestKind>, Cow<'a, str>, Option<&'a str>);
+
+/// Generated checks are matched by threshold rather than name, so renaming them (by hand or with
+/// `settings.name_templates`) doesn't add a second copy. A `cargo_test` is matched on the function
+/// its `stable_key` selects, so one with `path`/`filter` and a friendly name is still recognised.
+fn key(test: &AutoTest) -> TestKey<'_> {
+    let id = match &test.kind {
+        // Scanned tests only know the function name, not its module path
+        TestKind::CargoTest { .. } => {
+            Cow::Borrowed(test.stable_key().rsplit("::").next().unwrap_or_default())
+        }
+        TestKind::Clippy { .. } => Cow::Borrowed(""),
+        TestKind::CommitCount { min_commits } => Cow::Owned(min_commits.to_string()),
+        TestKind::BranchCount { min_branches } => Cow::Owned(min_branches.to_string()),
+        TestKind::TestCount { min_tests, .. } => Cow::Owned(min_tests.to_string()),
+        _ => Cow::Borrowed(test.meta.name.as_str()),
+    };
+    (std::mem::discriminant(&test.kind), id, test.manifest_path())
+}
+
+/// Existing tests are kept as written (points, descriptions, options). Scanned tests that aren't
+/// in the config yet are appended, and `cargo_test` entries whose function is gone are dropped.
+///
+/// `resolved` is `existing` with its `extends` bases merged in; tests a base already provides
+/// are not added locally.
+pub fn merge(
+    existing: &AutograderConfig,
+    resolved: &AutograderConfig,
+    scanned: &[AutoTest],
+    toolchain: Option<String>,
+) -> Merge {
+    let scanned_keys: HashSet<TestKey> = scanned.iter().map(key).collect();
+    let existing_keys: HashSet<TestKey> = resolved.tests.iter().map(key).collect();
+
+    let remove = existing
+        .tests
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| matches!(t.kind, TestKind::CargoTest { .. }))
+        .filter(|(_, t)| !scanned_keys.contains(&key(t)))
+        .map(|(i, _)| i)
+        .collect();
+    let add = scanned
+        .iter()
+        .filter(|t| !existing_keys.contains(&key(t)))
+        .cloned()
+        .collect();
+    let toolchain = toolchain.filter(|t| resolved.settings.toolchain.as_ref() != Some(t));
+    Merge {
+        remove,
+        add,
+        toolchain,
+    }
+}
+
+/// Write the merge to the config file. TOML and YAML are edited in place; if the file's layout
+/// can't be edited safely it is rewritten (losing comments) with a warning.
+pub fn write_merge(root: &Path, existing: &AutograderConfig, merge: &Merge) -> Result<PathBuf> {
+    let path = autograder_config_path(root);
+    let format = ConfigFormat::from_path(&path).unwrap_or_default();
+    let raw = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.to_string_lossy()))?;
+
+    let edited = match format {
+        ConfigFormat::Json => None,
+        ConfigFormat::Toml => edit_toml(&raw, existing.tests.len(), merge),
+        ConfigFormat::Yaml => edit_yaml(&raw, existing.tests.len(), merge),
+    };
+    // Only trust an in-place edit that reads back as the merged config
+    let expected = merge.apply(existing);
+    let edited = edited.filter(|text| {
+        parse_autograder_config(text, format)
+            .is_ok_and(|c| c.tests.len() == expected.tests.len() && c.settings == expected.settings)
+    });
+
+    match edited {
+        Some(text) => {
+            fs::write(&path, text)
+                .with_context(|| format!("Failed to write {}", path.to_string_lossy()))?;
+            Ok(path)
+        }
+        None => {
+            if format != ConfigFormat::Json {
+                println!(
+                    "Warning: could not edit {} in place; rewriting it (comments are not kept)",
+                    path.to_string_lossy()
+                );
+            }
+            write_autograder_config(root, &expected)
+        }
+    }
+}
+
+fn edit_toml(raw: &str, count: usize, merge: &Merge) -> Option<String> {
+    let mut doc: DocumentMut = raw.parse().ok()?;
+    let tests = doc.get_mut("tests")?.as_array_of_tables_mut()?;
+    if tests.len() != count {
+        return None;
+    }
+    for &i in merge.remove.iter().rev() {
+        tests.remove(i);
+    }
+    for test in &merge.add {
+        tests.push(toml_test_table(test).ok()?);
+    }
+    if let Some(toolchain) = &merge.toolchain {
+        if !doc.contains_key("settings") {
+            // Keep `[settings]` above the tests rather than appended after them
+            let mut table = Table::new();
+            table.set_position(0);
+            doc.insert("settings", Item::Table(table));
+        }
+        let settings = doc.get_mut("settings")?.as_table_like_mut()?;
+        settings.insert("toolchain", toml_edit::value(toolchain.as_str()));
+    }
+    Some(doc.to_string())
+}
+
+/// Splice the `tests:` block sequence line by line. Each entry owns the comment lines directly
+/// above it, so removing a test takes its comments along and every other line stays put.
+fn edit_yaml(raw: &str, count: usize, merge: &Merge) -> Option<String> {
+    let mut lines: Vec<String> = raw.lines().map(str::to_string).collect();
+
+    if let Some(toolchain) = &merge.toolchain {
+        set_yaml_toolchain(&mut lines, toolchain)?;
+    }
+
+    let header = lines
+        .iter()
+        .position(|l| top_level_key(l) == Some("tests"))?;
+    if !yaml_value_is_empty(&lines[header]) {
+        return None; // flow style (`tests: [...]`)
+    }
+    let body_start = header + 1;
+    let first = (body_start..lines.len()).find(|&i| is_content(&lines[i]))?;
+    let indent = indent_of(&lines[first]);
+    if !lines[first].trim_start().starts_with('-') {
+        return None;
+    }
+    let is_item = |l: &str| indent_of(l) == indent && l.trim_start().starts_with('-');
+
+    // Entries run until the next content line that is less indented (or a sibling key)
+    let mut starts = Vec::new();
+    let mut end = body_start;
+    for (i, line) in lines.iter().enumerate().skip(body_start) {
+        if !is_content(line) {
+            continue;
+        }
+        if is_item(line) {
+            starts.push(i);
+        } else if indent_of(line) <= indent {
+            break;
+        }
+        end = i + 1;
+    }
+    if starts.len() != count {
+        return None;
+    }
+
+    // Pull each entry's start back over the comments directly above it
+    let spans: Vec<(usize, usize)> = starts
+        .iter()
+        .enumerate()
+        .map(|(k, &s)| {
+            let mut from = s;
+            while from > body_start && lines[from - 1].trim_start().starts_with('#') {
+                from -= 1;
+            }
+            let to = starts.get(k + 1).copied().unwrap_or(end);
+            (from, to)
+        })
+        .collect();
+    // Earlier spans end where later ones begin, so trim comment runs off the previous span
+    let spans: Vec<(usize, usize)> = spans
+        .iter()
+        .enumerate()
+        .map(|(k, &(from, to))| (from, spans.get(k + 1).map_or(to, |next| next.0)))
+        .collect();
+
+    let mut added = Vec::new();
+    for test in &merge.add {
+        let rendered = crate::utils::yaml::to_string(&[test]).ok()?;
+        let pad = " ".repeat(indent);
+        added.extend(rendered.lines().map(|l| format!("{pad}{l}")));
+    }
+    let insert_at = spans.last().map_or(end, |&(_, to)| to);
+    lines.splice(insert_at..insert_at, added);
+    for &i in merge.remove.iter().rev() {
+        let (from, to) = spans[i];
+        lines.drain(from..to);
+    }
+
+    let mut out = lines.join("\n");
+    if raw.ends_with('\n') {
+        out.push('\n');
+    }
+    Some(out)
+}
+
+/// Replace (or add) `toolchain:` directly under a block-style `settings:`
+fn set_yaml_toolchain(lines: &mut Vec<String>, toolchain: &str) -> Option<()> {
+    let header = lines
+        .iter()
+        .position(|l| top_level_key(l) == Some("settings"))?;
+    if !yaml_value_is_empty(&lines[header]) {
+        return None;
+    }
+    let first = (header + 1..lines.len()).find(|&i| is_content(&lines[i]))?;
+    let indent = indent_of(&lines[first]);
+    if indent == 0 {
+        return None;
+    }
+    let value = format!(
+        "{}toolchain: {}",
+        " ".repeat(indent),
+        crate::utils::yaml_quote(toolchain)
+    );
+    let existing = (first..lines.len())
+        .take_while(|&i| !is_content(&lines[i]) || indent_of(&lines[i]) >= indent)
+        .find(|&i| {
+            indent_of(&lines[i]) == indent && lines[i].trim_start().starts_with("toolchain:")
+        });
+    match existing {
+        Some(i) => lines[i] = value,
+        None => lines.insert(first, value),
+    }
+    Some(())
+}
+
+fn top_level_key(line: &str) -> Option<&str> {
+    if line.starts_with([' ', '\t', '#', '-']) {
+        return None;
+    }
+    line.split_once(':').map(|(k, _)| k.trim())
+}
+
+/// `key:` with nothing but an optional comment after it (a block value follows)
+fn yaml_value_is_empty(line: &str) -> bool {
+    line.split_once(':')
+        .map(|(_, v)| v.trim().is_empty() || v.trim_start().starts_with('#'))
+        .unwrap_or(false)
+}
+
+fn is_content(line: &str) -> bool {
+    let t = line.trim_start();
+    !t.is_empty() && !t.starts_with('#')
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
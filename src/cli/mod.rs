@@ -1,13 +1,28 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 
 use clap::{Args, Parser, Subcommand};
 
+use crate::types::config::Triggers;
+use crate::utils::config_format::ConfigFormat;
+use build::backends::Backend;
+use build::partition::Partition;
+use table::formats::TableFormat;
+use table::layout::{Column, GroupBy, SortBy};
+
 pub mod build;
+pub mod convert;
+pub mod export;
+pub mod import;
 pub mod init;
 pub mod reset;
+pub mod rubric;
+pub mod schema;
+pub mod show;
 pub mod table;
+pub mod upgrade;
+pub mod validate;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -34,8 +49,32 @@ pub enum Command {
     /// Get a table of test names, docstrings, and points for assignment READMEs
     Table(TableArgs),
 
+    /// Write a student-facing RUBRIC.md describing every graded test and check
+    Rubric(RubricArgs),
+
     /// Delete all files created by autograder-setup
     Reset(ResetArgs),
+
+    /// Export tests/autograder.json to another grading platform
+    Export(ExportArgs),
+
+    /// Create tests/autograder.json from another platform's configuration
+    Import(ImportArgs),
+
+    /// Rewrite an older autograder.json in the current schema version
+    Upgrade(UpgradeArgs),
+
+    /// Convert the autograder config between json, toml and yaml
+    Convert(ConvertArgs),
+
+    /// Print the autograder config, optionally with its `extends` bases merged in
+    Show(ShowArgs),
+
+    /// Check the autograder config for mistakes before building
+    Validate(ValidateArgs),
+
+    /// Print the JSON Schema of the autograder config (for editor completion)
+    Schema(SchemaArgs),
 }
 
 #[derive(Args, Debug)]
@@ -93,6 +132,47 @@ pub struct InitArgs {
     /// Require specific student-written test thresholds (e.g --require-tests 2 4 6)
     #[arg(long = "require-tests", value_delimiter = ' ', num_args(1..), default_values_t = Vec::<u32>::new())]
     pub require_tests: Vec<u32>,
+
+    /// Rust toolchain to record for grading (defaults to the channel in rust-toolchain.toml, if any)
+    #[arg(long = "toolchain")]
+    pub toolchain: Option<String>,
+
+    /// Config file format (defaults to the existing config's format, else json)
+    #[arg(long = "format", value_enum)]
+    pub format: Option<ConfigFormat>,
+
+    /// Merge newly found tests into the existing config instead of replacing it (keeps edits and comments)
+    #[arg(long = "update")]
+    pub update: bool,
+
+    /// Language of the generated descriptions, recorded as `settings.lang` (bundled: en, es)
+    #[arg(long = "lang")]
+    pub lang: Option<String>,
+
+    /// Translations file overriding the catalog's strings, relative to <root>
+    #[arg(long = "translations")]
+    pub translations: Option<PathBuf>,
+
+    /// Name generated checks of a type from a template, recorded in `settings.name_templates`
+    /// (e.g. --name-template clippy="Style: {member}" --name-template commit_count="At least {min} commits")
+    #[arg(long = "name-template", value_name = "TYPE=TEMPLATE", value_parser = parse_name_template)]
+    pub name_templates: Vec<(String, String)>,
+}
+
+/// `clippy=Style: {member}` -> ("clippy", "Style: {member}")
+fn parse_name_template(s: &str) -> Result<(String, String), String> {
+    let (kind, template) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected TYPE=TEMPLATE, got `{s}`"))?;
+    let kinds: Vec<&str> = init::GENERATED_CHECKS.iter().map(|(k, _, _)| *k).collect();
+    if !kinds.contains(&kind.trim()) {
+        return Err(format!(
+            "`{}` isn't a generated check (expected one of: {})",
+            kind.trim(),
+            kinds.join(", ")
+        ));
+    }
+    Ok((kind.trim().to_string(), template.to_string()))
 }
 
 #[derive(Debug, Clone)]
@@ -112,6 +192,14 @@ pub struct RunConfig {
     pub require_tests: Vec<u32>,
     pub require_commits: Vec<u32>,
     pub require_branches: Vec<u32>,
+
+    pub toolchain: Option<String>,
+    pub format: Option<ConfigFormat>,
+    pub update: bool,
+
+    pub lang: Option<String>,
+    pub translations: Option<PathBuf>,
+    pub name_templates: Vec<(String, String)>,
 }
 
 impl RunConfig {
@@ -145,7 +233,7 @@ impl RunConfig {
 impl From<InitArgs> for RunConfig {
     fn from(args: InitArgs) -> Self {
         // If tests_dir is default and root is not, use root for tests_dir
-        let tests_dir = if args.tests_dir == PathBuf::from(".") && args.root != PathBuf::from(".") {
+        let tests_dir = if args.tests_dir == Path::new(".") && args.root != Path::new(".") {
             &args.root
         } else {
             &args.tests_dir
@@ -162,6 +250,12 @@ impl From<InitArgs> for RunConfig {
             require_tests: args.require_tests,
             require_commits: args.require_commits,
             require_branches: args.require_branches,
+            toolchain: args.toolchain,
+            format: args.format,
+            update: args.update,
+            lang: args.lang,
+            translations: args.translations,
+            name_templates: args.name_templates,
         }
     }
 }
@@ -178,6 +272,12 @@ impl Default for RunConfig {
             require_tests: Vec::new(),
             require_commits: Vec::new(),
             require_branches: Vec::new(),
+            toolchain: None,
+            format: None,
+            update: false,
+            lang: None,
+            translations: None,
+            name_templates: Vec::new(),
         }
     }
 }
@@ -191,6 +291,104 @@ pub struct BuildArgs {
     /// Have autograder run on push to any branch (default: grade only on "Grade All" or `repository_dispatch`)
     #[arg(long = "grade-on-push", default_value_t = false)]
     pub grade_on_push: bool,
+
+    /// Rust toolchain to grade with, e.g. 1.82.0 (defaults to autograder.json, then rust-toolchain.toml, then stable)
+    #[arg(long = "toolchain")]
+    pub toolchain: Option<String>,
+
+    /// Cache the cargo registry and target/ between grading runs (keyed on Cargo.lock and the toolchain)
+    #[arg(long = "cache")]
+    pub cache: bool,
+
+    /// Allow manual grading runs from the Actions tab (`workflow_dispatch`)
+    #[arg(long = "workflow-dispatch")]
+    pub workflow_dispatch: bool,
+
+    /// Grade on a cron schedule, e.g. --schedule "0 3 * * *" (repeatable)
+    #[arg(long = "schedule", value_name = "CRON")]
+    pub schedule: Vec<String>,
+
+    /// Only grade pushes to these branches (implies --grade-on-push)
+    #[arg(long = "push-branches", value_name = "BRANCH", num_args(1..))]
+    pub push_branches: Vec<String>,
+
+    /// Only grade pushes that touch these paths, e.g. --push-paths "src/**" "tests/**" (implies --grade-on-push)
+    #[arg(long = "push-paths", value_name = "GLOB", num_args(1..))]
+    pub push_paths: Vec<String>,
+
+    /// Cancel an in-progress grading run when a newer one starts on the same ref
+    #[arg(long = "cancel-superseded")]
+    pub cancel_superseded: bool,
+
+    /// Pin third-party actions to commit SHAs (tag kept as a comment)
+    #[arg(long = "pin-actions")]
+    pub pin_actions: bool,
+
+    /// Use actions from a mirror organization instead of their upstream owners
+    #[arg(long = "action-mirror", value_name = "ORG")]
+    pub action_mirror: Option<String>,
+
+    /// Split grading across N parallel jobs, reported together as one score
+    #[arg(long = "jobs", value_name = "N", default_value_t = 1)]
+    pub jobs: usize,
+
+    /// How tests are split across --jobs
+    #[arg(long = "partition", value_enum, default_value_t = Partition::Balanced)]
+    pub partition: Partition,
+
+    /// CI system to generate for (every non-GitHub target also writes .autograder/grade.sh)
+    #[arg(long = "target", value_enum, default_value_t = Backend::Github)]
+    pub target: Backend,
+
+    /// Language of the workflow and step names (defaults to `settings.lang`, then en)
+    #[arg(long = "lang")]
+    pub lang: Option<String>,
+
+    /// Translations file overriding the catalog's strings, relative to <root>
+    #[arg(long = "translations")]
+    pub translations: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default)]
+/// A helper container struct for Build::run(), mirroring `RunConfig` for init
+pub struct BuildConfig {
+    pub root: PathBuf,
+    pub toolchain: Option<String>,
+    pub cache: bool,
+    /// Trigger flags; overlaid on `settings.triggers` from autograder.json
+    pub triggers: Triggers,
+    pub pin_actions: bool,
+    pub action_mirror: Option<String>,
+    pub jobs: usize,
+    pub partition: Partition,
+    pub target: Backend,
+    pub lang: Option<String>,
+    pub translations: Option<PathBuf>,
+}
+
+impl From<BuildArgs> for BuildConfig {
+    fn from(args: BuildArgs) -> Self {
+        Self {
+            root: args.root,
+            toolchain: args.toolchain,
+            cache: args.cache,
+            triggers: Triggers {
+                grade_on_push: args.grade_on_push,
+                workflow_dispatch: args.workflow_dispatch,
+                schedule: args.schedule,
+                push_branches: args.push_branches,
+                push_paths: args.push_paths,
+                cancel_superseded: args.cancel_superseded,
+            },
+            pin_actions: args.pin_actions,
+            action_mirror: args.action_mirror,
+            jobs: args.jobs,
+            partition: args.partition,
+            target: args.target,
+            lang: args.lang,
+            translations: args.translations,
+        }
+    }
 }
 
 #[derive(Args, Debug)]
@@ -203,9 +401,64 @@ pub struct TableArgs {
     #[arg(long = "no-clipboard")]
     pub no_clipboard: bool,
 
-    /// Append the table to the end of README.md
+    /// Write the table into README.md between `<!-- autograder-table:start/end -->` markers
+    /// (added at the end on first use)
     #[arg(long = "to-readme")]
     pub to_readme: bool,
+
+    /// Markdown file to write or check, relative to <root>
+    #[arg(long = "file", default_value = "README.md")]
+    pub file: PathBuf,
+
+    /// Fail if the table in the file is out of date with the autograder config
+    #[arg(long = "check", conflicts_with = "to_readme")]
+    pub check: bool,
+
+    /// Output format (--to-readme and --check need markdown)
+    #[arg(long = "format", value_enum, default_value_t = TableFormat::Markdown)]
+    pub format: TableFormat,
+
+    /// Write the table to this file instead of the clipboard
+    #[arg(short, long, conflicts_with_all = ["to_readme", "check"])]
+    pub output: Option<PathBuf>,
+
+    /// One section per group, each with a subtotal, and a grand total at the end
+    #[arg(long = "group-by", value_enum)]
+    pub group_by: Option<GroupBy>,
+
+    /// Columns to show, in order
+    #[arg(
+        long = "columns",
+        value_enum,
+        value_delimiter = ',',
+        default_value = "name,points,description"
+    )]
+    pub columns: Vec<Column>,
+
+    /// Row order (within each group)
+    #[arg(long = "sort", value_enum, default_value_t = SortBy::Config)]
+    pub sort: SortBy,
+
+    /// Link test names to the file and line where `init` found them
+    #[arg(long = "link-source")]
+    pub link_source: bool,
+
+    /// Repository web URL for absolute links, e.g. https://github.com/owner/repo
+    /// (detected from .git/config unless writing into the README)
+    #[arg(long = "repo-url", requires = "link_source")]
+    pub repo_url: Option<String>,
+
+    /// Branch for absolute links (defaults to the checked-out branch, then main)
+    #[arg(long = "branch", requires = "link_source")]
+    pub branch: Option<String>,
+
+    /// Language of the headers and labels (defaults to `settings.lang`, then en)
+    #[arg(long = "lang")]
+    pub lang: Option<String>,
+
+    /// Translations file overriding the catalog's strings, relative to <root>
+    #[arg(long = "translations")]
+    pub translations: Option<PathBuf>,
 }
 
 #[derive(Args, Debug)]
@@ -215,6 +468,170 @@ pub struct ResetArgs {
     pub root: PathBuf,
 }
 
+#[derive(Args, Debug)]
+pub struct RubricArgs {
+    /// Root of the Rust project (defaults to current directory)
+    #[arg(short, long, default_value = ".")]
+    pub root: PathBuf,
+
+    /// File to write, relative to <root>
+    #[arg(short, long, default_value = "RUBRIC.md")]
+    pub output: PathBuf,
+
+    /// Assignment name for the heading (defaults to the Cargo.toml package name)
+    #[arg(long)]
+    pub title: Option<String>,
+
+    /// One section of tests per group (default: a single "Tests" section)
+    #[arg(long = "group-by", value_enum)]
+    pub group_by: Option<GroupBy>,
+
+    /// Language of the group labels (defaults to `settings.lang`, then en)
+    #[arg(long = "lang")]
+    pub lang: Option<String>,
+
+    /// Translations file overriding the catalog's strings, relative to <root>
+    #[arg(long = "translations")]
+    pub translations: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    #[command(subcommand)]
+    pub target: ExportTarget,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ExportTarget {
+    /// Gradescope autograder bundle (setup.sh, run_autograder and a results.json writer)
+    Gradescope(GradescopeArgs),
+
+    /// GitHub Classroom's legacy .github/classroom/autograding.json
+    Classroom(ClassroomExportArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct GradescopeArgs {
+    /// Root of the Rust project (defaults to current directory)
+    #[arg(short, long, default_value = ".")]
+    pub root: PathBuf,
+
+    /// Directory to write the bundle to (defaults to <root>/.autograder/gradescope)
+    #[arg(short, long)]
+    pub out: Option<PathBuf>,
+
+    /// Rust toolchain installed by setup.sh (defaults to autograder.json, then rust-toolchain.toml, then stable)
+    #[arg(long = "toolchain")]
+    pub toolchain: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct ClassroomExportArgs {
+    /// Root of the Rust project (defaults to current directory)
+    #[arg(short, long, default_value = ".")]
+    pub root: PathBuf,
+
+    /// File to write (defaults to <root>/.github/classroom/autograding.json)
+    #[arg(short, long)]
+    pub out: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    #[command(subcommand)]
+    pub source: ImportSource,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ImportSource {
+    /// GitHub Classroom's legacy .github/classroom/autograding.json
+    Classroom(ClassroomImportArgs),
+
+    /// An existing (possibly hand-written) .github/workflows/classroom.yml
+    Workflow(WorkflowImportArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct WorkflowImportArgs {
+    /// Root of the Rust project (defaults to current directory)
+    #[arg(short, long, default_value = ".")]
+    pub root: PathBuf,
+
+    /// Workflow to import (defaults to <root>/.github/workflows/classroom.yml)
+    #[arg(short, long)]
+    pub file: Option<PathBuf>,
+
+    /// Replace the tests of an existing autograder.json (its settings are kept)
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ClassroomImportArgs {
+    /// Root of the Rust project (defaults to current directory)
+    #[arg(short, long, default_value = ".")]
+    pub root: PathBuf,
+
+    /// File to import (defaults to <root>/.github/classroom/autograding.json)
+    #[arg(short, long)]
+    pub file: Option<PathBuf>,
+
+    /// Replace the tests of an existing autograder.json (its settings are kept)
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct UpgradeArgs {
+    /// Root of the Rust project (defaults to current directory)
+    #[arg(short, long, default_value = ".")]
+    pub root: PathBuf,
+
+    /// Only check; exit with an error if the file needs upgrading
+    #[arg(long)]
+    pub check: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ConvertArgs {
+    /// Root of the Rust project (defaults to current directory)
+    #[arg(short, long, default_value = ".")]
+    pub root: PathBuf,
+
+    /// Format to convert to
+    #[arg(long = "to", value_enum)]
+    pub to: ConfigFormat,
+}
+
+#[derive(Args, Debug)]
+pub struct ShowArgs {
+    /// Root of the Rust project (defaults to current directory)
+    #[arg(short, long, default_value = ".")]
+    pub root: PathBuf,
+
+    /// Merge in every `extends` base, as `build` and `table` see the config
+    #[arg(long = "resolved")]
+    pub resolved: bool,
+
+    /// Output format (defaults to the config file's format)
+    #[arg(long = "format", value_enum)]
+    pub format: Option<ConfigFormat>,
+}
+
+#[derive(Args, Debug)]
+pub struct ValidateArgs {
+    /// Root of the Rust project (defaults to current directory)
+    #[arg(short, long, default_value = ".")]
+    pub root: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct SchemaArgs {
+    /// Write the schema to this file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
 pub fn run() -> Result<()> {
     let cli = Cli::parse();
     match cli.command {
@@ -222,10 +639,20 @@ pub fn run() -> Result<()> {
             let cfg = RunConfig::from(a);
             init::run(&cfg)
         }
-        // Build has no args; default to current dir root like init would.
-        Command::Build(a) => build::run(&a.root, a.grade_on_push),
-        Command::Table(a) => table::run(&a.root, !a.no_clipboard, a.to_readme),
+        Command::Build(a) => {
+            let cfg = BuildConfig::from(a);
+            build::run(&cfg)
+        }
+        Command::Table(a) => table::run(&a),
+        Command::Rubric(a) => rubric::run(&a),
         Command::Reset(a) => reset::run(&a.root),
+        Command::Export(a) => export::run(&a),
+        Command::Import(a) => import::run(&a),
+        Command::Upgrade(a) => upgrade::run(&a.root, a.check),
+        Command::Convert(a) => convert::run(&a.root, a.to),
+        Command::Show(a) => show::run(&a.root, a.resolved, a.format),
+        Command::Validate(a) => validate::run(&a.root),
+        Command::Schema(a) => schema::run(a.output.as_deref()),
     }
 }
 
@@ -2,6 +2,8 @@ use anyhow::Result;
 use std::fs;
 use std::path::Path;
 
+use crate::cli::build::backends::{FORGEJO_WORKFLOW_FILE, GENERATED_MARKER, GITLAB_CI_FILE};
+
 pub fn run(root: &Path) -> Result<()> {
     // Remove the generated .autograder directory
     let autograder_dir = root.join(".autograder");
@@ -17,6 +19,18 @@ pub fn run(root: &Path) -> Result<()> {
         println!("Deleted {}", classroom_yml.to_string_lossy());
     }
 
+    // Other CI backends share their paths with hand-written pipelines, so only remove our own
+    for rel in [GITLAB_CI_FILE, FORGEJO_WORKFLOW_FILE] {
+        let path = root.join(rel);
+        let generated = fs::read_to_string(&path)
+            .map(|s| s.starts_with(GENERATED_MARKER))
+            .unwrap_or(false);
+        if generated {
+            fs::remove_file(&path)?;
+            println!("Deleted {}", path.to_string_lossy());
+        }
+    }
+
     Ok(())
 }
 
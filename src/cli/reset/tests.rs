@@ -33,3 +33,20 @@ fn does_nothing_if_files_missing() {
     // No .autograder or classroom.yml
     assert!(run(dir.path()).is_ok());
 }
+
+#[test]
+fn removes_generated_ci_files_but_keeps_hand_written_ones() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join(".gitlab-ci.yml"),
+        "# Generated by autograder-setup from autograder.json\n",
+    )
+    .unwrap();
+    let forgejo = dir.path().join(".forgejo").join("workflows");
+    fs::create_dir_all(&forgejo).unwrap();
+    fs::write(forgejo.join("autograding.yml"), "name: Mine\n").unwrap();
+
+    run(dir.path()).unwrap();
+    assert!(!dir.path().join(".gitlab-ci.yml").exists());
+    assert!(forgejo.join("autograding.yml").exists());
+}
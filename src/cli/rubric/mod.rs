@@ -0,0 +1,279 @@
+// `rubric`: a student-facing RUBRIC.md with every test's full docstring, the style and repository
+// checks in plain language, and the commands to run everything locally.
+use anyhow::{Context, Result};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::RubricArgs;
+use super::build::apply_test_defaults;
+use super::build::build_functions::{cargo_test_counts, graded_command};
+use super::table::layout::{Column, GroupBy, Layout, SortBy};
+use crate::types::{AutoTest, Comparison, TestKind};
+use crate::utils::i18n::Catalog;
+use crate::utils::{package_name, read_autograder_config};
+
+pub fn run(args: &RubricArgs) -> Result<()> {
+    let root = args.root.as_path();
+    let rubric = render(
+        root,
+        args.title.as_deref(),
+        args.group_by,
+        args.lang.as_deref(),
+        args.translations.as_deref(),
+    )?;
+    let path = root.join(&args.output);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.to_string_lossy()))?;
+    }
+    fs::write(&path, rubric)
+        .with_context(|| format!("Failed to write {}", path.to_string_lossy()))?;
+    println!("Wrote {}", path.to_string_lossy());
+    Ok(())
+}
+
+pub fn render(
+    root: &Path,
+    title: Option<&str>,
+    group_by: Option<GroupBy>,
+    lang: Option<&str>,
+    translations: Option<&Path>,
+) -> Result<String> {
+    let config = read_autograder_config(root)?;
+    let strings = Catalog::for_project(root, &config.settings, lang, translations)?;
+    let tests = apply_test_defaults(config.tests, &config.settings)?;
+    let counts = cargo_test_counts(&tests);
+    let title = match title {
+        Some(t) => t.to_string(),
+        None => assignment_name(root),
+    };
+
+    let (cargo_tests, checks): (Vec<AutoTest>, Vec<AutoTest>) = tests
+        .iter()
+        .cloned()
+        .partition(|t| matches!(t.kind, TestKind::CargoTest { .. }));
+    let total: u32 = tests.iter().map(|t| t.meta.points).sum();
+
+    let mut out = format!("# Grading Rubric: {title}\n\n");
+    out.push_str(
+        "This assignment is graded automatically. Each item below is checked on every grading \
+         run, and you earn its points when the check passes.\n\n",
+    );
+    out.push_str(&format!("**Total: {}**\n", points(total)));
+
+    let layout = Layout::new(
+        &cargo_tests,
+        &[Column::Name],
+        group_by,
+        SortBy::Config,
+        &strings,
+    );
+    // A config without cargo tests still gets one (empty) ungrouped section; leave it out
+    for section in layout.sections.iter().filter(|s| !s.tests.is_empty()) {
+        let heading = section.title.as_deref().unwrap_or("Tests");
+        out.push_str(&format!(
+            "\n## {} ({})\n",
+            heading,
+            points(section.points())
+        ));
+        for test in &section.tests {
+            out.push_str(&test_entry(test, &counts));
+        }
+    }
+
+    if !checks.is_empty() {
+        let subtotal = checks.iter().map(|t| t.meta.points).sum();
+        out.push_str(&format!(
+            "\n## Style and repository checks ({})\n",
+            points(subtotal)
+        ));
+        for test in &checks {
+            out.push_str(&check_entry(test, &counts));
+        }
+    }
+
+    out.push_str(&run_locally(&tests, &counts));
+    Ok(out)
+}
+
+/// `[package] name` of the root Cargo.toml, else the directory name
+fn assignment_name(root: &Path) -> String {
+    package_name(&root.join("Cargo.toml"))
+        .or_else(|| {
+            let dir = root.canonicalize().unwrap_or_else(|_| PathBuf::from(root));
+            Some(dir.file_name()?.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| "Assignment".to_string())
+}
+
+fn points(n: u32) -> String {
+    plural(n, "point")
+}
+
+/// Doc comments keep the space after `///`; drop one so Markdown indentation means what it says
+fn dedent_doc(doc: &str) -> String {
+    doc.lines()
+        .map(|l| l.strip_prefix(' ').unwrap_or(l))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn test_entry(test: &AutoTest, counts: &HashMap<Option<String>, u32>) -> String {
+    let heading = match &test.meta.display_name {
+        Some(display_name) => display_name.trim().to_string(),
+        None => format!("`{}`", test.meta.name),
+    };
+    let mut out = format!("\n### {} ({})\n\n", heading, points(test.meta.points));
+    let doc = dedent_doc(&test.plain_description());
+    if !doc.trim().is_empty() {
+        out.push_str(doc.trim());
+        out.push_str("\n\n");
+    }
+    out.push_str(&format!("Run it: `{}`\n", graded_command(test, counts)));
+    out
+}
+
+fn check_entry(test: &AutoTest, counts: &HashMap<Option<String>, u32>) -> String {
+    let mut out = format!(
+        "\n### {} ({})\n\n",
+        test.display_name(),
+        points(test.meta.points)
+    );
+    out.push_str(&explain(test, counts));
+    out.push('\n');
+    let description = dedent_doc(&test.plain_description());
+    if !description.trim().is_empty() {
+        out.push_str(&format!("\n{}\n", description.trim()));
+    }
+    out
+}
+
+fn manifest_note(test: &AutoTest) -> String {
+    match test.manifest_path() {
+        Some(p) if !matches!(p, "" | "." | "Cargo.toml") => format!(" (in `{p}`)"),
+        _ => String::new(),
+    }
+}
+
+/// What a non-test check requires, with its threshold filled in
+fn explain(test: &AutoTest, counts: &HashMap<Option<String>, u32>) -> String {
+    match &test.kind {
+        TestKind::Clippy { .. } => format!(
+            "Your code{} must build with no `cargo clippy` warnings.",
+            manifest_note(test)
+        ),
+        TestKind::TestCount {
+            min_tests,
+            manifest_path,
+        } => {
+            let provided = *counts.get(manifest_path).unwrap_or(&0);
+            let mut s = format!(
+                "You must write at least {} of your own{}",
+                plural(*min_tests, "test"),
+                manifest_note(test)
+            );
+            if provided > 0 {
+                s.push_str(&format!(
+                    ", on top of the {} already provided",
+                    plural(provided, "graded test")
+                ));
+            }
+            s.push('.');
+            s
+        }
+        TestKind::CommitCount { min_commits } => format!(
+            "Your repository must have at least {}.",
+            plural(*min_commits, "commit")
+        ),
+        TestKind::BranchCount { min_branches } => format!(
+            "Your repository must have at least {}.",
+            plural(*min_branches, "branch")
+        ),
+        TestKind::Command { command } => {
+            format!("`{command}` must finish successfully (exit status 0).")
+        }
+        TestKind::Io {
+            command,
+            input,
+            expected_output,
+            comparison,
+        } => {
+            let fed = if input.is_empty() {
+                String::new()
+            } else {
+                format!(" with `{}` as input", input.escape_debug())
+            };
+            let rule = match comparison {
+                Comparison::Included => "must include",
+                Comparison::Exact => "must be exactly",
+                Comparison::Regex => "must match the regular expression",
+            };
+            format!(
+                "Running `{command}`{fed}, the output {rule} `{}`.",
+                expected_output.escape_debug()
+            )
+        }
+        TestKind::CargoTest { .. } => String::new(),
+    }
+}
+
+fn plural(n: u32, noun: &str) -> String {
+    match (n, noun.ends_with("ch")) {
+        (1, _) => format!("1 {noun}"),
+        (_, true) => format!("{n} {noun}es"),
+        _ => format!("{n} {noun}s"),
+    }
+}
+
+/// One `cargo test` per crate, then every other check's own command
+fn run_locally(tests: &[AutoTest], counts: &HashMap<Option<String>, u32>) -> String {
+    let manifests: BTreeSet<Option<&str>> = tests
+        .iter()
+        .filter(|t| matches!(t.kind, TestKind::CargoTest { .. }))
+        .map(|t| t.manifest_path())
+        .collect();
+
+    let mut commands = Vec::new();
+    for manifest in manifests {
+        match manifest {
+            Some(p) if !matches!(p, "" | "." | "Cargo.toml") => {
+                commands.push(format!("cargo test --manifest-path {p}"))
+            }
+            _ => commands.push("cargo test".to_string()),
+        }
+    }
+    for test in tests {
+        let command = match &test.kind {
+            TestKind::CargoTest { .. } => continue,
+            // The graded pipeline only compares the count; the listing is what students need
+            TestKind::TestCount { .. } => graded_command(test, counts)
+                .split(" | ")
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            TestKind::Io { command, .. } => command.clone(),
+            _ => graded_command(test, counts),
+        };
+        if !commands.contains(&command) {
+            commands.push(command);
+        }
+    }
+    if commands.is_empty() {
+        return String::new();
+    }
+
+    let mut out = String::from(
+        "\n## Running the checks locally\n\nRun these from the repository root before you \
+         push to see how you are doing:\n\n```bash\n",
+    );
+    for command in commands {
+        out.push_str(&command);
+        out.push('\n');
+    }
+    out.push_str("```\n");
+    out
+}
+
+#[cfg(test)]
+pub mod tests;
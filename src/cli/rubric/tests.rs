@@ -0,0 +1,114 @@
+use super::*;
+
+const CONFIG: &str = r#"{
+  "version": 2,
+  "tests": [
+    { "meta": { "name": "parses_numbers", "description": " Parses plain numbers.\n\n Also handles:\n - negatives\n - `+` signs", "points": 5, "group": "Part 1" }, "type": "cargo_test" },
+    { "meta": { "name": "evaluates", "description": "", "points": 1, "group": "Part 2" }, "type": "cargo_test", "manifest_path": "calc/Cargo.toml" },
+    { "meta": { "name": "CLIPPY_STYLE_CHECK", "description": "", "points": 2 }, "type": "clippy" },
+    { "meta": { "name": "COMMIT_COUNT", "description": "Commit early, commit often: ## commits.", "points": 1 }, "type": "commit_count", "min_commits": 4 },
+    { "meta": { "name": "TEST_COUNT", "description": "", "points": 2 }, "type": "test_count", "min_tests": 3 },
+    { "meta": { "name": "greets", "description": "", "points": 2 }, "type": "io", "command": "cargo run -q", "input": "Ada\n", "expected_output": "Hello, Ada", "comparison": "exact" }
+  ]
+}"#;
+
+fn project() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir_all(root.join(".autograder")).unwrap();
+    fs::write(root.join(".autograder/autograder.json"), CONFIG).unwrap();
+    fs::write(
+        root.join("Cargo.toml"),
+        "[package]\nname = \"calculator\"\nversion = \"0.1.0\"\n",
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn rubric_has_intro_total_groups_and_full_docstrings() -> Result<()> {
+    let dir = project();
+    let rubric = render(dir.path(), None, Some(GroupBy::Tag), None, None)?;
+
+    assert!(rubric.starts_with("# Grading Rubric: calculator\n"));
+    assert!(rubric.contains("**Total: 13 points**"));
+    assert!(rubric.contains("\n## Part 1 (5 points)\n"));
+    assert!(rubric.contains("\n## Part 2 (1 point)\n"));
+    // The docstring keeps its paragraphs and list, rather than being flattened into a cell
+    assert!(rubric.contains(
+        "### `parses_numbers` (5 points)\n\nParses plain numbers.\n\nAlso handles:\n- negatives\n- `+` signs\n\nRun it: `cargo test parses_numbers`\n"
+    ));
+    assert!(rubric.contains("Run it: `cargo test evaluates --manifest-path calc/Cargo.toml`"));
+    Ok(())
+}
+
+#[test]
+fn checks_are_explained_with_thresholds() -> Result<()> {
+    let dir = project();
+    let rubric = render(dir.path(), Some("Lab 2"), None, None, None)?;
+
+    assert!(rubric.starts_with("# Grading Rubric: Lab 2\n"));
+    assert!(rubric.contains("\n## Tests (6 points)\n"));
+    assert!(rubric.contains("\n## Style and repository checks (7 points)\n"));
+    assert!(rubric.contains("Your code must build with no `cargo clippy` warnings."));
+    assert!(rubric.contains(
+        "Your repository must have at least 4 commits.\n\nCommit early, commit often: 4 commits."
+    ));
+    assert!(rubric.contains(
+        "You must write at least 3 tests of your own, on top of the 1 graded test already provided."
+    ));
+    assert!(rubric.contains(
+        r"Running `cargo run -q` with `Ada\n` as input, the output must be exactly `Hello, Ada`."
+    ));
+    Ok(())
+}
+
+#[test]
+fn run_locally_lists_the_actual_commands_once() -> Result<()> {
+    let dir = project();
+    let rubric = render(dir.path(), None, None, None, None)?;
+    let (_, local) = rubric
+        .split_once("## Running the checks locally")
+        .expect("local section");
+
+    assert!(local.contains(
+        "```bash\ncargo test\ncargo test --manifest-path calc/Cargo.toml\ncargo clippy -- -D warnings\nbash ./.autograder/commit_count.sh 4\ncargo test -- --list\ncargo run -q\n```\n"
+    ), "{local}");
+    Ok(())
+}
+
+#[test]
+fn sections_without_entries_are_left_out() -> Result<()> {
+    let dir = project();
+    let checks_only = r#"{ "version": 2, "tests": [
+        { "meta": { "name": "CLIPPY_STYLE_CHECK", "description": "", "points": 2 }, "type": "clippy" }
+    ] }"#;
+    fs::write(dir.path().join(".autograder/autograder.json"), checks_only)?;
+
+    for group_by in [None, Some(GroupBy::Tag)] {
+        let rubric = render(dir.path(), None, group_by, None, None)?;
+        assert!(!rubric.contains("## Tests"), "{rubric}");
+        assert!(!rubric.contains("(0 points)"), "{rubric}");
+        assert!(rubric.contains("\n## Style and repository checks (2 points)\n"));
+    }
+    Ok(())
+}
+
+#[test]
+fn group_labels_follow_the_configured_language() -> Result<()> {
+    let dir = project();
+    let path = dir.path().join(".autograder/autograder.json");
+    let config = fs::read_to_string(&path)?
+        .replace(r#", "group": "Part 2""#, "")
+        .replace(
+            r#""version": 2,"#,
+            r#""version": 2, "settings": { "lang": "es" },"#,
+        );
+    fs::write(&path, config)?;
+
+    let rubric = render(dir.path(), None, Some(GroupBy::Tag), None, None)?;
+    assert!(rubric.contains("\n## Sin grupo (1 point)\n"), "{rubric}");
+    let rubric = render(dir.path(), None, Some(GroupBy::Tag), Some("en"), None)?;
+    assert!(rubric.contains("\n## Ungrouped (1 point)\n"), "{rubric}");
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::types::config::AutograderConfig;
+
+/// Written next to the config by `init`, so editors resolve `$schema` without network access
+pub const SCHEMA_FILE: &str = "autograder.schema.json";
+/// The `$schema` value `init` records; relative to the config file
+pub const SCHEMA_REF: &str = "./autograder.schema.json";
+
+/// JSON Schema for the config, generated from the serde model
+pub fn schema_json() -> Result<String> {
+    let schema = schemars::schema_for!(AutograderConfig);
+    Ok(serde_json::to_string_pretty(&schema)?)
+}
+
+/// Print the schema, or write it to `output`
+pub fn run(output: Option<&Path>) -> Result<()> {
+    let schema = schema_json()?;
+    match output {
+        Some(path) => {
+            fs::write(path, schema)
+                .with_context(|| format!("Failed to write {}", path.to_string_lossy()))?;
+            println!("Wrote {}", path.to_string_lossy());
+        }
+        None => println!("{}", schema),
+    }
+    Ok(())
+}
+
+/// Refresh `.autograder/autograder.schema.json` for the installed version
+pub fn write_schema_file(root: &Path) -> Result<PathBuf> {
+    let path = root.join(".autograder").join(SCHEMA_FILE);
+    fs::write(&path, schema_json()?)
+        .with_context(|| format!("Failed to write {}", path.to_string_lossy()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+pub mod tests;
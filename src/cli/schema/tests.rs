@@ -0,0 +1,56 @@
+use super::*;
+use serde_json::Value;
+
+fn schema() -> Value {
+    serde_json::from_str(&schema_json().unwrap()).unwrap()
+}
+
+#[test]
+fn every_test_type_is_a_tagged_variant() {
+    let schema = schema();
+    let test = &schema["definitions"]["AutoTest"];
+    assert!(
+        test["required"]
+            .as_array()
+            .unwrap()
+            .contains(&"meta".into())
+    );
+
+    let types: Vec<&str> = test["oneOf"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v["properties"]["type"]["enum"][0].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        types,
+        [
+            "cargo_test",
+            "clippy",
+            "commit_count",
+            "test_count",
+            "branch_count",
+            "command",
+            "io"
+        ]
+    );
+
+    // Per-variant fields live on their variant
+    let io = &test["oneOf"][6];
+    assert!(
+        io["required"]
+            .as_array()
+            .unwrap()
+            .contains(&"expected_output".into())
+    );
+    assert!(test["properties"]["env"].is_object(), "flattened options");
+}
+
+#[test]
+fn top_level_accepts_schema_and_extends() {
+    let schema = schema();
+    let props = &schema["properties"];
+    assert!(props["$schema"].is_object());
+    assert!(props["extends"].is_object());
+    assert!(schema["definitions"]["OneOrMany"]["anyOf"].is_array());
+}
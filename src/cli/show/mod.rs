@@ -0,0 +1,29 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::utils::config_format::ConfigFormat;
+use crate::utils::{autograder_config_format, load_autograder_config, read_autograder_config};
+
+/// Print the config (as `format`, default: the file's own format). With `resolved`, print the
+/// view `build` and `table` use, with every `extends` base merged in.
+pub fn run(root: &Path, resolved: bool, format: Option<ConfigFormat>) -> Result<()> {
+    print!("{}", render(root, resolved, format)?);
+    Ok(())
+}
+
+pub fn render(root: &Path, resolved: bool, format: Option<ConfigFormat>) -> Result<String> {
+    let config = if resolved {
+        read_autograder_config(root)?
+    } else {
+        load_autograder_config(root)?
+    };
+    let format = format.unwrap_or_else(|| autograder_config_format(root));
+    let mut out = format.render(&config)?;
+    if !out.ends_with('\n') {
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+pub mod tests;
@@ -0,0 +1,88 @@
+use super::*;
+use std::fs;
+
+fn write(path: &Path, contents: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+const BASE: &str = r#"{
+  "version": 2,
+  "settings": { "toolchain": "1.82.0", "default_timeout": 5 },
+  "tests": [
+    { "meta": { "name": "CLIPPY_STYLE_CHECK", "description": "clippy", "points": 2 }, "type": "clippy" },
+    { "meta": { "name": "COMMIT_COUNT_1", "description": "commits", "points": 1 }, "type": "commit_count", "min_commits": 5 }
+  ]
+}"#;
+
+/// course/base.json + assignment/.autograder/autograder.yaml extending it
+fn course(dir: &Path) -> std::path::PathBuf {
+    write(&dir.join("course/base.json"), BASE);
+    let root = dir.join("assignment");
+    write(
+        &root.join(".autograder/autograder.yaml"),
+        r#"extends: ../course/base.json
+settings:
+  default_timeout: 3
+tests:
+- meta: { name: adds, description: adds, points: 4 }
+  type: cargo_test
+- meta: { name: COMMIT_COUNT_1, description: more commits, points: 3 }
+  type: commit_count
+  min_commits: 10
+"#,
+    );
+    root
+}
+
+#[test]
+fn resolved_view_merges_bases_by_name() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = course(dir.path());
+
+    let config = read_autograder_config(&root).unwrap();
+    let names: Vec<_> = config.tests.iter().map(|t| t.meta.name.as_str()).collect();
+    assert_eq!(names, ["CLIPPY_STYLE_CHECK", "COMMIT_COUNT_1", "adds"]);
+    assert_eq!(
+        config.tests[1].meta.points, 3,
+        "local entry overrides the base"
+    );
+    assert_eq!(config.settings.toolchain.as_deref(), Some("1.82.0"));
+    assert_eq!(config.settings.default_timeout, Some(3));
+    assert!(config.extends.is_empty());
+}
+
+#[test]
+fn show_prints_raw_or_resolved() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = course(dir.path());
+
+    let raw = render(&root, false, Some(ConfigFormat::Json)).unwrap();
+    assert!(
+        raw.contains("\"extends\": \"../course/base.json\""),
+        "{raw}"
+    );
+    assert!(!raw.contains("CLIPPY_STYLE_CHECK"), "{raw}");
+
+    let resolved = render(&root, true, None).unwrap();
+    assert!(resolved.contains("CLIPPY_STYLE_CHECK"), "{resolved}");
+    assert!(!resolved.contains("extends"), "{resolved}");
+}
+
+#[test]
+fn extends_cycles_and_missing_bases_are_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(
+        &root.join(".autograder/autograder.json"),
+        r#"{ "extends": ["a.toml"], "tests": [] }"#,
+    );
+    write(&root.join("a.toml"), "extends = \"b.toml\"\n");
+    write(&root.join("b.toml"), "extends = [\"a.toml\"]\n");
+    let err = format!("{:#}", read_autograder_config(root).unwrap_err());
+    assert!(err.contains("extends itself"), "{err}");
+
+    fs::remove_file(root.join("b.toml")).unwrap();
+    let err = format!("{:#}", read_autograder_config(root).unwrap_err());
+    assert!(err.contains("does not exist"), "{err}");
+}
@@ -0,0 +1,401 @@
+// The test table in each output format. Every format renders the same `Layout` and applies its
+// own escaping.
+use anyhow::Result;
+use clap::ValueEnum;
+use serde_json::{Map, Value, json};
+
+use super::layout::{Column, Layout, Section};
+use crate::types::AutoTest;
+use crate::utils::markdown_escape;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum TableFormat {
+    /// Markdown table (for READMEs)
+    #[default]
+    Markdown,
+    /// HTML `<table>` (for Canvas pages and other LMSs)
+    Html,
+    /// Comma-separated values (for spreadsheets)
+    Csv,
+    /// JSON array of objects
+    Json,
+    /// LaTeX `tabular` (for syllabus PDFs)
+    Latex,
+}
+
+pub fn render(layout: &Layout, format: TableFormat) -> Result<String> {
+    Ok(match format {
+        TableFormat::Markdown => markdown(layout),
+        TableFormat::Html => html(layout),
+        TableFormat::Csv => csv(layout),
+        TableFormat::Json => json(layout)?,
+        TableFormat::Latex => latex(layout),
+    })
+}
+
+/// Rows of unescaped cells for one section, in column order
+fn cells(layout: &Layout, section: &Section) -> Vec<Vec<String>> {
+    section
+        .tests
+        .iter()
+        .map(|t| layout.columns.iter().map(|c| c.cell(t)).collect())
+        .collect()
+}
+
+// ---- Markdown ---------------------------------------------------------------
+
+fn markdown(layout: &Layout) -> String {
+    let header: Vec<String> = layout
+        .columns
+        .iter()
+        .map(|c| markdown_escape(c.title(&layout.strings)))
+        .collect();
+    let row = |test: &AutoTest| -> Vec<String> {
+        layout
+            .columns
+            .iter()
+            .map(|c| match c {
+                Column::Name => match layout.source_url(test) {
+                    Some(url) => format!("[`{}`]({})", c.cell(test), url.replace(' ', "%20")),
+                    None => format!("`{}`", c.cell(test)),
+                },
+                Column::Description => test.resolved_description(),
+                _ => markdown_escape(&c.cell(test)),
+            })
+            .collect()
+    };
+    let bold = |cells: Vec<String>| -> Vec<String> {
+        cells
+            .into_iter()
+            .map(|c| if c.is_empty() { c } else { format!("**{c}**") })
+            .collect()
+    };
+
+    if !layout.is_grouped() {
+        let rows: Vec<Vec<String>> = layout.sections[0].tests.iter().map(|t| row(t)).collect();
+        return markdown_grid(&header, &rows);
+    }
+
+    let mut out = String::new();
+    for section in &layout.sections {
+        let mut rows: Vec<Vec<String>> = section.tests.iter().map(|t| row(t)).collect();
+        rows.push(bold(
+            layout.summary_cells("table.subtotal", section.points()),
+        ));
+        out.push_str(&format!(
+            "### {}\n\n",
+            markdown_escape(section.title.as_deref().unwrap_or_default())
+        ));
+        out.push_str(&markdown_grid(&header, &rows));
+        out.push('\n');
+    }
+    let total = layout.strings.fmt(
+        "table.label_points",
+        &[
+            ("label", &layout.strings.text("table.total")),
+            ("points", &layout.total()),
+        ],
+    );
+    out.push_str(&format!("**{}**\n", markdown_escape(&total)));
+    out
+}
+
+/// A padded pipe table; the header is kept even when there are no rows
+fn markdown_grid(header: &[String], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.chars().count());
+        }
+    }
+    let line = |cells: &[String]| -> String {
+        let mut out = String::from("|");
+        for (cell, width) in cells.iter().zip(&widths) {
+            out.push_str(&format!(" {:<width$} |", cell, width = width));
+        }
+        out.push('\n');
+        out
+    };
+
+    let mut out = line(header);
+    out.push('|');
+    for width in &widths {
+        out.push_str(&format!("{:-<width$}|", "", width = width + 2));
+    }
+    out.push('\n');
+    for row in rows {
+        out.push_str(&line(row));
+    }
+    out
+}
+
+// ---- HTML -------------------------------------------------------------------
+
+fn html(layout: &Layout) -> String {
+    let td = |column: &Column, test: &AutoTest| -> String {
+        let cell = html_escape(&column.cell(test)).replace('\n', "<br>");
+        match (column, layout.source_url(test)) {
+            (Column::Name, Some(url)) => format!(
+                "<td><a href=\"{}\"><code>{cell}</code></a></td>",
+                html_escape(&url)
+            ),
+            (Column::Name, None) => format!("<td><code>{cell}</code></td>"),
+            _ => format!("<td>{cell}</td>"),
+        }
+    };
+    let summary = |cells: Vec<String>| -> String {
+        let cells: String = cells
+            .iter()
+            .map(|c| match c.is_empty() {
+                true => "<td></td>".to_string(),
+                false => format!("<td><strong>{}</strong></td>", html_escape(c)),
+            })
+            .collect();
+        format!("<tr>{cells}</tr>\n")
+    };
+
+    let mut out = String::from("<table>\n  <thead>\n    <tr>");
+    for column in &layout.columns {
+        out.push_str(&format!(
+            "<th>{}</th>",
+            html_escape(column.title(&layout.strings))
+        ));
+    }
+    out.push_str("</tr>\n  </thead>\n");
+    for section in &layout.sections {
+        out.push_str("  <tbody>\n");
+        if let Some(title) = &section.title {
+            out.push_str(&format!(
+                "    <tr><th colspan=\"{}\">{}</th></tr>\n",
+                layout.columns.len(),
+                html_escape(title)
+            ));
+        }
+        for test in &section.tests {
+            let row: String = layout.columns.iter().map(|c| td(c, test)).collect();
+            out.push_str(&format!("    <tr>{row}</tr>\n"));
+        }
+        if layout.is_grouped() {
+            out.push_str("    ");
+            out.push_str(&summary(
+                layout.summary_cells("table.subtotal", section.points()),
+            ));
+        }
+        out.push_str("  </tbody>\n");
+    }
+    if layout.is_grouped() {
+        out.push_str("  <tfoot>\n    ");
+        out.push_str(&summary(
+            layout.summary_cells("table.total", layout.total()),
+        ));
+        out.push_str("  </tfoot>\n");
+    }
+    out.push_str("</table>\n");
+    out
+}
+
+pub fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// ---- CSV --------------------------------------------------------------------
+
+/// Grouped tables get a leading `Group` column, so every line stays one record; source links
+/// get a trailing `Source` column
+fn csv(layout: &Layout) -> String {
+    let grouped = layout.is_grouped();
+    let linked = layout.links.is_some();
+    let with_group = |group: &str, mut fields: Vec<String>| {
+        if grouped {
+            fields.insert(0, group.to_string());
+        }
+        if linked && fields.len() == layout.columns.len() + usize::from(grouped) {
+            fields.push(String::new());
+        }
+        csv_line(fields)
+    };
+
+    let mut header: Vec<String> = layout
+        .columns
+        .iter()
+        .map(|c| c.title(&layout.strings).to_string())
+        .collect();
+    if linked {
+        header.push(layout.strings.text("table.source").to_string());
+    }
+    let mut out = with_group(layout.strings.text("table.group"), header);
+    for section in &layout.sections {
+        let group = section.title.as_deref().unwrap_or_default();
+        for (test, mut row) in section.tests.iter().zip(cells(layout, section)) {
+            if linked {
+                row.push(layout.source_url(test).unwrap_or_default());
+            }
+            out.push_str(&with_group(group, row));
+        }
+        if grouped {
+            out.push_str(&with_group(
+                group,
+                layout.summary_cells("table.subtotal", section.points()),
+            ));
+        }
+    }
+    if grouped {
+        out.push_str(&with_group(
+            "",
+            layout.summary_cells("table.total", layout.total()),
+        ));
+    }
+    out
+}
+
+/// One RFC 4180 record; fields with commas, quotes or newlines are quoted
+fn csv_line(fields: Vec<String>) -> String {
+    let fields: Vec<String> = fields
+        .into_iter()
+        .map(|f| {
+            if f.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", f.replace('"', "\"\""))
+            } else {
+                f
+            }
+        })
+        .collect();
+    format!("{}\n", fields.join(","))
+}
+
+// ---- JSON -------------------------------------------------------------------
+
+/// An array of rows, or `{ "total", "groups": [{ "group", "points", "tests" }] }` when grouped.
+/// Points and timeouts stay numbers; `source` is the link (or null) with `--link-source`.
+fn json(layout: &Layout) -> Result<String> {
+    let rows = |section: &Section| -> Vec<Value> {
+        section
+            .tests
+            .iter()
+            .map(|test| {
+                let mut row = Map::new();
+                for column in &layout.columns {
+                    let value = match column {
+                        Column::Points => Value::from(test.meta.points),
+                        Column::Timeout => Value::from(test.meta.timeout),
+                        c => Value::String(c.cell(test)),
+                    };
+                    row.insert(column.key().to_string(), value);
+                }
+                if layout.links.is_some() {
+                    row.insert("source".to_string(), layout.source_url(test).into());
+                }
+                Value::Object(row)
+            })
+            .collect()
+    };
+
+    let value = if layout.is_grouped() {
+        let groups: Vec<Value> = layout
+            .sections
+            .iter()
+            .map(|s| json!({ "group": s.title, "points": s.points(), "tests": rows(s) }))
+            .collect();
+        json!({ "total": layout.total(), "groups": groups })
+    } else {
+        Value::Array(rows(&layout.sections[0]))
+    };
+    Ok(format!("{}\n", serde_json::to_string_pretty(&value)?))
+}
+
+// ---- LaTeX ------------------------------------------------------------------
+
+fn latex(layout: &Layout) -> String {
+    let spec: String = layout
+        .columns
+        .iter()
+        .map(|c| match c {
+            Column::Description => "p{0.5\\textwidth}",
+            c if c.is_numeric() => "r",
+            _ => "l",
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let line = |cells: Vec<String>| format!("{} \\\\\n", cells.join(" & "));
+    let bold = |cells: Vec<String>| -> Vec<String> {
+        cells
+            .iter()
+            .map(|c| match c.is_empty() {
+                true => String::new(),
+                false => format!("\\textbf{{{}}}", latex_escape(c)),
+            })
+            .collect()
+    };
+
+    let mut out = format!("\\begin{{tabular}}{{{spec}}}\n\\hline\n");
+    out.push_str(&line(
+        layout
+            .columns
+            .iter()
+            .map(|c| latex_escape(c.title(&layout.strings)))
+            .collect(),
+    ));
+    out.push_str("\\hline\n");
+    for section in &layout.sections {
+        if let Some(title) = &section.title {
+            out.push_str(&format!(
+                "\\multicolumn{{{}}}{{l}}{{\\textbf{{{}}}}} \\\\\n",
+                layout.columns.len(),
+                latex_escape(title)
+            ));
+        }
+        for (test, row) in section.tests.iter().zip(cells(layout, section)) {
+            let row = layout
+                .columns
+                .iter()
+                .zip(row)
+                .map(|(c, v)| match (c, layout.source_url(test)) {
+                    // \href needs hyperref in the preamble
+                    (Column::Name, Some(url)) => format!(
+                        "\\href{{{}}}{{\\texttt{{{}}}}}",
+                        url.replace('%', "\\%").replace('#', "\\#"),
+                        latex_escape(&v)
+                    ),
+                    (Column::Name, None) => format!("\\texttt{{{}}}", latex_escape(&v)),
+                    _ => latex_escape(&v),
+                })
+                .collect();
+            out.push_str(&line(row));
+        }
+        if layout.is_grouped() {
+            out.push_str(&line(bold(
+                layout.summary_cells("table.subtotal", section.points()),
+            )));
+            out.push_str("\\hline\n");
+        }
+    }
+    if layout.is_grouped() {
+        out.push_str(&line(bold(
+            layout.summary_cells("table.total", layout.total()),
+        )));
+    }
+    out.push_str("\\hline\n\\end{tabular}\n");
+    out
+}
+
+/// LaTeX's special characters as text; newlines become spaces so a cell stays on one row
+pub fn latex_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\textbackslash{}"),
+            '~' => out.push_str("\\textasciitilde{}"),
+            '^' => out.push_str("\\textasciicircum{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' | '\r' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,235 @@
+// What a table shows: its columns, its rows grouped into sections and their order, and the point
+// totals. `formats` only decides how a layout is written out.
+use clap::ValueEnum;
+
+use super::source_links::SourceLinks;
+use crate::types::AutoTest;
+use crate::utils::i18n::Catalog;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Column {
+    Name,
+    Points,
+    Description,
+    /// Minutes, with `settings.default_timeout` applied
+    Timeout,
+    /// The test `type`
+    Kind,
+}
+
+impl Column {
+    /// Header text, in the catalog's language
+    pub fn title(self, strings: &Catalog) -> &str {
+        strings.text(match self {
+            Column::Name => "table.name",
+            Column::Points => "table.points",
+            Column::Description => "table.description",
+            Column::Timeout => "table.timeout",
+            Column::Kind => "table.kind",
+        })
+    }
+
+    /// Key in JSON output
+    pub fn key(self) -> &'static str {
+        match self {
+            Column::Name => "name",
+            Column::Points => "points",
+            Column::Description => "description",
+            Column::Timeout => "timeout",
+            Column::Kind => "kind",
+        }
+    }
+
+    pub fn is_numeric(self) -> bool {
+        matches!(self, Column::Points | Column::Timeout)
+    }
+
+    /// The unescaped cell
+    pub fn cell(self, test: &AutoTest) -> String {
+        match self {
+            Column::Name => test.display_name().to_string(),
+            Column::Points => test.meta.points.to_string(),
+            Column::Description => test.plain_description(),
+            Column::Timeout => test.meta.timeout.to_string(),
+            Column::Kind => test.kind.type_name().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GroupBy {
+    /// The module path in the test's `path` or name (`parser::tests::parses` -> `parser::tests`)
+    Module,
+    /// The Cargo.toml a cargo step runs against
+    Manifest,
+    /// The test `type`
+    Kind,
+    /// The test's `meta.group` label
+    Tag,
+}
+
+impl GroupBy {
+    /// `table.other_checks` is the section for steps that aren't cargo tests when grouping by
+    /// module or manifest
+    pub fn key(self, test: &AutoTest, strings: &Catalog) -> String {
+        let is_cargo_test = test.kind.type_name() == "cargo_test";
+        match self {
+            GroupBy::Module if !is_cargo_test => strings.text("table.other_checks").to_string(),
+            GroupBy::Module => match test
+                .meta
+                .path
+                .as_ref()
+                .unwrap_or(&test.meta.name)
+                .rsplit_once("::")
+            {
+                Some((module, _)) => module.to_string(),
+                None => strings.text("table.crate_root").to_string(),
+            },
+            GroupBy::Manifest => match (test.manifest_path(), test.kind.type_name()) {
+                (Some(path), _) => path.to_string(),
+                (None, "cargo_test" | "clippy" | "test_count") => "Cargo.toml".to_string(),
+                (None, _) => strings.text("table.other_checks").to_string(),
+            },
+            GroupBy::Kind => test.kind.type_name().to_string(),
+            GroupBy::Tag => test
+                .meta
+                .group
+                .clone()
+                .unwrap_or_else(|| strings.text("table.ungrouped").to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum SortBy {
+    /// The order of the autograder config
+    #[default]
+    Config,
+    /// A-Z
+    Name,
+    /// Most points first
+    Points,
+    /// Longest timeout first
+    Timeout,
+    /// By `type`, A-Z
+    Kind,
+}
+
+impl SortBy {
+    fn sort(self, tests: &mut [&AutoTest]) {
+        match self {
+            SortBy::Config => {}
+            SortBy::Name => tests.sort_by(|a, b| a.display_name().cmp(b.display_name())),
+            SortBy::Points => tests.sort_by_key(|t| std::cmp::Reverse(t.meta.points)),
+            SortBy::Timeout => tests.sort_by_key(|t| std::cmp::Reverse(t.meta.timeout)),
+            SortBy::Kind => tests.sort_by_key(|t| t.kind.type_name()),
+        }
+    }
+}
+
+pub struct Section<'a> {
+    /// None for an ungrouped table
+    pub title: Option<String>,
+    pub tests: Vec<&'a AutoTest>,
+}
+
+impl Section<'_> {
+    pub fn points(&self) -> u32 {
+        self.tests.iter().map(|t| t.meta.points).sum()
+    }
+}
+
+pub struct Layout<'a> {
+    pub columns: Vec<Column>,
+    /// A single untitled section unless grouped
+    pub sections: Vec<Section<'a>>,
+    grouped: bool,
+    /// Link test names to their source (`--link-source`)
+    pub links: Option<SourceLinks>,
+    /// Headers, group fallbacks and total labels
+    pub strings: Catalog,
+}
+
+impl<'a> Layout<'a> {
+    /// Groups keep the order in which they first appear in the config; sorting applies within each
+    pub fn new(
+        tests: &'a [AutoTest],
+        columns: &[Column],
+        group_by: Option<GroupBy>,
+        sort: SortBy,
+        strings: &Catalog,
+    ) -> Self {
+        let mut sections: Vec<Section<'a>> = Vec::new();
+        match group_by {
+            None => sections.push(Section {
+                title: None,
+                tests: tests.iter().collect(),
+            }),
+            Some(group_by) => {
+                for test in tests {
+                    let key = group_by.key(test, strings);
+                    match sections.iter_mut().find(|s| s.title.as_ref() == Some(&key)) {
+                        Some(section) => section.tests.push(test),
+                        None => sections.push(Section {
+                            title: Some(key),
+                            tests: vec![test],
+                        }),
+                    }
+                }
+            }
+        }
+        for section in &mut sections {
+            sort.sort(&mut section.tests);
+        }
+        Layout {
+            columns: columns.to_vec(),
+            sections,
+            grouped: group_by.is_some(),
+            links: None,
+            strings: strings.clone(),
+        }
+    }
+
+    pub fn with_links(mut self, links: Option<SourceLinks>) -> Self {
+        self.links = links;
+        self
+    }
+
+    pub fn source_url(&self, test: &AutoTest) -> Option<String> {
+        self.links.as_ref()?.url(test)
+    }
+
+    /// Subtotals and a grand total are only shown for grouped tables
+    pub fn is_grouped(&self) -> bool {
+        self.grouped
+    }
+
+    pub fn total(&self) -> u32 {
+        self.sections.iter().map(Section::points).sum()
+    }
+
+    /// Cells for a subtotal or total row: the label (`table.subtotal` / `table.total`) in the first
+    /// non-points column and the sum under Points (or folded into the label when that column
+    /// isn't shown)
+    pub fn summary_cells(&self, label_key: &str, points: u32) -> Vec<String> {
+        let label = self.strings.text(label_key);
+        let mut cells = vec![String::new(); self.columns.len()];
+        let points_at = self.columns.iter().position(|c| *c == Column::Points);
+        let label_at = (0..cells.len()).find(|&i| Some(i) != points_at);
+        match (label_at, points_at) {
+            (Some(l), Some(p)) => {
+                cells[l] = label.to_string();
+                cells[p] = points.to_string();
+            }
+            (Some(l), None) => {
+                cells[l] = self.strings.fmt(
+                    "table.label_points",
+                    &[("label", &label), ("points", &points)],
+                )
+            }
+            (None, Some(p)) => cells[p] = points.to_string(),
+            (None, None) => {}
+        }
+        cells
+    }
+}
@@ -1,52 +1,112 @@
-use crate::utils::{ensure_exists, read_autograder_config};
+use super::TableArgs;
+use super::build::apply_test_defaults;
+use crate::utils::i18n::Catalog;
+use crate::utils::read_autograder_config;
 use anyhow::{Context, Result};
 use cli_clipboard;
-use markdown_tables::as_table;
-use std::fs::OpenOptions;
-use std::io::Write;
+use formats::TableFormat;
+use layout::Layout;
+use source_links::SourceLinks;
+use std::fs;
 use std::path::Path;
 
-pub fn run(root: &Path, to_clipboard: bool, write_to_readme: bool) -> Result<()> {
-    let tests = read_autograder_config(root)?;
+pub mod formats;
+pub mod layout;
+pub mod readme;
+pub mod source_links;
+#[cfg(test)]
+pub mod tests;
 
-    let table = as_table(&tests);
+pub fn run(args: &TableArgs) -> Result<()> {
+    let root = args.root.as_path();
+    let config = read_autograder_config(root)?;
+    let strings = Catalog::for_project(
+        root,
+        &config.settings,
+        args.lang.as_deref(),
+        args.translations.as_deref(),
+    )?;
+    let tests = apply_test_defaults(config.tests, &config.settings)?;
 
-    if to_clipboard && !write_to_readme {
+    if (args.to_readme || args.check) && args.format != TableFormat::Markdown {
+        anyhow::bail!("--to-readme and --check only work with --format markdown");
+    }
+
+    let links = args.link_source.then(|| source_links(args));
+    let layout =
+        Layout::new(&tests, &args.columns, args.group_by, args.sort, &strings).with_links(links);
+    let table = formats::render(&layout, args.format)?;
+    let target = root.join(&args.file);
+
+    if args.check {
+        readme::check_table(&target, &table)?;
+        println!("{} table is up to date", target.to_string_lossy());
+        return Ok(());
+    }
+
+    if let Some(output) = &args.output {
+        if let Some(dir) = output.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.to_string_lossy()))?;
+        }
+        fs::write(output, &table)
+            .with_context(|| format!("Failed to write {}", output.to_string_lossy()))?;
+        println!("Wrote {}", output.to_string_lossy());
+        return Ok(());
+    }
+
+    if args.format != TableFormat::Markdown && args.no_clipboard {
+        // Bare output, so `--format csv --no-clipboard > rubric.csv` works
+        print!("{}", table);
+        return Ok(());
+    }
+
+    if !args.no_clipboard && !args.to_readme {
         cli_clipboard::set_contents(table.clone()).expect("copy to clipbard");
         println!("Table copied to clipboard:");
     } else {
         println!("README Table:\n{}", table);
     }
 
-    if write_to_readme {
-        write_content_to_readme(root, &table)?;
-        println!("Test description table appended to README.md");
+    if args.to_readme {
+        let replaced = readme::write_table(&target, &table, strings.text("table.readme_heading"))?;
+        if replaced {
+            println!("Updated the test table in {}", target.to_string_lossy());
+        } else {
+            println!(
+                "Test description table added to {}",
+                target.to_string_lossy()
+            );
+        }
     }
 
     Ok(())
 }
 
-fn write_content_to_readme(root: &Path, content: &str) -> Result<()> {
-    let readme_path = root.join("README.md");
-    ensure_exists(&readme_path)?;
-    let mut readme = OpenOptions::new()
-        .append(true)
-        .open(&readme_path)
-        .with_context(|| {
-            format!(
-                "Failed to open {} in append mode",
-                readme_path.to_string_lossy()
-            )
-        })?;
-
-    // Prepend a header to separate from existing content
-    let header = "\n## Autograder Test Cases\n\n";
-    readme
-        .write_all(header.as_bytes())
-        .expect("Failed to write header to README.md");
-    readme
-        .write_all(content.as_bytes())
-        .expect("Failed to write to README.md");
-
-    Ok(())
+/// Web URLs when a repository is given; relative links when the table is written into the
+/// repository; otherwise web URLs for the detected `origin`, falling back to root-relative paths
+fn source_links(args: &TableArgs) -> SourceLinks {
+    let root = args.root.as_path();
+    let branch = || {
+        args.branch
+            .clone()
+            .or_else(|| source_links::detect_branch(root))
+            .unwrap_or_else(|| "main".to_string())
+    };
+    if let Some(repo) = &args.repo_url {
+        return SourceLinks::blob(repo, &branch());
+    }
+    if args.to_readme || args.check {
+        return SourceLinks::relative_to(&args.file);
+    }
+    match source_links::detect_repo_url(root) {
+        Some(repo) => SourceLinks::blob(&repo, &branch()),
+        None => {
+            // stderr, so it stays out of redirected output
+            eprintln!(
+                "Warning: no repository URL found (pass --repo-url); source links are relative to the project root"
+            );
+            SourceLinks::relative_to(Path::new(""))
+        }
+    }
 }
@@ -0,0 +1,127 @@
+// Keep the test table in README.md (or another Markdown file) between marker comments, so
+// re-running `table --to-readme` replaces it instead of appending another copy.
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+pub const START_MARKER: &str = "<!-- autograder-table:start -->";
+pub const END_MARKER: &str = "<!-- autograder-table:end -->";
+/// The heading older versions appended, with the table right under it and no markers
+const LEGACY_HEADING: &str = "Autograder Test Cases";
+
+/// The text between the markers, if both are present (in order)
+fn region(contents: &str) -> Option<(usize, usize)> {
+    let start = contents.find(START_MARKER)? + START_MARKER.len();
+    let end = start + contents[start..].find(END_MARKER)?;
+    Some((start, end))
+}
+
+/// An unmarked `## <heading>` section from an older version: the heading line through the end of
+/// the table directly under it
+fn legacy_section(contents: &str, headings: &[&str]) -> Option<(usize, usize)> {
+    let mut offset = 0;
+    let mut lines = contents.split_inclusive('\n');
+    let start = loop {
+        let line = lines.next()?;
+        let title = line.trim_end().strip_prefix("## ").map(str::trim);
+        offset += line.len();
+        if title.is_some_and(|t| headings.contains(&t)) {
+            break offset - line.len();
+        }
+    };
+    let mut end = None;
+    for line in lines {
+        let trimmed = line.trim();
+        if trimmed.starts_with('|') {
+            end = Some(offset + line.len());
+        } else if !trimmed.is_empty() || end.is_some() {
+            break;
+        }
+        offset += line.len();
+    }
+    end.map(|end| (start, end))
+}
+
+fn marked_section(heading: &str, table: &str) -> String {
+    format!(
+        "## {}\n\n{}{}{}\n",
+        heading,
+        START_MARKER,
+        block(table),
+        END_MARKER
+    )
+}
+
+fn block(table: &str) -> String {
+    format!("\n{}\n", table.trim_end())
+}
+
+/// Read `path` with `\r\n` folded to `\n`, and whether the file used CRLF line endings
+fn read_normalized(path: &Path) -> Result<(String, bool)> {
+    let raw = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.to_string_lossy()))?;
+    let crlf = raw.contains("\r\n");
+    Ok((raw.replace("\r\n", "\n"), crlf))
+}
+
+/// Replace the marked region, or append a section (titled `heading`) with markers on first use.
+/// A legacy unmarked section is replaced by the marked one instead of being left behind.
+/// Returns whether an existing table was replaced. The file keeps its existing line endings.
+pub fn write_table(path: &Path, table: &str, heading: &str) -> Result<bool> {
+    let (contents, crlf) = if path.exists() {
+        read_normalized(path)?
+    } else {
+        (String::new(), false)
+    };
+
+    let (updated, replaced) = if let Some((start, end)) = region(&contents) {
+        let mut out = contents.clone();
+        out.replace_range(start..end, &block(table));
+        (out, true)
+    } else if let Some((start, end)) = legacy_section(&contents, &[heading, LEGACY_HEADING]) {
+        let mut out = contents.clone();
+        out.replace_range(start..end, &marked_section(heading, table));
+        (out, true)
+    } else {
+        let mut out = contents.clone();
+        if !out.is_empty() && !out.ends_with('\n') {
+            out.push('\n');
+        }
+        out.push('\n');
+        out.push_str(&marked_section(heading, table));
+        (out, false)
+    };
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.to_string_lossy()))?;
+    }
+    let updated = if crlf {
+        updated.replace('\n', "\r\n")
+    } else {
+        updated
+    };
+    fs::write(path, updated)
+        .with_context(|| format!("Failed to write {}", path.to_string_lossy()))?;
+    Ok(replaced)
+}
+
+/// Fail unless the marked region matches `table`
+pub fn check_table(path: &Path, table: &str) -> Result<()> {
+    let (contents, _) = read_normalized(path)?;
+    let Some((start, end)) = region(&contents) else {
+        anyhow::bail!(
+            "{} has no {} ... {} table; run `autograder-setup table --to-readme`",
+            path.to_string_lossy(),
+            START_MARKER,
+            END_MARKER
+        );
+    };
+    if contents[start..end].trim() != table.replace("\r\n", "\n").trim() {
+        anyhow::bail!(
+            "The table in {} is out of date with the autograder config; run `autograder-setup table --to-readme`",
+            path.to_string_lossy()
+        );
+    }
+    Ok(())
+}
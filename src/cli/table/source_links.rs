@@ -0,0 +1,103 @@
+// `table --link-source`: test names link to the file and line `init` recorded in `meta.source`.
+// Links are relative when the table lives in the repository, or web URLs for a hosted repository.
+use std::fs;
+use std::path::{Component, Path};
+
+use crate::types::AutoTest;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceLinks {
+    /// Relative to the file the table is written into; the prefix climbs back to the root
+    Relative { prefix: String },
+    /// `<repo>/blob/<branch>/<file>`
+    Blob { repo: String, branch: String },
+}
+
+impl SourceLinks {
+    /// Links for a table written to `file` (relative to the project root)
+    pub fn relative_to(file: &Path) -> Self {
+        // Only real directories count: `./README.md` is at the root, `docs/../README.md` too
+        let depth = file.parent().map_or(0, |dir| {
+            dir.components().fold(0usize, |depth, c| match c {
+                Component::Normal(_) => depth + 1,
+                Component::ParentDir => depth.saturating_sub(1),
+                _ => depth,
+            })
+        });
+        SourceLinks::Relative {
+            prefix: "../".repeat(depth),
+        }
+    }
+
+    pub fn blob(repo: &str, branch: &str) -> Self {
+        SourceLinks::Blob {
+            repo: web_url(repo).unwrap_or_else(|| repo.trim_end_matches('/').to_string()),
+            branch: branch.to_string(),
+        }
+    }
+
+    /// None for tests without a recorded source (written by hand or by an older `init`)
+    pub fn url(&self, test: &AutoTest) -> Option<String> {
+        let source = test.meta.source.as_ref()?;
+        let file = source.file.trim_start_matches("./");
+        Some(match self {
+            SourceLinks::Relative { prefix } => format!("{prefix}{file}#L{}", source.line),
+            SourceLinks::Blob { repo, branch } => {
+                format!("{repo}/blob/{branch}/{file}#L{}", source.line)
+            }
+        })
+    }
+}
+
+/// The `origin` remote in `.git/config`, as a web URL
+pub fn detect_repo_url(root: &Path) -> Option<String> {
+    let config = fs::read_to_string(root.join(".git").join("config")).ok()?;
+    let mut in_origin = false;
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_origin = line == r#"[remote "origin"]"#;
+        } else if in_origin
+            && let Some((key, value)) = line.split_once('=')
+            && key.trim() == "url"
+        {
+            return web_url(value.trim());
+        }
+    }
+    None
+}
+
+/// The checked-out branch, from `.git/HEAD`
+pub fn detect_branch(root: &Path) -> Option<String> {
+    let head = fs::read_to_string(root.join(".git").join("HEAD")).ok()?;
+    head.trim()
+        .strip_prefix("ref: refs/heads/")
+        .map(str::to_string)
+}
+
+/// `git@host:owner/repo.git`, `ssh://git@host/owner/repo.git` or `https://host/owner/repo.git`
+/// -> `https://host/owner/repo`
+pub fn web_url(remote: &str) -> Option<String> {
+    let remote = remote.trim().trim_end_matches('/');
+    let remote = remote.strip_suffix(".git").unwrap_or(remote);
+    let (host, path) = if let Some(rest) = remote
+        .strip_prefix("https://")
+        .or_else(|| remote.strip_prefix("http://"))
+        .or_else(|| remote.strip_prefix("ssh://"))
+    {
+        rest.split_once('/')?
+    } else {
+        // scp-like syntax: user@host:owner/repo
+        let (host, path) = remote.split_once(':')?;
+        if host.contains('/') {
+            return None;
+        }
+        (host, path)
+    };
+    // Drop credentials and ports
+    let host = host.rsplit('@').next()?;
+    let host = host.split(':').next()?;
+    if host.is_empty() || path.is_empty() {
+        return None;
+    }
+    Some(format!("https://{host}/{path}"))
+}
@@ -0,0 +1,454 @@
+use super::formats::*;
+use super::layout::*;
+use super::readme::*;
+use super::source_links::*;
+use crate::types::*;
+use crate::utils::i18n::Catalog;
+use std::fs;
+
+fn test(name: &str, points: u32, description: &str) -> AutoTest {
+    AutoTest {
+        meta: TestMeta {
+            name: name.into(),
+            description: description.into(),
+            points,
+            ..Default::default()
+        },
+        kind: TestKind::CargoTest {
+            manifest_path: None,
+        },
+        options: TestOptions::default(),
+    }
+}
+
+const TABLE: &str = "| Name | Points |\n|---|---|\n| `a` | 1 |\n";
+const HEADING: &str = "Autograder Test Cases";
+
+#[test]
+fn first_write_adds_markers_then_replaces_in_place() {
+    let dir = tempfile::tempdir().unwrap();
+    let readme = dir.path().join("README.md");
+    fs::write(&readme, "# Lab 3\n\nIntro.").unwrap();
+
+    assert!(!write_table(&readme, TABLE, HEADING).unwrap());
+    let first = fs::read_to_string(&readme).unwrap();
+    assert!(first.starts_with("# Lab 3\n\nIntro.\n\n## Autograder Test Cases\n\n"));
+    assert!(first.contains(&format!("{START_MARKER}\n{TABLE}{END_MARKER}\n")));
+
+    // Text after the table is kept, and a second run doesn't append another copy
+    fs::write(&readme, format!("{first}\n## Submitting\n")).unwrap();
+    let newer = TABLE.replace("| 1 |", "| 2 |");
+    assert!(write_table(&readme, &newer, HEADING).unwrap());
+    let second = fs::read_to_string(&readme).unwrap();
+    assert_eq!(second.matches(START_MARKER).count(), 1);
+    assert!(second.contains("| `a` | 2 |"));
+    assert!(!second.contains("| `a` | 1 |"));
+    assert!(second.ends_with("## Submitting\n"));
+}
+
+#[test]
+fn custom_target_is_created() {
+    let dir = tempfile::tempdir().unwrap();
+    let target = dir.path().join("docs/GRADING.md");
+    write_table(&target, TABLE, HEADING).unwrap();
+    assert!(fs::read_to_string(target).unwrap().contains(TABLE));
+}
+
+#[test]
+fn check_detects_missing_and_stale_tables() {
+    let dir = tempfile::tempdir().unwrap();
+    let readme = dir.path().join("README.md");
+    fs::write(&readme, "# Lab 3\n").unwrap();
+
+    let err = format!("{:#}", check_table(&readme, TABLE).unwrap_err());
+    assert!(err.contains("has no"), "{err}");
+
+    write_table(&readme, TABLE, HEADING).unwrap();
+    check_table(&readme, TABLE).unwrap();
+
+    let err = format!(
+        "{:#}",
+        check_table(&readme, &TABLE.replace("| 1 |", "| 3 |")).unwrap_err()
+    );
+    assert!(err.contains("out of date"), "{err}");
+}
+
+#[test]
+fn legacy_unmarked_table_is_replaced_not_duplicated() {
+    let dir = tempfile::tempdir().unwrap();
+    let readme = dir.path().join("README.md");
+    let legacy = "# Lab 3\n\n## Autograder Test Cases\n\n| Name | Points |\n|---|---|\n| `old` | 9 |\n\n## Submitting\n";
+    fs::write(&readme, legacy).unwrap();
+
+    assert!(write_table(&readme, TABLE, HEADING).unwrap());
+    let written = fs::read_to_string(&readme).unwrap();
+    assert!(!written.contains("`old`"), "{written}");
+    assert_eq!(written.matches("## Autograder Test Cases").count(), 1);
+    assert!(
+        written.contains(&format!(
+            "## Autograder Test Cases\n\n{START_MARKER}\n{TABLE}{END_MARKER}\n\n## Submitting\n"
+        )),
+        "{written}"
+    );
+    check_table(&readme, TABLE).unwrap();
+}
+
+#[test]
+fn crlf_readmes_keep_their_line_endings() {
+    let dir = tempfile::tempdir().unwrap();
+    let readme = dir.path().join("README.md");
+    fs::write(&readme, "# Lab 3\r\n\r\nIntro.\r\n").unwrap();
+
+    write_table(&readme, TABLE, HEADING).unwrap();
+    check_table(&readme, TABLE).unwrap();
+    write_table(&readme, TABLE, HEADING).unwrap();
+    check_table(&readme, TABLE).unwrap();
+
+    let written = fs::read_to_string(&readme).unwrap();
+    assert_eq!(written.matches(START_MARKER).count(), 1);
+    assert_eq!(
+        written.matches('\n').count(),
+        written.matches("\r\n").count()
+    );
+}
+
+const DEFAULT_COLUMNS: [Column; 3] = [Column::Name, Column::Points, Column::Description];
+
+/// Render `tests` ungrouped with the default columns
+fn flat(tests: &[AutoTest], format: TableFormat) -> anyhow::Result<String> {
+    let layout = Layout::new(
+        tests,
+        &DEFAULT_COLUMNS,
+        None,
+        SortBy::Config,
+        &Catalog::english(),
+    );
+    render(&layout, format)
+}
+
+#[test]
+fn formats_escape_their_own_special_characters() -> anyhow::Result<()> {
+    let tests = [test("fits_50%", 5, "a <b> & \"c\", d | e_f\nsecond line")];
+
+    let markdown = flat(&tests, TableFormat::Markdown)?;
+    assert!(markdown.contains("| `fits_50%` |"), "{markdown}");
+    assert!(markdown.contains(r"d \| e_f"), "{markdown}");
+
+    let html = flat(&tests, TableFormat::Html)?;
+    assert!(html.contains("<th>Name</th><th>Points</th><th>Description</th>"));
+    assert!(html.contains(
+        "<td><code>fits_50%</code></td><td>5</td><td>a &lt;b&gt; &amp; &quot;c&quot;, d | e_f<br>second line</td>"
+    ), "{html}");
+
+    let csv = flat(&tests, TableFormat::Csv)?;
+    assert_eq!(
+        csv,
+        "Name,Points,Description\nfits_50%,5,\"a <b> & \"\"c\"\", d | e_f\nsecond line\"\n"
+    );
+
+    let json: serde_json::Value = serde_json::from_str(&flat(&tests, TableFormat::Json)?)?;
+    assert_eq!(json[0]["name"], "fits_50%");
+    assert_eq!(json[0]["points"], 5);
+    assert_eq!(
+        json[0]["description"],
+        "a <b> & \"c\", d | e_f\nsecond line"
+    );
+
+    let latex = flat(&tests, TableFormat::Latex)?;
+    assert!(latex.starts_with("\\begin{tabular}"));
+    assert!(
+        latex.contains(r#"\texttt{fits\_50\%} & 5 & a <b> \& "c", d | e\_f second line \\"#),
+        "{latex}"
+    );
+    Ok(())
+}
+
+#[test]
+fn every_format_fills_hashes() -> anyhow::Result<()> {
+    let mut commits = test("commits", 1, "At least ## commits");
+    commits.kind = TestKind::CommitCount { min_commits: 3 };
+    for format in [TableFormat::Markdown, TableFormat::Csv] {
+        assert!(flat(&[commits.clone()], format)?.contains("At least 3 commits"));
+    }
+    Ok(())
+}
+
+fn course_tests() -> Vec<AutoTest> {
+    let mut tests = vec![
+        test("parser::parses_numbers", 5, "Numbers"),
+        test("eval::adds", 3, "Addition"),
+        test("parser::rejects_junk", 10, "Bad input"),
+        test("clippy", 2, "No clippy warnings"),
+    ];
+    tests[3].kind = TestKind::Clippy {
+        manifest_path: None,
+    };
+    tests[0].meta.group = Some("part-1".into());
+    tests
+}
+
+#[test]
+fn grouping_keys_come_from_name_manifest_kind_and_group() {
+    let tests = course_tests();
+    let keys = |by: GroupBy| -> Vec<String> {
+        tests
+            .iter()
+            .map(|t| by.key(t, &Catalog::english()))
+            .collect()
+    };
+    assert_eq!(
+        keys(GroupBy::Module),
+        ["parser", "eval", "parser", "Other checks"]
+    );
+    assert_eq!(
+        keys(GroupBy::Manifest),
+        ["Cargo.toml", "Cargo.toml", "Cargo.toml", "Cargo.toml"]
+    );
+    assert_eq!(
+        keys(GroupBy::Kind),
+        ["cargo_test", "cargo_test", "cargo_test", "clippy"]
+    );
+    assert_eq!(
+        keys(GroupBy::Tag),
+        ["part-1", "Ungrouped", "Ungrouped", "Ungrouped"]
+    );
+}
+
+#[test]
+fn grouped_markdown_has_subtotals_and_a_total() -> anyhow::Result<()> {
+    let tests = course_tests();
+    let layout = Layout::new(
+        &tests,
+        &[Column::Name, Column::Points],
+        Some(GroupBy::Module),
+        SortBy::Points,
+        &Catalog::english(),
+    );
+    assert_eq!(
+        render(&layout, TableFormat::Markdown)?,
+        "### parser\n\n\
+         | Name                     | Points |\n\
+         |--------------------------|--------|\n\
+         | `parser::rejects_junk`   | 10     |\n\
+         | `parser::parses_numbers` | 5      |\n\
+         | **Subtotal**             | **15** |\n\n\
+         ### eval\n\n\
+         | Name         | Points |\n\
+         |--------------|--------|\n\
+         | `eval::adds` | 3      |\n\
+         | **Subtotal** | **3**  |\n\n\
+         ### Other checks\n\n\
+         | Name         | Points |\n\
+         |--------------|--------|\n\
+         | `clippy`     | 2      |\n\
+         | **Subtotal** | **2**  |\n\n\
+         **Total: 20 points**\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn grouped_csv_and_json_keep_group_structure() -> anyhow::Result<()> {
+    let tests = course_tests();
+    let layout = Layout::new(
+        &tests,
+        &[Column::Name, Column::Timeout, Column::Kind],
+        Some(GroupBy::Kind),
+        SortBy::Name,
+        &Catalog::english(),
+    );
+
+    let csv = render(&layout, TableFormat::Csv)?;
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "Group,Name,Timeout (min),Kind");
+    assert_eq!(lines[1], "cargo_test,eval::adds,0,cargo_test");
+    assert_eq!(lines[4], "cargo_test,Subtotal: 18 points,,");
+    assert_eq!(lines.last(), Some(&",Total: 20 points,,"));
+
+    let json: serde_json::Value = serde_json::from_str(&render(&layout, TableFormat::Json)?)?;
+    assert_eq!(json["total"], 20);
+    assert_eq!(json["groups"][1]["group"], "clippy");
+    assert_eq!(json["groups"][1]["points"], 2);
+    assert_eq!(json["groups"][0]["tests"][0]["timeout"], 0);
+    assert_eq!(json["groups"][0]["tests"][0]["kind"], "cargo_test");
+    Ok(())
+}
+
+#[test]
+fn spanish_catalog_translates_headers_groups_and_totals() -> anyhow::Result<()> {
+    let es = Catalog::load(Some("es"), None)?;
+    let tests = course_tests();
+    let layout = Layout::new(
+        &tests,
+        &[Column::Name, Column::Description],
+        Some(GroupBy::Tag),
+        SortBy::Config,
+        &es,
+    );
+    let csv = render(&layout, TableFormat::Csv)?;
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "Grupo,Nombre,Descripción");
+    assert!(lines.iter().any(|l| l.starts_with("Sin grupo,")), "{csv}");
+    assert_eq!(lines.last(), Some(&",Total: 20 puntos,"));
+    // JSON keys are for programs, so they stay the same in every language
+    let json: serde_json::Value = serde_json::from_str(&render(&layout, TableFormat::Json)?)?;
+    assert_eq!(json["groups"][1]["group"], "Sin grupo");
+    assert!(json["groups"][0]["tests"][0].get("description").is_some());
+
+    let dir = tempfile::tempdir()?;
+    let readme = dir.path().join("README.md");
+    write_table(&readme, TABLE, es.text("table.readme_heading"))?;
+    assert!(fs::read_to_string(&readme)?.contains("## Pruebas del autocorrector\n"));
+    Ok(())
+}
+
+#[test]
+fn grouping_an_empty_config_still_renders() -> anyhow::Result<()> {
+    let layout = Layout::new(
+        &[],
+        &[Column::Name],
+        Some(GroupBy::Tag),
+        SortBy::Config,
+        &Catalog::english(),
+    );
+    for format in [TableFormat::Markdown, TableFormat::Json, TableFormat::Html] {
+        render(&layout, format)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn empty_markdown_table_keeps_its_header() -> anyhow::Result<()> {
+    let layout = Layout::new(
+        &[],
+        &DEFAULT_COLUMNS,
+        None,
+        SortBy::Config,
+        &Catalog::english(),
+    );
+    let markdown = render(&layout, TableFormat::Markdown)?;
+    assert!(markdown.starts_with("| Name "), "{markdown}");
+    assert_eq!(markdown.lines().count(), 2, "{markdown}");
+    Ok(())
+}
+
+#[test]
+fn resolved_description_escapes_for_markdown() {
+    let mut t = test("a", 1, r"at least ## commits | a\b");
+    t.kind = TestKind::CommitCount { min_commits: 3 };
+    assert_eq!(t.resolved_description(), r"at least 3 commits \| a\\b");
+    assert_eq!(t.plain_description(), r"at least 3 commits | a\b");
+}
+
+fn linked_test() -> AutoTest {
+    let mut t = test("adds", 2, "Adds");
+    t.meta.source = Some(SourceLocation {
+        file: "src/lib.rs".into(),
+        line: 42,
+    });
+    t
+}
+
+#[test]
+fn source_links_are_relative_to_the_table_file_or_blob_urls() {
+    let t = linked_test();
+    let readme = SourceLinks::relative_to(std::path::Path::new("README.md"));
+    assert_eq!(readme.url(&t).as_deref(), Some("src/lib.rs#L42"));
+    let nested = SourceLinks::relative_to(std::path::Path::new("docs/GRADING.md"));
+    assert_eq!(nested.url(&t).as_deref(), Some("../src/lib.rs#L42"));
+    for dotted in ["./README.md", "./docs/../README.md"] {
+        let links = SourceLinks::relative_to(std::path::Path::new(dotted));
+        assert_eq!(links.url(&t).as_deref(), Some("src/lib.rs#L42"), "{dotted}");
+    }
+    let dotted_nested = SourceLinks::relative_to(std::path::Path::new("./docs/GRADING.md"));
+    assert_eq!(dotted_nested.url(&t).as_deref(), Some("../src/lib.rs#L42"));
+
+    let blob = SourceLinks::blob("git@github.com:course/lab-3.git", "main");
+    assert_eq!(
+        blob.url(&t).as_deref(),
+        Some("https://github.com/course/lab-3/blob/main/src/lib.rs#L42")
+    );
+    // Hand-written tests have no recorded source
+    assert_eq!(blob.url(&test("manual", 1, "")), None);
+}
+
+#[test]
+fn remote_urls_become_web_urls() {
+    for remote in [
+        "git@github.com:course/lab-3.git",
+        "https://github.com/course/lab-3.git",
+        "https://token@github.com/course/lab-3",
+        "ssh://git@github.com:22/course/lab-3.git",
+        "https://github.com/course/lab-3/",
+    ] {
+        assert_eq!(
+            web_url(remote).as_deref(),
+            Some("https://github.com/course/lab-3"),
+            "{remote}"
+        );
+    }
+    assert_eq!(web_url("../local-mirror"), None);
+}
+
+#[test]
+fn repo_and_branch_are_detected_from_dot_git() {
+    let dir = tempfile::tempdir().unwrap();
+    let git = dir.path().join(".git");
+    fs::create_dir_all(&git).unwrap();
+    fs::write(
+        git.join("config"),
+        "[core]\n\tbare = false\n[remote \"upstream\"]\n\turl = https://github.com/other/repo\n[remote \"origin\"]\n\turl = git@github.com:course/lab-3.git\n\tfetch = +refs/heads/*:refs/remotes/origin/*\n",
+    )
+    .unwrap();
+    fs::write(git.join("HEAD"), "ref: refs/heads/fall-2025\n").unwrap();
+
+    assert_eq!(
+        detect_repo_url(dir.path()).as_deref(),
+        Some("https://github.com/course/lab-3")
+    );
+    assert_eq!(detect_branch(dir.path()).as_deref(), Some("fall-2025"));
+}
+
+#[test]
+fn linked_names_in_each_format() -> anyhow::Result<()> {
+    let tests = [linked_test(), test("manual", 1, "No source")];
+    let layout = Layout::new(
+        &tests,
+        &DEFAULT_COLUMNS,
+        None,
+        SortBy::Config,
+        &Catalog::english(),
+    )
+    .with_links(Some(SourceLinks::relative_to(std::path::Path::new(
+        "README.md",
+    ))));
+
+    let markdown = render(&layout, TableFormat::Markdown)?;
+    assert!(
+        markdown.contains("| [`adds`](src/lib.rs#L42) |"),
+        "{markdown}"
+    );
+    assert!(markdown.contains("| `manual`"), "{markdown}");
+
+    let html = render(&layout, TableFormat::Html)?;
+    assert!(html.contains(r#"<td><a href="src/lib.rs#L42"><code>adds</code></a></td>"#));
+
+    let csv = render(&layout, TableFormat::Csv)?;
+    assert!(
+        csv.starts_with(
+            "Name,Points,Description,Source\nadds,2,Adds,src/lib.rs#L42\nmanual,1,No source,\n"
+        ),
+        "{csv}"
+    );
+
+    let json: serde_json::Value = serde_json::from_str(&render(&layout, TableFormat::Json)?)?;
+    assert_eq!(json[0]["source"], "src/lib.rs#L42");
+    assert!(json[1]["source"].is_null());
+
+    let latex = render(&layout, TableFormat::Latex)?;
+    assert!(
+        latex.contains(r"\href{src/lib.rs\#L42}{\texttt{adds}}"),
+        "{latex}"
+    );
+    Ok(())
+}
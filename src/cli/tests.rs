@@ -148,3 +148,31 @@ fn parse_table_flags() {
         _ => panic!("expected table"),
     }
 }
+
+#[test]
+fn parse_build_trigger_flags() {
+    let cli = Cli::try_parse_from([
+        "autograder-setup",
+        "build",
+        "--workflow-dispatch",
+        "--schedule",
+        "0 3 * * *",
+        "--push-paths",
+        "src/**",
+        "tests/**",
+        "--cancel-superseded",
+    ])
+    .expect("parse ok");
+
+    match cli.command {
+        Command::Build(a) => {
+            let cfg = BuildConfig::from(a);
+            assert!(cfg.triggers.workflow_dispatch);
+            assert_eq!(cfg.triggers.schedule, vec!["0 3 * * *"]);
+            assert_eq!(cfg.triggers.push_paths, vec!["src/**", "tests/**"]);
+            assert!(cfg.triggers.on_push(), "path filters imply push");
+            assert!(cfg.triggers.cancel_superseded);
+        }
+        _ => panic!("expected build"),
+    }
+}
@@ -0,0 +1,52 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+use crate::types::config::CONFIG_VERSION;
+use crate::utils::{
+    autograder_config_format, autograder_config_path, config_version, ensure_exists,
+    load_autograder_config, write_autograder_config,
+};
+
+/// Rewrite the config in the current schema version, keeping its format. With `check`, only report whether
+/// an upgrade is needed (and fail if it is).
+pub fn run(root: &Path, check: bool) -> Result<()> {
+    let path = autograder_config_path(root);
+    ensure_exists(&path)?;
+    let raw = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.to_string_lossy()))?;
+    let value = autograder_config_format(root)
+        .to_value(&raw)
+        .with_context(|| format!("Failed to parse {}", path.to_string_lossy()))?;
+
+    let from = config_version(&value);
+    if from == CONFIG_VERSION && value.get("version").is_some() {
+        println!(
+            "{} is already version {}",
+            path.to_string_lossy(),
+            CONFIG_VERSION
+        );
+        return Ok(());
+    }
+    if check {
+        anyhow::bail!(
+            "{} is version {} (current is {}); run `autograder-setup upgrade`",
+            path.to_string_lossy(),
+            from,
+            CONFIG_VERSION
+        );
+    }
+
+    let config = load_autograder_config(root)?;
+    write_autograder_config(root, &config)?;
+    println!(
+        "Upgraded {} from version {} to {}",
+        path.to_string_lossy(),
+        from,
+        CONFIG_VERSION
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests;
@@ -0,0 +1,57 @@
+use super::*;
+use crate::utils::read_autograder_config;
+
+const LEGACY: &str = r#"[
+  {
+    "meta": { "name": "adds", "description": "", "points": 2, "timeout": 10 },
+    "type": "cargo_test"
+  }
+]"#;
+
+fn write_raw(root: &Path, contents: &str) {
+    fs::create_dir_all(root.join(".autograder")).unwrap();
+    fs::write(autograder_config_path(root), contents).unwrap();
+}
+
+fn raw_json(root: &Path) -> serde_json::Value {
+    serde_json::from_str(&fs::read_to_string(autograder_config_path(root)).unwrap()).unwrap()
+}
+
+#[test]
+fn upgrades_legacy_array_to_versioned_object() {
+    let dir = tempfile::tempdir().unwrap();
+    write_raw(dir.path(), LEGACY);
+
+    run(dir.path(), false).unwrap();
+    let json = raw_json(dir.path());
+    assert_eq!(json["version"], CONFIG_VERSION);
+    assert!(json["settings"].is_object());
+    assert_eq!(json["tests"][0]["meta"]["name"], "adds");
+    assert_eq!(json["tests"][0]["meta"]["points"], 2);
+}
+
+#[test]
+fn check_fails_for_outdated_files_and_leaves_them_alone() {
+    let dir = tempfile::tempdir().unwrap();
+    write_raw(dir.path(), LEGACY);
+
+    let err = run(dir.path(), true).unwrap_err();
+    assert!(err.to_string().contains("version 1"), "{err}");
+    assert!(raw_json(dir.path()).is_array());
+
+    run(dir.path(), false).unwrap();
+    run(dir.path(), true).unwrap();
+}
+
+#[test]
+fn legacy_array_reads_transparently_and_newer_versions_are_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    write_raw(dir.path(), LEGACY);
+    let config = read_autograder_config(dir.path()).unwrap();
+    assert_eq!(config.version, CONFIG_VERSION);
+    assert_eq!(config.tests.len(), 1);
+
+    write_raw(dir.path(), r#"{ "version": 99, "tests": [] }"#);
+    let err = format!("{:#}", read_autograder_config(dir.path()).unwrap_err());
+    assert!(err.contains("version 99"), "{err}");
+}
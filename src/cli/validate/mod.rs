@@ -0,0 +1,556 @@
+use anyhow::{Context, Result};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::build::apply_test_defaults;
+use crate::cli::build::build_functions::{runner_ids, step_slug};
+use crate::cli::init::GENERATED_CHECKS;
+use crate::cli::init::scan::find_all_tests;
+use crate::types::config::AutograderConfig;
+use crate::types::{AutoTest, TestKind};
+use crate::utils::config_format::ConfigFormat;
+use crate::utils::{
+    autograder_config_path, closest, collect_rs_files_with_manifest, ensure_exists,
+    existing_config_paths, extends, parse_autograder_config, slug_id,
+};
+use spans::{Node, Pos};
+
+pub mod spans;
+#[cfg(test)]
+pub mod tests;
+
+const TOP_LEVEL_FIELDS: &[&str] = &["$schema", "version", "extends", "settings", "tests"];
+const SETTINGS_FIELDS: &[&str] = &[
+    "toolchain",
+    "default_timeout",
+    "total_points",
+    "triggers",
+    "pin_actions",
+    "action_mirror",
+    "action_mirror_names",
+    "action_pins",
+    "defaults",
+    "lang",
+    "translations",
+    "name_templates",
+];
+const TRIGGER_FIELDS: &[&str] = &[
+    "grade_on_push",
+    "workflow_dispatch",
+    "schedule",
+    "push_branches",
+    "push_paths",
+    "cancel_superseded",
+];
+const META_FIELDS: &[&str] = &[
+    "name",
+    "display_name",
+    "filter",
+    "path",
+    "description",
+    "points",
+    "timeout",
+    "group",
+    "source",
+];
+const OPTION_FIELDS: &[&str] = &[
+    "env",
+    "features",
+    "profile",
+    "test_threads",
+    "target",
+    "extra_args",
+    "setup",
+];
+/// Every `type` and the fields it takes (besides `meta`, `type` and the test options)
+const KIND_FIELDS: &[(&str, &[&str])] = &[
+    ("cargo_test", &["manifest_path"]),
+    ("clippy", &["manifest_path"]),
+    ("commit_count", &["min_commits"]),
+    ("test_count", &["min_tests", "manifest_path"]),
+    ("branch_count", &["min_branches"]),
+    ("command", &["command"]),
+    ("io", &["command", "input", "expected_output", "comparison"]),
+];
+const COMPARISONS: &[&str] = &["included", "exact", "regex"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub pos: Option<Pos>,
+    pub message: String,
+    pub help: Option<String>,
+}
+
+/// Everything `validate` found in one config file
+#[derive(Debug)]
+pub struct Report {
+    pub path: PathBuf,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Report {
+    pub fn errors(&self) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+            .count()
+    }
+
+    fn error(&mut self, pos: Option<Pos>, message: String, help: Option<String>) {
+        self.push(Severity::Error, pos, message, help);
+    }
+
+    fn warning(&mut self, pos: Option<Pos>, message: String, help: Option<String>) {
+        self.push(Severity::Warning, pos, message, help);
+    }
+
+    fn push(
+        &mut self,
+        severity: Severity,
+        pos: Option<Pos>,
+        message: String,
+        help: Option<String>,
+    ) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            pos,
+            message,
+            help,
+        });
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for d in &self.diagnostics {
+            let label = match d.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            };
+            writeln!(f, "{}: {}", label, d.message)?;
+            match d.pos {
+                Some(p) => writeln!(
+                    f,
+                    "  --> {}:{}:{}",
+                    self.path.to_string_lossy(),
+                    p.line,
+                    p.col
+                )?,
+                None => writeln!(f, "  --> {}", self.path.to_string_lossy())?,
+            }
+            if let Some(help) = &d.help {
+                writeln!(f, "   = help: {}", help)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn run(root: &Path) -> Result<()> {
+    let report = check(root)?;
+    print!("{}", report);
+    let errors = report.errors();
+    if errors > 0 {
+        anyhow::bail!("{} has {} error(s)", report.path.to_string_lossy(), errors);
+    }
+    println!(
+        "{} is valid ({} warning(s))",
+        report.path.to_string_lossy(),
+        report.diagnostics.len()
+    );
+    Ok(())
+}
+
+/// Check the config file: its shape (with positions), then the merged tests against the project
+pub fn check(root: &Path) -> Result<Report> {
+    let path = autograder_config_path(root);
+    ensure_exists(&path)?;
+    let mut report = Report {
+        path: path.clone(),
+        diagnostics: Vec::new(),
+    };
+
+    let others: Vec<_> = existing_config_paths(root)
+        .into_iter()
+        .filter(|p| *p != path)
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+    if !others.is_empty() {
+        report.error(
+            None,
+            format!("other autograder configs exist too: {}", others.join(", ")),
+            Some("keep only one; `convert` switches formats".into()),
+        );
+    }
+
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.to_string_lossy()))?;
+    let format = ConfigFormat::from_path(&path).unwrap_or_default();
+    let tree = match spans::parse(&contents, format) {
+        Ok(tree) => tree,
+        Err(e) => {
+            report.error(e.pos, format!("syntax error: {}", e.message), None);
+            return Ok(report);
+        }
+    };
+
+    check_shape(&tree, &mut report);
+    let config = match parse_autograder_config(&contents, format) {
+        Ok(config) => config,
+        Err(e) => {
+            // Shape errors usually explain why serde gave up; only report serde's when they don't
+            if report.errors() == 0 {
+                report.error(None, format!("{:#}", e), None);
+            }
+            return Ok(report);
+        }
+    };
+    let resolved = match extends::resolve(&config, &path) {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            let pos = tree.get("extends").map(|e| e.node.pos);
+            report.error(pos, format!("{:#}", e), None);
+            return Ok(report);
+        }
+    };
+    check_tests(root, &tree, &resolved, &mut report)?;
+    Ok(report)
+}
+
+// ---- Shape: field names and `type` values ------------------------------------
+
+fn check_shape(tree: &Node, report: &mut Report) {
+    if tree.items().is_empty() && tree.entries().is_empty() {
+        return;
+    }
+    // A legacy (version 1) file is a bare array of tests
+    let tests = match tree.get("tests") {
+        Some(entry) => {
+            check_fields(tree, TOP_LEVEL_FIELDS, "the top level", report);
+            entry.node.items()
+        }
+        None if !tree.items().is_empty() => tree.items(),
+        None => {
+            check_fields(tree, TOP_LEVEL_FIELDS, "the top level", report);
+            &[]
+        }
+    };
+
+    if let Some(settings) = tree.get("settings") {
+        check_fields(&settings.node, SETTINGS_FIELDS, "settings", report);
+        if let Some(triggers) = settings.node.get("triggers") {
+            check_fields(&triggers.node, TRIGGER_FIELDS, "settings.triggers", report);
+        }
+        if let Some(defaults) = settings.node.get("defaults") {
+            check_fields(&defaults.node, OPTION_FIELDS, "settings.defaults", report);
+        }
+        if let Some(names) = settings.node.get("name_templates") {
+            let kinds: Vec<&str> = GENERATED_CHECKS.iter().map(|(k, _, _)| *k).collect();
+            check_fields(&names.node, &kinds, "settings.name_templates", report);
+        }
+    }
+
+    for (i, test) in tests.iter().enumerate() {
+        check_test_shape(i, test, report);
+    }
+}
+
+fn check_test_shape(index: usize, test: &Node, report: &mut Report) {
+    let label = match test
+        .get("meta")
+        .and_then(|m| m.node.get("name"))
+        .and_then(|n| n.node.as_str())
+    {
+        Some(name) => format!("test `{}`", name),
+        None => format!("test #{}", index + 1),
+    };
+
+    match test.get("meta") {
+        Some(meta) => check_fields(&meta.node, META_FIELDS, &format!("{label}'s meta"), report),
+        None => report.error(Some(test.pos), format!("{label} has no `meta`"), None),
+    }
+
+    let Some(kind) = test.get("type") else {
+        report.error(Some(test.pos), format!("{label} has no `type`"), None);
+        return;
+    };
+    let kind_name = kind.node.as_str().unwrap_or_default();
+    let Some((_, kind_fields)) = KIND_FIELDS.iter().find(|(k, _)| *k == kind_name) else {
+        let kinds: Vec<&str> = KIND_FIELDS.iter().map(|(k, _)| *k).collect();
+        report.error(
+            Some(kind.node.pos),
+            format!("unknown test type `{}` in {}", kind_name, label),
+            Some(
+                suggest(kind_name, &kinds)
+                    .unwrap_or_else(|| format!("expected one of {}", kinds.join(", "))),
+            ),
+        );
+        return;
+    };
+
+    let mut allowed: Vec<&str> = vec!["meta", "type"];
+    allowed.extend(kind_fields.iter());
+    allowed.extend(OPTION_FIELDS);
+    for entry in test.entries() {
+        if allowed.contains(&entry.key.as_str()) {
+            continue;
+        }
+        let owner = KIND_FIELDS
+            .iter()
+            .find(|(_, fields)| fields.contains(&entry.key.as_str()));
+        let help = match owner {
+            Some((owner, _)) => Some(format!("`{}` only applies to `{}` tests", entry.key, owner)),
+            None if META_FIELDS.contains(&entry.key.as_str()) => {
+                Some(format!("`{}` belongs inside `meta`", entry.key))
+            }
+            None => suggest(&entry.key, &allowed).or_else(|| {
+                closest(&entry.key, META_FIELDS).map(|c| format!("did you mean `meta.{}`?", c))
+            }),
+        };
+        report.error(
+            Some(entry.key_pos),
+            format!("unknown field `{}` in {}", entry.key, label),
+            help,
+        );
+    }
+
+    if let Some(cmp) = test.get("comparison")
+        && let Some(value) = cmp.node.as_str()
+        && !COMPARISONS.contains(&value)
+    {
+        report.error(
+            Some(cmp.node.pos),
+            format!("unknown comparison `{}` in {}", value, label),
+            suggest(value, COMPARISONS),
+        );
+    }
+}
+
+fn check_fields(node: &Node, allowed: &[&str], within: &str, report: &mut Report) {
+    for entry in node.entries() {
+        if !allowed.contains(&entry.key.as_str()) {
+            report.error(
+                Some(entry.key_pos),
+                format!("unknown field `{}` in {}", entry.key, within),
+                suggest(&entry.key, allowed),
+            );
+        }
+    }
+}
+
+fn lowercase_first(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) => c.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// "did you mean" for a close enough candidate
+fn suggest(word: &str, candidates: &[&str]) -> Option<String> {
+    closest(word, candidates).map(|c| format!("did you mean `{}`?", c))
+}
+
+// ---- Tests: names, points, paths, and the source ----------------------------
+
+fn check_tests(
+    root: &Path,
+    tree: &Node,
+    config: &AutograderConfig,
+    report: &mut Report,
+) -> Result<()> {
+    // Where each name is written in this file (tests from a base have no position)
+    let tests = tree
+        .get("tests")
+        .map(|e| e.node.items())
+        .unwrap_or(tree.items());
+    let mut name_positions: Vec<(&str, Pos)> = Vec::new();
+    for test in tests {
+        if let Some(name) = test.get("meta").and_then(|m| m.node.get("name")) {
+            name_positions.push((name.node.as_str().unwrap_or_default(), name.node.pos));
+        }
+    }
+    let pos_of = |name: &str| {
+        name_positions
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, p)| *p)
+    };
+
+    for (i, (name, pos)) in name_positions.iter().enumerate() {
+        if let Some((_, first)) = name_positions[..i].iter().find(|(n, _)| n == name) {
+            report.error(
+                Some(*pos),
+                format!("duplicate test name `{}`", name),
+                Some(format!("first used on line {}", first.line)),
+            );
+        }
+    }
+
+    check_step_ids(config, &pos_of, report);
+    for test in &config.tests {
+        let name = test.meta.name.as_str();
+        if test.meta.points == 0 {
+            report.warning(
+                pos_of(name),
+                format!(
+                    "test `{}` has 0 points and is left out of the workflow",
+                    name
+                ),
+                None,
+            );
+        }
+
+        if let Some(manifest) = test.manifest_path()
+            && !root.join(manifest).exists()
+        {
+            report.error(
+                pos_of(name),
+                format!(
+                    "manifest_path `{}` of test `{}` does not exist",
+                    manifest, name
+                ),
+                None,
+            );
+        }
+
+        let selection = [("filter", &test.meta.filter), ("path", &test.meta.path)];
+        for (field, value) in selection {
+            if value.is_some() && !matches!(test.kind, TestKind::CargoTest { .. }) {
+                report.error(
+                    pos_of(name),
+                    format!(
+                        "`{}` of test `{}` only applies to cargo_test tests",
+                        field, name
+                    ),
+                    None,
+                );
+            }
+        }
+        if test.meta.filter.is_some() && test.meta.path.is_some() {
+            report.error(
+                pos_of(name),
+                format!("test `{}` sets both `filter` and `path`", name),
+                Some(
+                    "`path` runs exactly one test; `filter` every test whose path contains it"
+                        .into(),
+                ),
+            );
+        }
+
+        if let Err(e) = test.render_templates() {
+            report.error(
+                pos_of(name),
+                format!("{}: {}", lowercase_first(&e.to_string()), e.root_cause()),
+                Some("write `{{` and `}}` for literal braces".into()),
+            );
+        }
+    }
+
+    check_cargo_tests_exist(root, &config.tests, &pos_of, report)
+}
+
+/// Explain the step ids `build` will assign where they differ from a test's own slug, using the
+/// same rendered, graded tests and the same `runner_ids` as `build`
+fn check_step_ids(
+    config: &AutograderConfig,
+    pos_of: &dyn Fn(&str) -> Option<Pos>,
+    report: &mut Report,
+) {
+    // Template errors are reported per test below; fall back to the names as written
+    let rendered = apply_test_defaults(config.tests.clone(), &config.settings)
+        .unwrap_or_else(|_| config.tests.clone());
+    let (written, graded): (Vec<&AutoTest>, Vec<AutoTest>) = config
+        .tests
+        .iter()
+        .zip(rendered)
+        .filter(|(t, _)| t.meta.points > 0)
+        .unzip();
+    let (ids, _) = runner_ids(&graded);
+    let slugs: Vec<String> = graded.iter().map(step_slug).collect();
+
+    for (i, test) in graded.iter().enumerate() {
+        let name = written[i].meta.name.as_str();
+        if slug_id(test.stable_key()).is_empty() {
+            report.warning(
+                pos_of(name),
+                format!("test name `{}` has no letters or digits", test.stable_key()),
+                Some(format!(
+                    "the reporter identifies tests by the letters and digits of their names; `build` uses `{}`",
+                    ids[i]
+                )),
+            );
+        }
+        if ids[i] == slugs[i] {
+            continue;
+        }
+        let Some(j) = (0..i).find(|&j| slugs[j] == slugs[i]) else {
+            continue;
+        };
+        let other = written[j].meta.name.as_str();
+        // Exact duplicates are already an error
+        if other == name {
+            continue;
+        }
+        report.warning(
+            pos_of(name),
+            format!(
+                "tests `{}` and `{}` both become `{}` in the reporter's wiring",
+                graded[j].meta.name, test.meta.name, slugs[i]
+            ),
+            Some(format!(
+                "`build` gives `{}` the id `{}`; rename one of them to choose the ids yourself",
+                test.meta.name, ids[i]
+            )),
+        );
+    }
+}
+
+/// `cargo test <filter>` runs every test whose path contains the filter (`meta.filter`, `meta.path`
+/// or the name); one matching nothing grades as a pass with zero tests run. Skipped when the project has no Rust sources
+/// (e.g. tests copied in at grading time).
+fn check_cargo_tests_exist(
+    root: &Path,
+    tests: &[AutoTest],
+    pos_of: &dyn Fn(&str) -> Option<Pos>,
+    report: &mut Report,
+) -> Result<()> {
+    let files = collect_rs_files_with_manifest(root)
+        .with_context(|| format!("While scanning {}", root.to_string_lossy()))?;
+    if files.is_empty() {
+        return Ok(());
+    }
+    let scanned: Vec<String> = find_all_tests(&files)?
+        .into_iter()
+        .map(|t| t.test.name)
+        .collect();
+    let scanned_refs: Vec<&str> = scanned.iter().map(String::as_str).collect();
+
+    for test in tests {
+        if !matches!(test.kind, TestKind::CargoTest { .. }) {
+            continue;
+        }
+        let name = test.meta.name.as_str();
+        let selection = test.stable_key();
+        let last = selection.rsplit("::").next().unwrap_or(selection);
+        if !scanned.iter().any(|s| s.contains(last)) {
+            report.error(
+                pos_of(name),
+                format!(
+                    "no test function matching `{}` found in the project",
+                    selection
+                ),
+                suggest(last, &scanned_refs),
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,388 @@
+// A format-independent tree of the config file with line/column positions, so diagnostics can
+// point at the offending key. Only the shape and string scalars are kept; serde does the rest.
+use std::ops::Range;
+use toml_edit::{ImDocument, Item, Table, Value};
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust2::scanner::Marker;
+
+use crate::utils::config_format::ConfigFormat;
+
+/// 1-based line and column
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Pos {
+    pub line: usize,
+    pub col: usize,
+}
+
+#[derive(Debug)]
+pub struct Node {
+    pub pos: Pos,
+    pub value: NodeValue,
+}
+
+#[derive(Debug)]
+pub enum NodeValue {
+    Map(Vec<Entry>),
+    Seq(Vec<Node>),
+    Str(String),
+    Other,
+}
+
+#[derive(Debug)]
+pub struct Entry {
+    pub key: String,
+    pub key_pos: Pos,
+    pub node: Node,
+}
+
+impl Node {
+    fn new(pos: Pos, value: NodeValue) -> Self {
+        Self { pos, value }
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        match &self.value {
+            NodeValue::Map(entries) => entries,
+            _ => &[],
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Entry> {
+        self.entries().iter().find(|e| e.key == key)
+    }
+
+    pub fn items(&self) -> &[Node] {
+        match &self.value {
+            NodeValue::Seq(items) => items,
+            _ => &[],
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.value {
+            NodeValue::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+/// A syntax error, located where the parser gave up
+#[derive(Debug)]
+pub struct SyntaxError {
+    pub pos: Option<Pos>,
+    pub message: String,
+}
+
+pub fn parse(contents: &str, format: ConfigFormat) -> Result<Node, SyntaxError> {
+    match format {
+        ConfigFormat::Json => JsonParser::new(contents).document(),
+        ConfigFormat::Toml => parse_toml(contents),
+        ConfigFormat::Yaml => parse_yaml(contents),
+    }
+}
+
+// ---- JSON -------------------------------------------------------------------
+
+struct JsonParser<'a> {
+    src: &'a str,
+    at: usize,
+    pos: Pos,
+}
+
+impl<'a> JsonParser<'a> {
+    fn new(src: &'a str) -> Self {
+        Self {
+            src,
+            at: 0,
+            pos: Pos { line: 1, col: 1 },
+        }
+    }
+
+    fn document(mut self) -> Result<Node, SyntaxError> {
+        let node = self.value()?;
+        self.skip_ws();
+        if self.peek().is_some() {
+            return Err(self.error("trailing characters after the document"));
+        }
+        Ok(node)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.at..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.at += c.len_utf8();
+        if c == '\n' {
+            self.pos.line += 1;
+            self.pos.col = 1;
+        } else {
+            self.pos.col += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_ws(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    fn error(&self, message: &str) -> SyntaxError {
+        SyntaxError {
+            pos: Some(self.pos),
+            message: message.to_string(),
+        }
+    }
+
+    fn expect(&mut self, want: char) -> Result<(), SyntaxError> {
+        self.skip_ws();
+        match self.bump() {
+            Some(c) if c == want => Ok(()),
+            _ => Err(self.error(&format!("expected `{want}`"))),
+        }
+    }
+
+    fn value(&mut self) -> Result<Node, SyntaxError> {
+        self.skip_ws();
+        let pos = self.pos;
+        match self.peek() {
+            Some('{') => self.object(pos),
+            Some('[') => self.array(pos),
+            Some('"') => Ok(Node::new(pos, NodeValue::Str(self.string()?))),
+            Some(c) if c == '-' || c.is_ascii_alphanumeric() => {
+                while self
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+                {
+                    self.bump();
+                }
+                Ok(Node::new(pos, NodeValue::Other))
+            }
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, SyntaxError> {
+        let start = self.at;
+        self.bump(); // opening quote
+        loop {
+            match self.bump() {
+                Some('"') => break,
+                Some('\\') => {
+                    self.bump();
+                }
+                Some(_) => {}
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+        serde_json::from_str(&self.src[start..self.at]).map_err(|e| self.error(&e.to_string()))
+    }
+
+    fn object(&mut self, pos: Pos) -> Result<Node, SyntaxError> {
+        self.bump();
+        let mut entries = Vec::new();
+        self.skip_ws();
+        if self.peek() == Some('}') {
+            self.bump();
+            return Ok(Node::new(pos, NodeValue::Map(entries)));
+        }
+        loop {
+            self.skip_ws();
+            let key_pos = self.pos;
+            if self.peek() != Some('"') {
+                return Err(self.error("expected a quoted key"));
+            }
+            let key = self.string()?;
+            self.expect(':')?;
+            let node = self.value()?;
+            entries.push(Entry { key, key_pos, node });
+            self.skip_ws();
+            match self.bump() {
+                Some(',') => continue,
+                Some('}') => return Ok(Node::new(pos, NodeValue::Map(entries))),
+                _ => return Err(self.error("expected `,` or `}`")),
+            }
+        }
+    }
+
+    fn array(&mut self, pos: Pos) -> Result<Node, SyntaxError> {
+        self.bump();
+        let mut items = Vec::new();
+        self.skip_ws();
+        if self.peek() == Some(']') {
+            self.bump();
+            return Ok(Node::new(pos, NodeValue::Seq(items)));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_ws();
+            match self.bump() {
+                Some(',') => continue,
+                Some(']') => return Ok(Node::new(pos, NodeValue::Seq(items))),
+                _ => return Err(self.error("expected `,` or `]`")),
+            }
+        }
+    }
+}
+
+// ---- TOML -------------------------------------------------------------------
+
+/// Byte offset -> line/column, via the start offset of every line
+struct LineIndex<'a> {
+    src: &'a str,
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    fn new(src: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(src.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { src, starts }
+    }
+
+    fn pos(&self, span: Option<Range<usize>>) -> Pos {
+        let Some(span) = span else {
+            return Pos::default();
+        };
+        let line = self.starts.partition_point(|&s| s <= span.start);
+        let start = self.starts[line - 1];
+        let col = self.src[start..span.start].chars().count() + 1;
+        Pos { line, col }
+    }
+}
+
+fn parse_toml(contents: &str) -> Result<Node, SyntaxError> {
+    let index = LineIndex::new(contents);
+    let doc = ImDocument::parse(contents).map_err(|e| SyntaxError {
+        pos: e.span().map(|s| index.pos(Some(s))),
+        message: e.message().to_string(),
+    })?;
+    Ok(Node::new(
+        Pos { line: 1, col: 1 },
+        toml_table(&index, doc.as_table()),
+    ))
+}
+
+fn toml_table(index: &LineIndex, table: &Table) -> NodeValue {
+    let entries = table
+        .iter()
+        .map(|(key, item)| Entry {
+            key: key.to_string(),
+            key_pos: index.pos(table.key(key).and_then(|k| k.span())),
+            node: toml_item(index, item),
+        })
+        .collect();
+    NodeValue::Map(entries)
+}
+
+fn toml_item(index: &LineIndex, item: &Item) -> Node {
+    match item {
+        Item::Table(t) => Node::new(index.pos(t.span()), toml_table(index, t)),
+        Item::ArrayOfTables(a) => {
+            let items = a
+                .iter()
+                .map(|t| Node::new(index.pos(t.span()), toml_table(index, t)))
+                .collect();
+            Node::new(index.pos(a.span()), NodeValue::Seq(items))
+        }
+        Item::Value(v) => toml_value(index, v),
+        Item::None => Node::new(Pos::default(), NodeValue::Other),
+    }
+}
+
+fn toml_value(index: &LineIndex, value: &Value) -> Node {
+    let pos = index.pos(value.span());
+    match value {
+        Value::String(s) => Node::new(pos, NodeValue::Str(s.value().clone())),
+        Value::Array(a) => Node::new(
+            pos,
+            NodeValue::Seq(a.iter().map(|v| toml_value(index, v)).collect()),
+        ),
+        Value::InlineTable(t) => {
+            let entries = t
+                .iter()
+                .map(|(key, v)| Entry {
+                    key: key.to_string(),
+                    key_pos: index.pos(t.key(key).and_then(|k| k.span())),
+                    node: toml_value(index, v),
+                })
+                .collect();
+            Node::new(pos, NodeValue::Map(entries))
+        }
+        _ => Node::new(pos, NodeValue::Other),
+    }
+}
+
+// ---- YAML -------------------------------------------------------------------
+
+fn marker_pos(mark: &Marker) -> Pos {
+    Pos {
+        line: mark.line(),
+        col: mark.col() + 1,
+    }
+}
+
+/// Builds nodes from parser events; a mapping collects alternating key and value nodes
+#[derive(Default)]
+struct YamlBuilder {
+    stack: Vec<(Pos, bool, Vec<Node>)>,
+    root: Option<Node>,
+}
+
+impl YamlBuilder {
+    fn push(&mut self, node: Node) {
+        match self.stack.last_mut() {
+            Some((_, _, children)) => children.push(node),
+            None => self.root = Some(node),
+        }
+    }
+}
+
+impl MarkedEventReceiver for YamlBuilder {
+    fn on_event(&mut self, ev: Event, mark: Marker) {
+        let pos = marker_pos(&mark);
+        match ev {
+            Event::MappingStart(..) => self.stack.push((pos, true, Vec::new())),
+            Event::SequenceStart(..) => self.stack.push((pos, false, Vec::new())),
+            Event::MappingEnd | Event::SequenceEnd => {
+                let Some((pos, is_map, children)) = self.stack.pop() else {
+                    return;
+                };
+                let value = if is_map {
+                    let mut entries = Vec::new();
+                    let mut it = children.into_iter();
+                    while let (Some(k), Some(v)) = (it.next(), it.next()) {
+                        entries.push(Entry {
+                            key: k.as_str().unwrap_or_default().to_string(),
+                            key_pos: k.pos,
+                            node: v,
+                        });
+                    }
+                    NodeValue::Map(entries)
+                } else {
+                    NodeValue::Seq(children)
+                };
+                self.push(Node::new(pos, value));
+            }
+            Event::Scalar(s, ..) => self.push(Node::new(pos, NodeValue::Str(s))),
+            Event::Alias(_) => self.push(Node::new(pos, NodeValue::Other)),
+            _ => {}
+        }
+    }
+}
+
+fn parse_yaml(contents: &str) -> Result<Node, SyntaxError> {
+    let mut builder = YamlBuilder::default();
+    Parser::new_from_str(contents)
+        .load(&mut builder, false)
+        .map_err(|e| SyntaxError {
+            pos: Some(marker_pos(e.marker())),
+            message: e.info().to_string(),
+        })?;
+    Ok(builder
+        .root
+        .unwrap_or_else(|| Node::new(Pos { line: 1, col: 1 }, NodeValue::Map(Vec::new()))))
+}
@@ -0,0 +1,253 @@
+use super::*;
+
+fn project(config_name: &str, contents: &str) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join(".autograder")).unwrap();
+    fs::write(dir.path().join(".autograder").join(config_name), contents).unwrap();
+    dir
+}
+
+fn find<'a>(report: &'a Report, needle: &str) -> &'a Diagnostic {
+    report
+        .diagnostics
+        .iter()
+        .find(|d| d.message.contains(needle))
+        .unwrap_or_else(|| panic!("no diagnostic containing {needle:?}:\n{report}"))
+}
+
+fn at(line: usize, col: usize) -> Option<Pos> {
+    Some(Pos { line, col })
+}
+
+#[test]
+fn json_typos_point_at_the_key_with_suggestions() {
+    let dir = project(
+        "autograder.json",
+        r#"{
+  "version": 2,
+  "tests": [
+    {
+      "meta": { "name": "adds", "description": "", "pionts": 1 },
+      "type": "cargo_tset"
+    },
+    {
+      "meta": { "name": "lint", "description": "", "points": 1 },
+      "type": "clippy",
+      "min_commits": 3
+    }
+  ]
+}"#,
+    );
+    let report = check(dir.path()).unwrap();
+
+    let typo = find(&report, "unknown field `pionts`");
+    assert_eq!(typo.pos, at(5, 52));
+    assert_eq!(typo.help.as_deref(), Some("did you mean `points`?"));
+
+    let kind = find(&report, "unknown test type `cargo_tset`");
+    assert_eq!(kind.pos, at(6, 15));
+    assert_eq!(kind.help.as_deref(), Some("did you mean `cargo_test`?"));
+
+    let misplaced = find(&report, "unknown field `min_commits` in test `lint`");
+    assert_eq!(
+        misplaced.help.as_deref(),
+        Some("`min_commits` only applies to `commit_count` tests")
+    );
+    assert_eq!(report.errors(), 3);
+}
+
+#[test]
+fn toml_and_yaml_positions() {
+    let toml = project(
+        "autograder.toml",
+        "version = 2\n\n[[tests]]\nmeta = { name = \"adds\", description = \"\", points = 1 }\ntype = \"cargo_test\"\ntimout = 5\n",
+    );
+    let report = check(toml.path()).unwrap();
+    let d = find(&report, "unknown field `timout`");
+    assert_eq!(d.pos, at(6, 1));
+    assert_eq!(d.help.as_deref(), Some("did you mean `meta.timeout`?"));
+
+    let yaml = project(
+        "autograder.yaml",
+        "version: 2\nsettings:\n  toolchian: 1.82.0\ntests:\n- meta: { name: lint, description: '', points: 1 }\n  type: clippy\n",
+    );
+    let report = check(yaml.path()).unwrap();
+    let d = find(&report, "unknown field `toolchian` in settings");
+    assert_eq!(d.pos, at(3, 3));
+    assert_eq!(d.help.as_deref(), Some("did you mean `toolchain`?"));
+}
+
+#[test]
+fn syntax_errors_are_located() {
+    let dir = project("autograder.json", "{\n  \"tests\": [\n    {\n  ]\n}");
+    let report = check(dir.path()).unwrap();
+    let d = find(&report, "syntax error");
+    assert_eq!(d.pos.map(|p| p.line), Some(4));
+}
+
+#[test]
+fn checks_names_points_paths_and_sources() {
+    let dir = project(
+        "autograder.json",
+        r#"{
+  "tests": [
+    { "meta": { "name": "adds", "description": "", "points": 1 }, "type": "cargo_test" },
+    { "meta": { "name": "adds", "description": "", "points": 1 }, "type": "cargo_test" },
+    { "meta": { "name": "sub tracts", "description": "", "points": 1 }, "type": "cargo_test" },
+    { "meta": { "name": "sub-tracts", "description": "", "points": 1 }, "type": "command", "command": "true" },
+    { "meta": { "name": "mul", "description": "", "points": 1 }, "type": "cargo_test" },
+    { "meta": { "name": "ungraded", "description": "", "points": 0 }, "type": "command", "command": "true" },
+    { "meta": { "name": "lint", "description": "", "points": 1 }, "type": "clippy", "manifest_path": "calc/Cargo.toml" }
+  ]
+}"#,
+    );
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::write(
+        dir.path().join("Cargo.toml"),
+        "[package]\nname = \"calc\"\nversion = \"0.1.0\"\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("src/lib.rs"),
+        "#[test] fn adds() {}\n#[test] fn sub_tracts() {}\n#[test] fn muls() {}\n#[test] fn divs() {}\n",
+    )
+    .unwrap();
+    let report = check(dir.path()).unwrap();
+
+    assert_eq!(find(&report, "duplicate test name `adds`").pos, at(4, 25));
+    let shared = find(
+        &report,
+        "`sub tracts` and `sub-tracts` both become `sub_tracts`",
+    );
+    assert_eq!(shared.severity, Severity::Warning);
+    let zero = find(&report, "`ungraded` has 0 points");
+    assert_eq!(zero.severity, Severity::Warning);
+    find(
+        &report,
+        "manifest_path `calc/Cargo.toml` of test `lint` does not exist",
+    );
+    let missing = find(&report, "no test function matching `sub tracts`");
+    assert_eq!(missing.help.as_deref(), Some("did you mean `sub_tracts`?"));
+    // `cargo test mul` also runs `muls`, so `mul` is found
+    assert!(!format!("{report}").contains("matching `mul`"), "{report}");
+    assert_eq!(report.errors(), 3);
+}
+
+#[test]
+fn build_refuses_an_invalid_config() {
+    let dir = project(
+        "autograder.json",
+        r#"{ "tests": [ { "meta": { "name": "a", "description": "", "points": 1 }, "type": "cargo_test", "manifest_path": "nope/Cargo.toml" } ] }"#,
+    );
+    let cfg = crate::cli::BuildConfig {
+        root: dir.path().to_path_buf(),
+        ..Default::default()
+    };
+    let err = format!("{:#}", crate::cli::build::run(&cfg).unwrap_err());
+    assert!(err.contains("Not building"), "{err}");
+    assert!(!dir.path().join(".github").exists());
+}
+
+#[test]
+fn placeholders_must_exist_for_the_test_type() {
+    let dir = project(
+        "autograder.json",
+        r#"{
+  "version": 2,
+  "tests": [
+    { "meta": { "name": "COMMITS_{min_commits}", "description": "At least {min_commits}, worth {points}", "points": 1 }, "type": "commit_count", "min_commits": 3 },
+    { "meta": { "name": "lint", "description": "Needs {min_tests} tests", "points": 1 }, "type": "clippy" }
+  ]
+}"#,
+    );
+    let report = check(dir.path()).unwrap();
+
+    let d = find(&report, "in the description of test `lint`");
+    assert!(
+        d.message
+            .contains("`{min_tests}` isn't available for clippy tests"),
+        "{}",
+        d.message
+    );
+    assert_eq!(d.pos, at(5, 25));
+    assert_eq!(report.errors(), 1, "{report}");
+}
+
+#[test]
+fn filter_and_path_only_apply_to_one_cargo_test_selection() {
+    let dir = project(
+        "autograder.json",
+        r#"{
+  "version": 2,
+  "tests": [
+    { "meta": { "name": "lint", "filter": "x", "description": "", "points": 1 }, "type": "clippy" },
+    { "meta": { "name": "adds", "filter": "add", "path": "tests::adds", "description": "", "points": 1 }, "type": "cargo_test" },
+    { "meta": { "name": "subtracts", "display_name": "Subtraction", "path": "tests::subtracts", "description": "", "points": 1 }, "type": "cargo_test" }
+  ]
+}"#,
+    );
+    let report = check(dir.path()).unwrap();
+
+    assert_eq!(find(&report, "`filter` of test `lint`").pos, at(4, 25));
+    find(&report, "test `adds` sets both `filter` and `path`");
+    assert_eq!(report.errors(), 2, "{report}");
+}
+
+#[test]
+fn step_id_hints_match_the_ids_build_assigns() {
+    let dir = project(
+        "autograder.json",
+        r#"{
+  "version": 2,
+  "tests": [
+    { "meta": { "name": "test_a", "description": "", "points": 1 }, "type": "command", "command": "true" },
+    { "meta": { "name": "test-a", "description": "", "points": 1 }, "type": "command", "command": "true" },
+    { "meta": { "name": "test a 2", "description": "", "points": 1 }, "type": "command", "command": "true" },
+    { "meta": { "name": "!!!", "description": "", "points": 1 }, "type": "command", "command": "true" }
+  ]
+}"#,
+    );
+    let report = check(dir.path()).unwrap();
+
+    let shared = find(&report, "`test_a` and `test-a` both become `test_a`");
+    assert_eq!(
+        shared.help.as_deref(),
+        Some(
+            "`build` gives `test-a` the id `test_a_3`; rename one of them to choose the ids yourself"
+        )
+    );
+    // `build` names it `test`, so it's not an error
+    let empty = find(&report, "test name `!!!` has no letters or digits");
+    assert_eq!(empty.severity, Severity::Warning);
+    assert!(
+        empty
+            .help
+            .as_deref()
+            .unwrap()
+            .ends_with("`build` uses `test`")
+    );
+    assert_eq!(report.errors(), 0, "{report}");
+}
+
+#[test]
+fn build_output_does_not_count_as_a_test_source() {
+    let dir = project(
+        "autograder.json",
+        r#"{ "version": 2, "tests": [
+    { "meta": { "name": "gone", "description": "", "points": 1 }, "type": "cargo_test" }
+] }"#,
+    );
+    let root = dir.path();
+    fs::write(root.join("Cargo.toml"), "[package]\nname = \"p\"\n").unwrap();
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(root.join("src/lib.rs"), "#[test] fn kept() {}\n").unwrap();
+    fs::create_dir_all(root.join("target/debug/build/p/out")).unwrap();
+    fs::write(
+        root.join("target/debug/build/p/out/stale.rs"),
+        "#[test] fn gone() {}\n",
+    )
+    .unwrap();
+
+    let report = check(root).unwrap();
+    find(&report, "no test function matching `gone`");
+}
@@ -0,0 +1,220 @@
+// GitHub Classroom's legacy `.github/classroom/autograding.json`, as written by the web UI.
+use super::{AutoTest, Comparison, TestKind, TestMeta, TestOptions};
+use crate::utils::scripts::SCRIPT_NAMES;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClassroomConfig {
+    pub tests: Vec<ClassroomTest>,
+}
+
+/// One UI-configured test. `output` is only used by input/output tests.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassroomTest {
+    pub name: String,
+    #[serde(default)]
+    pub setup: String,
+    pub run: String,
+    #[serde(default)]
+    pub input: String,
+    #[serde(default)]
+    pub output: String,
+    #[serde(default)]
+    pub comparison: Comparison,
+    /// Minutes
+    #[serde(default = "default_timeout")]
+    pub timeout: u64,
+    /// The UI writes `null` for tests that aren't scored
+    #[serde(default)]
+    pub points: Option<u32>,
+}
+
+fn default_timeout() -> u64 {
+    10
+}
+
+impl ClassroomTest {
+    /// Map onto the matching `TestKind`, falling back to a plain `command` test
+    pub fn to_auto_test(&self) -> AutoTest {
+        let name = self.name.trim().to_string();
+        let mut meta = TestMeta {
+            name: name.clone(),
+            description: String::new(),
+            points: self.points.unwrap_or(0),
+            timeout: self.timeout,
+            ..Default::default()
+        };
+
+        let (kind, mut options) = if !self.output.is_empty() || !self.input.is_empty() {
+            let kind = TestKind::Io {
+                command: self.run.trim().to_string(),
+                input: self.input.clone(),
+                expected_output: self.output.clone(),
+                comparison: self.comparison,
+            };
+            (kind, TestOptions::default())
+        } else {
+            match parse_run(&self.run) {
+                Some((kind, Some(selection), options)) => {
+                    // The filter identifies the test; the UI label is what students see
+                    if selection.filter != name {
+                        meta.display_name = Some(name);
+                    }
+                    if selection.exact {
+                        meta.path = Some(selection.filter.clone());
+                    }
+                    meta.name = selection.filter;
+                    (kind, options)
+                }
+                Some((kind, None, options)) => (kind, options),
+                None => {
+                    let kind = TestKind::Command {
+                        command: self.run.trim().to_string(),
+                    };
+                    (kind, TestOptions::default())
+                }
+            }
+        };
+
+        if !self.setup.trim().is_empty() {
+            options.setup = Some(self.setup.trim().to_string());
+        }
+        AutoTest {
+            meta,
+            kind,
+            options,
+        }
+    }
+
+    /// The reverse mapping; `run` is the test's fully resolved shell command
+    pub fn from_auto_test(test: &AutoTest, run: String) -> Self {
+        let (run, input, output, comparison) = match &test.kind {
+            TestKind::Io {
+                command,
+                input,
+                expected_output,
+                comparison,
+            } => (
+                command.clone(),
+                input.clone(),
+                expected_output.clone(),
+                *comparison,
+            ),
+            _ => (run, String::new(), String::new(), Comparison::Included),
+        };
+        Self {
+            name: test.display_name().to_string(),
+            setup: test.options.setup.clone().unwrap_or_default(),
+            run,
+            input,
+            output,
+            comparison,
+            timeout: test.meta.timeout,
+            points: Some(test.meta.points),
+        }
+    }
+}
+
+/// What a `cargo test` command selects: every test containing `filter`, or with `--exact` the one
+/// test at that path
+#[derive(Debug, PartialEq, Eq)]
+pub struct Selection {
+    pub filter: String,
+    pub exact: bool,
+}
+
+/// Recognize the commands our own command makers produce.
+///
+/// Returns the kind, the `cargo test` selection (used as the test name) and any cargo options.
+/// Anything with shell syntax or unknown flags is left for a `command` test.
+pub fn parse_run(run: &str) -> Option<(TestKind, Option<Selection>, TestOptions)> {
+    if let Some(parsed) = parse_test_count(run) {
+        return Some(parsed);
+    }
+    if run.contains(|c| "|&;<>$`'\"(){}\\".contains(c)) {
+        return None;
+    }
+    let tokens: Vec<&str> = run.split_whitespace().collect();
+    match tokens.as_slice() {
+        ["cargo", "test", filter, rest @ ..] if !filter.starts_with('-') => {
+            let (cargo, harness) = match rest.iter().position(|t| *t == "--") {
+                Some(i) => (&rest[..i], &rest[i + 1..]),
+                None => (rest, &[][..]),
+            };
+            let (manifest_path, mut options) = parse_cargo_flags(cargo)?;
+            let mut exact = false;
+            for flag in harness {
+                match flag.strip_prefix("--test-threads=") {
+                    Some(n) => options.test_threads = Some(n.parse().ok()?),
+                    None if *flag == "--exact" => exact = true,
+                    None => return None,
+                }
+            }
+            let selection = Selection {
+                filter: filter.to_string(),
+                exact,
+            };
+            Some((
+                TestKind::CargoTest { manifest_path },
+                Some(selection),
+                options,
+            ))
+        }
+        ["cargo", "clippy", rest @ .., "--", "-D", "warnings"] => {
+            let (manifest_path, options) = parse_cargo_flags(rest)?;
+            Some((TestKind::Clippy { manifest_path }, None, options))
+        }
+        ["bash", script, n] => {
+            let script = script
+                .trim_start_matches("./")
+                .strip_prefix(".autograder/")?;
+            let n: u32 = n.parse().ok()?;
+            let kind = if script == SCRIPT_NAMES.commit_count {
+                TestKind::CommitCount { min_commits: n }
+            } else if script == SCRIPT_NAMES.branch_count {
+                TestKind::BranchCount { min_branches: n }
+            } else {
+                return None;
+            };
+            Some((kind, None, TestOptions::default()))
+        }
+        _ => None,
+    }
+}
+
+/// The `cargo test -- --list | ... awk` pipeline from `test_count_cmd`, with `##` filled or not
+fn parse_test_count(run: &str) -> Option<(TestKind, Option<Selection>, TestOptions)> {
+    let (cargo, check) = run.split_once(" -- --list | tail -1 | awk ")?;
+    let tokens: Vec<&str> = cargo.split_whitespace().collect();
+    let ["cargo", "test", flags @ ..] = tokens.as_slice() else {
+        return None;
+    };
+    let (manifest_path, options) = parse_cargo_flags(flags)?;
+    let (_, min) = check.split_once("if ($1 < ")?;
+    let (min, _) = min.split_once('+')?;
+    let kind = TestKind::TestCount {
+        min_tests: min.trim().parse().ok()?,
+        manifest_path,
+    };
+    Some((kind, None, options))
+}
+
+/// `--manifest-path`, `--release`, `--profile`, `--features` and `--test`; None on anything else
+fn parse_cargo_flags(tokens: &[&str]) -> Option<(Option<String>, TestOptions)> {
+    let mut manifest_path = None;
+    let mut options = TestOptions::default();
+    let mut it = tokens.iter();
+    while let Some(flag) = it.next() {
+        match *flag {
+            "--release" => options.profile = Some("release".into()),
+            "--manifest-path" => manifest_path = Some(it.next()?.to_string()),
+            "--profile" => options.profile = Some(it.next()?.to_string()),
+            "--test" => options.target = Some(it.next()?.to_string()),
+            "--features" => {
+                options.features = it.next()?.split(',').map(str::to_string).collect();
+            }
+            _ => return None,
+        }
+    }
+    Some((manifest_path, options))
+}
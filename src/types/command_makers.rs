@@ -1,5 +1,7 @@
 // Helper functions for the `.command()` method on `AutoTest`
+use super::{Comparison, TestOptions};
 use crate::utils::scripts::SCRIPT_NAMES;
+use crate::utils::shell_quote;
 
 fn is_root_manifest(p: &str) -> bool {
     p.is_empty() || p == "Cargo.toml" || p == "."
@@ -12,30 +14,78 @@ fn manifest_flag(mp: Option<&str>) -> Option<String> {
     }
 }
 
-pub fn cargo_test_cmd(function: &str, mp: Option<&str>) -> String {
-    // TODO: Reimplement let exact = format!("{} -- --exact", function.trim());
-    match manifest_flag(mp) {
-        Some(flag) => format!("cargo test {} {}", function.trim(), flag),
-        None => format!("cargo test {}", function.trim()),
+/// Profile, feature and target flags shared by every cargo invocation
+fn build_flags(opts: &TestOptions) -> Vec<String> {
+    let mut flags = Vec::new();
+    match opts.profile.as_deref().map(str::trim) {
+        None | Some("") | Some("dev") | Some("debug") => {}
+        Some("release") => flags.push("--release".to_string()),
+        Some(p) => flags.push(format!("--profile {}", p)),
     }
+    if !opts.features.is_empty() {
+        flags.push(format!("--features {}", opts.features.join(",")));
+    }
+    flags
 }
 
-pub fn clippy_cmd(mp: Option<&str>) -> String {
-    match manifest_flag(mp) {
-        Some(flag) => format!("cargo clippy {} -- -D warnings", flag),
-        None => "cargo clippy -- -D warnings".to_string(),
+/// Flags for `cargo test` runs: the build flags plus the `--test` target and extra arguments, which
+/// only `cargo test` understands
+fn cargo_flags(opts: &TestOptions) -> Vec<String> {
+    let mut flags = build_flags(opts);
+    if let Some(t) = &opts.target {
+        flags.push(format!("--test {}", t.trim()));
+    }
+    flags.extend(opts.extra_args.iter().cloned());
+    flags
+}
+
+pub fn cargo_test_cmd(function: &str, mp: Option<&str>, opts: &TestOptions) -> String {
+    cargo_test_parts(function, mp, opts, &[]).join(" ")
+}
+
+/// `cargo test` for exactly one test, by its full path (e.g. `parser::tests::negative`)
+pub fn cargo_test_exact_cmd(path: &str, mp: Option<&str>, opts: &TestOptions) -> String {
+    cargo_test_parts(path, mp, opts, &["--exact".to_string()]).join(" ")
+}
+
+fn cargo_test_parts(
+    filter: &str,
+    mp: Option<&str>,
+    opts: &TestOptions,
+    harness: &[String],
+) -> Vec<String> {
+    let mut parts = vec!["cargo test".to_string(), filter.trim().to_string()];
+    parts.extend(manifest_flag(mp));
+    parts.extend(cargo_flags(opts));
+    let mut harness = harness.to_vec();
+    if let Some(n) = opts.test_threads {
+        harness.push(format!("--test-threads={}", n));
+    }
+    if !harness.is_empty() {
+        parts.push(format!("-- {}", harness.join(" ")));
     }
+    parts
+}
+
+pub fn clippy_cmd(mp: Option<&str>, opts: &TestOptions) -> String {
+    let mut parts = vec!["cargo clippy".to_string()];
+    parts.extend(manifest_flag(mp));
+    // Lint everything: a `--test` target or test-only arguments would narrow or break the check
+    parts.extend(build_flags(opts));
+    parts.push("-- -D warnings".to_string());
+    parts.join(" ")
 }
 
 // Uses your existing “+##” placeholder convention so you can inject framework baseline later.
-pub fn test_count_cmd(min: u32, mp: Option<&str>) -> String {
-    let base = match manifest_flag(mp) {
-        Some(flag) => format!("cargo test {} -- --list", flag),
-        None => "cargo test -- --list".to_string(),
-    };
+pub fn test_count_cmd(min: u32, mp: Option<&str>, opts: &TestOptions) -> String {
+    // Only profile/features apply: a --test target would undercount the student's tests
+    let mut parts = vec!["cargo test".to_string()];
+    parts.extend(manifest_flag(mp));
+    parts.extend(build_flags(opts));
+    parts.push("-- --list".to_string());
     format!(
         r#"{base} | tail -1 | awk '{{print $1}}' | awk '{{if ($1 < {min}+##) {{print "Too few tests ("$1-##") expected {min}"; exit 1}}}}'"#,
-        base = base,
+        base = parts.join(" "),
         min = min
     )
 }
@@ -53,3 +103,19 @@ pub fn branch_count_cmd(min_branches: &u32) -> String {
         SCRIPT_NAMES.branch_count, min_branches,
     )
 }
+
+/// Self-contained bash check for an `io` test, for runners without Classroom's IO grader
+pub fn io_check_cmd(command: &str, input: &str, expected: &str, comparison: Comparison) -> String {
+    let test = match comparison {
+        Comparison::Included => r#"[[ "$out" == *"$expected"* ]]"#,
+        Comparison::Exact => r#"[[ "$out" == "$expected" ]]"#,
+        Comparison::Regex => r#"[[ "$out" =~ $expected ]]"#,
+    };
+    format!(
+        "out=$(printf '%s' {} | {{ {}; }}); expected={}; {}",
+        shell_quote(input),
+        command,
+        shell_quote(expected),
+        test
+    )
+}
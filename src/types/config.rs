@@ -0,0 +1,233 @@
+use super::{AutoTest, TestOptions};
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// Schema version written by this release. Version 1 is the legacy bare array of tests.
+pub const CONFIG_VERSION: u32 = 2;
+
+/// Assignment-wide settings stored alongside the tests in autograder.json
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Settings {
+    /// Rust toolchain used for grading (e.g. "1.82.0", "stable", "nightly-2024-05-01")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toolchain: Option<String>,
+
+    /// Minutes for tests that leave `meta.timeout` unset (or 0); 10 when not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_timeout: Option<u64>,
+
+    /// Expected sum of all test points; `build` warns when the tests add up to something else
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_points: Option<u32>,
+
+    /// Events that start a grading run, on top of `repository_dispatch`
+    #[serde(default, skip_serializing_if = "Triggers::is_empty")]
+    pub triggers: Triggers,
+
+    /// Emit third-party actions pinned to a commit SHA instead of a mutable tag
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pin_actions: bool,
+
+    /// Organization that mirrors the third-party actions (replaces each action's owner)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action_mirror: Option<String>,
+
+    /// Mirror repository name per upstream `owner/repo`, for mirrors not named like the upstream
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub action_mirror_names: BTreeMap<String, String>,
+
+    /// Extra `owner/repo@tag` -> commit SHA pins, on top of the bundled lock table
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub action_pins: BTreeMap<String, String>,
+
+    /// Env and cargo options applied to every test that doesn't set its own
+    #[serde(default, skip_serializing_if = "TestOptions::is_empty")]
+    pub defaults: TestOptions,
+
+    /// Language of generated descriptions, table headers and workflow step names ("en", "es")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+
+    /// Translations file (JSON, TOML or YAML) overriding the catalog, relative to the project root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translations: Option<String>,
+
+    /// Name templates for the checks `init` generates, by type (`clippy`, `commit_count`,
+    /// `branch_count`, `test_count`), e.g. "Style: {member}"
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub name_templates: BTreeMap<String, String>,
+}
+
+/// Workflow triggers (`on:`) and the concurrency policy for the generated workflow
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct Triggers {
+    /// Grade on push (implied by `push_branches` / `push_paths`)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub grade_on_push: bool,
+    /// Allow manual runs from the Actions tab
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub workflow_dispatch: bool,
+    /// Cron expressions, e.g. "0 3 * * *" for nightly grading
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub schedule: Vec<String>,
+    /// Only grade pushes to these branches
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub push_branches: Vec<String>,
+    /// Only grade pushes touching these paths, e.g. "src/**"
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub push_paths: Vec<String>,
+    /// Cancel an in-progress grading run when a newer one starts on the same ref
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub cancel_superseded: bool,
+}
+
+impl Triggers {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn on_push(&self) -> bool {
+        self.grade_on_push || !self.push_branches.is_empty() || !self.push_paths.is_empty()
+    }
+
+    /// Overlay `other` (e.g. CLI flags) on top of `self`: switches are OR-ed, non-empty lists replace
+    pub fn merged_with(&self, other: &Triggers) -> Triggers {
+        fn pick(base: &[String], over: &[String]) -> Vec<String> {
+            if over.is_empty() { base } else { over }.to_vec()
+        }
+        Triggers {
+            grade_on_push: self.grade_on_push || other.grade_on_push,
+            workflow_dispatch: self.workflow_dispatch || other.workflow_dispatch,
+            schedule: pick(&self.schedule, &other.schedule),
+            push_branches: pick(&self.push_branches, &other.push_branches),
+            push_paths: pick(&self.push_paths, &other.push_paths),
+            cancel_superseded: self.cancel_superseded || other.cancel_superseded,
+        }
+    }
+
+    /// Simple (list-style) `on:` is enough when nothing needs filters or extra keys
+    pub fn is_simple(&self) -> bool {
+        !self.workflow_dispatch
+            && self.schedule.is_empty()
+            && self.push_branches.is_empty()
+            && self.push_paths.is_empty()
+    }
+}
+
+/// The full contents of autograder.json: a schema version, settings and the list of tests.
+///
+/// Older configs are a bare array of tests (version 1); those are read with default settings.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AutograderConfig {
+    /// Editor schema reference (see `autograder-setup schema`); ignored by the tool itself
+    #[serde(rename = "$schema", default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    /// Missing in configs written before versioning, which already had this layout
+    #[serde(default = "current_version")]
+    pub version: u32,
+    /// Base configs merged underneath this one: one path or a list, relative to the project root
+    /// (or, inside a base, to that base)
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "one_or_many",
+        serialize_with = "one_or_many_out"
+    )]
+    #[schemars(with = "OneOrMany")]
+    pub extends: Vec<String>,
+    #[serde(default)]
+    pub settings: Settings,
+    /// May be empty in a config that only `extends` others
+    #[serde(default)]
+    pub tests: Vec<AutoTest>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+fn one_or_many<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<String>, D::Error> {
+    Ok(match OneOrMany::deserialize(d)? {
+        OneOrMany::One(s) => vec![s],
+        OneOrMany::Many(v) => v,
+    })
+}
+
+/// A single base is written back as a plain string, as it is usually written by hand
+fn one_or_many_out<S: Serializer>(v: &[String], s: S) -> Result<S::Ok, S::Error> {
+    match v {
+        [one] => s.serialize_str(one),
+        many => many.serialize(s),
+    }
+}
+
+fn current_version() -> u32 {
+    CONFIG_VERSION
+}
+
+impl Default for AutograderConfig {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl AutograderConfig {
+    pub fn new(tests: Vec<AutoTest>) -> Self {
+        Self {
+            schema: None,
+            version: CONFIG_VERSION,
+            extends: Vec::new(),
+            settings: Settings::default(),
+            tests,
+        }
+    }
+
+    /// Overlay `local` on this (base) config: tests with the same name are replaced in place, new
+    /// ones are appended, and settings set in `local` win key by key.
+    pub fn merged_with(&self, local: &AutograderConfig) -> serde_json::Result<AutograderConfig> {
+        let mut tests = self.tests.clone();
+        for test in &local.tests {
+            match tests.iter_mut().find(|t| t.meta.name == test.meta.name) {
+                Some(slot) => *slot = test.clone(),
+                None => tests.push(test.clone()),
+            }
+        }
+
+        let mut settings = serde_json::to_value(&self.settings)?;
+        merge_values(&mut settings, serde_json::to_value(&local.settings)?);
+        Ok(AutograderConfig {
+            schema: local.schema.clone(),
+            version: CONFIG_VERSION,
+            extends: Vec::new(),
+            settings: serde_json::from_value(settings)?,
+            tests,
+        })
+    }
+
+    /// Sum of the points of every test
+    pub fn total_points(&self) -> u32 {
+        self.tests.iter().map(|t| t.meta.points).sum()
+    }
+}
+
+/// Objects merge recursively; anything else in `over` replaces `base`
+fn merge_values(base: &mut serde_json::Value, over: serde_json::Value) {
+    match (base, over) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(over)) => {
+            for (k, v) in over {
+                match base.get_mut(&k) {
+                    Some(slot) => merge_values(slot, v),
+                    None => {
+                        base.insert(k, v);
+                    }
+                }
+            }
+        }
+        (base, over) => *base = over,
+    }
+}
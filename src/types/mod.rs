@@ -1,20 +1,50 @@
+pub mod classroom;
 pub mod command_makers;
+pub mod config;
+pub mod template;
 
-use crate::utils::replace_double_hashtag;
+use crate::utils::{markdown_escape, replace_double_hashtag};
 use command_makers::*;
-use markdown_tables::MarkdownTableRow;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Common, always present bits
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct TestMeta {
+    /// Identifies the test; for `cargo_test` also the `cargo test` filter unless `filter`/`path`
+    /// is set
     pub name: String,
+    /// What students see instead of `name` (workflow step, table, rubric)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// `cargo_test` only: the `cargo test` filter, when it isn't `name`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    /// `cargo_test` only: the test's full path (e.g. `parser::tests::negative`), run with `--exact`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
     pub description: String,
     pub points: u32,
+    /// Minutes; 0 (or missing) falls back to `settings.default_timeout`
+    #[serde(default)]
     pub timeout: u64,
+    /// Optional grouping label (e.g. "part-1"), used to keep related tests in one grading job
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Where `init` found the test function; used by `table --link-source`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<SourceLocation>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A file (unix-style, relative to the project root) and 1-based line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum TestKind {
     CargoTest {
@@ -36,66 +66,281 @@ pub enum TestKind {
     BranchCount {
         min_branches: u32,
     },
+    /// Any shell command, graded on its exit status
+    Command {
+        command: String,
+    },
+    /// Feeds `input` to `command` on stdin and compares its stdout with `expected_output`
+    Io {
+        command: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        input: String,
+        expected_output: String,
+        #[serde(default)]
+        comparison: Comparison,
+    },
+}
+
+/// How an `io` test's output is matched, as in GitHub Classroom's input/output tests
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    /// The output contains the expected text
+    #[default]
+    Included,
+    /// The output equals the expected text (trailing newlines ignored)
+    Exact,
+    /// The output matches the expected text as a regular expression
+    Regex,
+}
+
+impl TestKind {
+    /// The `type` tag as written in the config
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            TestKind::CargoTest { .. } => "cargo_test",
+            TestKind::Clippy { .. } => "clippy",
+            TestKind::CommitCount { .. } => "commit_count",
+            TestKind::TestCount { .. } => "test_count",
+            TestKind::BranchCount { .. } => "branch_count",
+            TestKind::Command { .. } => "command",
+            TestKind::Io { .. } => "io",
+        }
+    }
+}
+
+impl Comparison {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Comparison::Included => "included",
+            Comparison::Exact => "exact",
+            Comparison::Regex => "regex",
+        }
+    }
+}
+
+/// Optional cargo invocation and environment tweaks, shared by every cargo-based kind
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct TestOptions {
+    /// Extra environment for the step, e.g. `RUST_BACKTRACE=1`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Cargo features to enable (`--features a,b`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    /// Build profile: `release` -> `--release`, anything else but `dev` -> `--profile <name>`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Test harness threads (`-- --test-threads=N`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test_threads: Option<u32>,
+    /// A specific integration test target (`--test <target>`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Anything else to pass to cargo, before `--`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extra_args: Vec<String>,
+    /// Ungraded command run before the test, e.g. installing a tool
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub setup: Option<String>,
+}
+
+impl TestOptions {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Fill unset fields from config-level `defaults`; per-test values always win
+    pub fn with_defaults(&self, defaults: &TestOptions) -> TestOptions {
+        fn pick(own: &[String], base: &[String]) -> Vec<String> {
+            if own.is_empty() { base } else { own }.to_vec()
+        }
+        let mut env = defaults.env.clone();
+        env.extend(self.env.clone());
+        TestOptions {
+            env,
+            features: pick(&self.features, &defaults.features),
+            profile: self.profile.clone().or_else(|| defaults.profile.clone()),
+            test_threads: self.test_threads.or(defaults.test_threads),
+            target: self.target.clone().or_else(|| defaults.target.clone()),
+            extra_args: pick(&self.extra_args, &defaults.extra_args),
+            setup: self.setup.clone().or_else(|| defaults.setup.clone()),
+        }
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// One graded test: `meta`, a `type` with its fields, and optional cargo and env options
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct AutoTest {
     pub meta: TestMeta,
     #[serde(flatten)]
     pub kind: TestKind,
+    #[serde(flatten)]
+    pub options: TestOptions,
 }
 
-// tiny esc to keep tables from breaking if  | or \ are used
-fn esc(s: &str) -> String {
-    s.replace('\\', r"\\").replace('|', r"\|")
-}
-
-/// Shared writing pattern between commit count and test count
-fn mk_description(desc: &str, min: u32) -> String {
-    esc(&replace_double_hashtag(desc, min))
-}
 impl AutoTest {
-    /// Fill tokens like {min_commits}, {min_tests}, {manifest_path}, {function}
-    /// Made public for testing
+    /// `plain_description` escaped for a Markdown table cell. `{placeholder}` templates are not
+    /// filled in here; `render_templates` does that before a test reaches any output.
     pub fn resolved_description(&self) -> String {
+        markdown_escape(&self.plain_description())
+    }
+
+    /// The description with `##` filled in, without Markdown table escaping
+    pub fn plain_description(&self) -> String {
         match &self.kind {
             TestKind::CommitCount { min_commits } => {
-                mk_description(&self.meta.description, *min_commits)
+                replace_double_hashtag(&self.meta.description, *min_commits)
             }
             TestKind::TestCount { min_tests, .. } => {
-                mk_description(&self.meta.description, *min_tests)
+                replace_double_hashtag(&self.meta.description, *min_tests)
+            }
+            TestKind::BranchCount { min_branches } => {
+                replace_double_hashtag(&self.meta.description, *min_branches)
             }
-            _ => esc(&self.meta.description),
+            _ => self.meta.description.clone(),
         }
     }
 
-    pub fn command(&self) -> String {
+    /// Placeholder values for name and description templates: every field of the test's kind,
+    /// plus `{points}` and `{timeout}` (`{name}` is added for descriptions)
+    pub fn template_vars(&self) -> Vec<(&'static str, String)> {
+        let mut vars = vec![
+            ("points", self.meta.points.to_string()),
+            ("timeout", self.meta.timeout.to_string()),
+        ];
+        // A cargo step without its own manifest runs against the root one
+        let manifest = |p: &Option<String>| p.clone().unwrap_or_else(|| "Cargo.toml".into());
         match &self.kind {
-            TestKind::CargoTest { manifest_path } => {
-                cargo_test_cmd(&self.meta.name, manifest_path.as_deref())
+            TestKind::CargoTest { manifest_path } | TestKind::Clippy { manifest_path } => {
+                vars.push(("manifest_path", manifest(manifest_path)));
+            }
+            TestKind::CommitCount { min_commits } => {
+                vars.push(("min_commits", min_commits.to_string()));
             }
-            TestKind::Clippy { manifest_path } => clippy_cmd(manifest_path.as_deref()),
-            TestKind::CommitCount { min_commits } => commit_count_cmd(min_commits),
             TestKind::TestCount {
                 min_tests,
                 manifest_path,
-            } => test_count_cmd(*min_tests, manifest_path.as_deref()),
-            TestKind::BranchCount { min_branches } => branch_count_cmd(min_branches),
+            } => {
+                vars.push(("min_tests", min_tests.to_string()));
+                vars.push(("manifest_path", manifest(manifest_path)));
+            }
+            TestKind::BranchCount { min_branches } => {
+                vars.push(("min_branches", min_branches.to_string()));
+            }
+            TestKind::Command { command } => vars.push(("command", command.clone())),
+            TestKind::Io {
+                command,
+                input,
+                expected_output,
+                comparison,
+            } => {
+                vars.push(("command", command.clone()));
+                vars.push(("input", input.clone()));
+                vars.push(("expected_output", expected_output.clone()));
+                vars.push(("comparison", comparison.as_str().to_string()));
+            }
         }
+        vars
+    }
+
+    /// The test with its name, display name and description templates filled in. The display name
+    /// gets `{name}`, the description `{name}` as students see it, and the older `##` for the
+    /// threshold.
+    pub fn render_templates(&self) -> anyhow::Result<AutoTest> {
+        use anyhow::Context;
+        let kind = self.kind.type_name();
+        let mut vars = self.template_vars();
+        let name = template::render(&self.meta.name, &vars, kind)
+            .with_context(|| format!("In the name of test `{}`", self.meta.name))?;
+        vars.push(("name", name.clone()));
+        let display_name = match &self.meta.display_name {
+            Some(d) => Some(
+                template::render(d, &vars, kind)
+                    .with_context(|| format!("In the display name of test `{}`", self.meta.name))?,
+            ),
+            None => None,
+        };
+        if let Some(d) = &display_name {
+            vars.retain(|(v, _)| *v != "name");
+            vars.push(("name", d.clone()));
+        }
+        let description = template::render(&self.plain_description(), &vars, kind)
+            .with_context(|| format!("In the description of test `{}`", self.meta.name))?;
+
+        let mut rendered = self.clone();
+        rendered.meta.name = name;
+        rendered.meta.display_name = display_name;
+        rendered.meta.description = description;
+        Ok(rendered)
+    }
+
+    /// What students see: `display_name`, else `name`
+    pub fn display_name(&self) -> &str {
+        self.meta
+            .display_name
+            .as_deref()
+            .unwrap_or(&self.meta.name)
+            .trim()
     }
-}
 
-impl MarkdownTableRow for AutoTest {
-    fn column_names() -> Vec<&'static str> {
-        vec!["Name", "Points", "Description"]
+    /// What the workflow step id and the reporter's wiring are derived from. For `cargo_test` it's
+    /// the test selection, so renaming the test doesn't rewire the reporter.
+    pub fn stable_key(&self) -> &str {
+        match &self.kind {
+            TestKind::CargoTest { .. } => self
+                .meta
+                .path
+                .as_deref()
+                .or(self.meta.filter.as_deref())
+                .unwrap_or(&self.meta.name)
+                .trim(),
+            _ => self.meta.name.trim(),
+        }
+    }
+
+    /// The manifest this test runs against, if it targets one (None = root or not a cargo step)
+    pub fn manifest_path(&self) -> Option<&str> {
+        match &self.kind {
+            TestKind::CargoTest { manifest_path }
+            | TestKind::Clippy { manifest_path }
+            | TestKind::TestCount { manifest_path, .. } => manifest_path.as_deref(),
+            TestKind::CommitCount { .. }
+            | TestKind::BranchCount { .. }
+            | TestKind::Command { .. }
+            | TestKind::Io { .. } => None,
+        }
     }
 
-    fn column_values(&self) -> Vec<String> {
-        let doc = self.resolved_description();
-        vec![
-            format!("`{}`", self.meta.name),
-            self.meta.points.to_string(),
-            doc,
-        ]
+    pub fn command(&self) -> String {
+        match &self.kind {
+            TestKind::CargoTest { manifest_path } => match (&self.meta.path, &self.meta.filter) {
+                (Some(path), _) => {
+                    cargo_test_exact_cmd(path, manifest_path.as_deref(), &self.options)
+                }
+                (None, filter) => cargo_test_cmd(
+                    filter.as_deref().unwrap_or(&self.meta.name),
+                    manifest_path.as_deref(),
+                    &self.options,
+                ),
+            },
+            TestKind::Clippy { manifest_path } => {
+                clippy_cmd(manifest_path.as_deref(), &self.options)
+            }
+            TestKind::CommitCount { min_commits } => commit_count_cmd(min_commits),
+            TestKind::TestCount {
+                min_tests,
+                manifest_path,
+            } => test_count_cmd(*min_tests, manifest_path.as_deref(), &self.options),
+            TestKind::BranchCount { min_branches } => branch_count_cmd(min_branches),
+            TestKind::Command { command } => command.clone(),
+            TestKind::Io {
+                command,
+                input,
+                expected_output,
+                comparison,
+            } => io_check_cmd(command, input, expected_output, *comparison),
+        }
     }
 }
@@ -0,0 +1,57 @@
+// Named placeholders in test names and descriptions, e.g. "Ensures at least {min_commits} commits".
+// `{{` and `}}` are literal braces; braces around anything that isn't a bare identifier (code in a
+// docstring, say) are left alone.
+use anyhow::Result;
+
+/// Fill every `{placeholder}` from `vars`; `kind` names the test type in the error for an unknown one
+pub fn render(template: &str, vars: &[(&str, String)], kind: &str) -> Result<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        let tail = &rest[i..];
+        if let Some(after) = tail.strip_prefix("{{").or_else(|| tail.strip_prefix("}}")) {
+            out.push_str(&tail[..1]);
+            rest = after;
+            continue;
+        }
+        match placeholder(tail) {
+            Some(name) => {
+                let Some((_, value)) = vars.iter().find(|(v, _)| *v == name) else {
+                    anyhow::bail!(
+                        "`{{{}}}` isn't available for {} tests (available: {})",
+                        name,
+                        kind,
+                        vars.iter()
+                            .map(|(v, _)| format!("{{{v}}}"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    );
+                };
+                out.push_str(value);
+                rest = &tail[name.len() + 2..];
+            }
+            None => {
+                out.push_str(&tail[..1]);
+                rest = &tail[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Double every brace so `s` renders back as itself (e.g. a scanned doc comment)
+pub fn escape(s: &str) -> String {
+    s.replace('{', "{{").replace('}', "}}")
+}
+
+/// `{ident}` at the start of `s`
+fn placeholder(s: &str) -> Option<&str> {
+    let inner = s.strip_prefix('{')?;
+    let end = inner.find('}')?;
+    let name = &inner[..end];
+    let is_ident = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    is_ident.then_some(name)
+}
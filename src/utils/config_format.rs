@@ -0,0 +1,114 @@
+// On-disk formats for .autograder/autograder.{json,toml,yaml}. All three share the serde model.
+use anyhow::Result;
+use clap::ValueEnum;
+use std::path::Path;
+use toml_edit::{ArrayOfTables, DocumentMut, Item, Table};
+
+use crate::types::config::AutograderConfig;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ConfigFormat {
+    /// .autograder/autograder.json
+    #[default]
+    Json,
+    /// .autograder/autograder.toml
+    Toml,
+    /// .autograder/autograder.yaml (.yml is also read)
+    Yaml,
+}
+
+impl ConfigFormat {
+    pub const ALL: [ConfigFormat; 3] = [ConfigFormat::Json, ConfigFormat::Toml, ConfigFormat::Yaml];
+
+    /// File names this format is read from; the first one is written
+    pub fn file_names(self) -> &'static [&'static str] {
+        match self {
+            ConfigFormat::Json => &["autograder.json"],
+            ConfigFormat::Toml => &["autograder.toml"],
+            ConfigFormat::Yaml => &["autograder.yaml", "autograder.yml"],
+        }
+    }
+
+    pub fn file_name(self) -> &'static str {
+        self.file_names()[0]
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(ConfigFormat::Json),
+            "toml" => Some(ConfigFormat::Toml),
+            "yaml" | "yml" => Some(ConfigFormat::Yaml),
+            _ => None,
+        }
+    }
+
+    /// Parse into a JSON value first, so version detection works the same for every format
+    pub fn to_value(self, contents: &str) -> Result<serde_json::Value> {
+        Ok(match self {
+            ConfigFormat::Json => serde_json::from_str(contents)?,
+            ConfigFormat::Toml => toml::from_str(contents)?,
+            ConfigFormat::Yaml => super::yaml::from_str(contents)?,
+        })
+    }
+
+    pub fn render(self, config: &AutograderConfig) -> Result<String> {
+        Ok(match self {
+            ConfigFormat::Json => serde_json::to_string_pretty(config)?,
+            ConfigFormat::Toml => render_toml(config)?.to_string(),
+            ConfigFormat::Yaml => super::yaml::to_string(config)?,
+        })
+    }
+}
+
+/// `[[tests]]` blocks with `meta` (and `env`) inlined, so each test reads as one block
+pub fn render_toml(config: &AutograderConfig) -> Result<DocumentMut> {
+    let mut doc = DocumentMut::new();
+    if let Some(schema) = &config.schema {
+        doc["$schema"] = toml_edit::value(schema.as_str());
+    }
+    match config.extends.as_slice() {
+        [] => {}
+        [one] => doc["extends"] = toml_edit::value(one.as_str()),
+        many => doc["extends"] = toml_edit::value(many.iter().collect::<toml_edit::Array>()),
+    }
+    doc["version"] = toml_edit::value(i64::from(config.version));
+    let settings = toml_edit::ser::to_document(&config.settings)?;
+    if !settings.is_empty() {
+        let mut settings = settings.as_table().clone();
+        expand_subtables(&mut settings);
+        doc["settings"] = Item::Table(settings);
+    }
+    let mut tests = ArrayOfTables::new();
+    for test in &config.tests {
+        tests.push(toml_test_table(test)?);
+    }
+    doc["tests"] = Item::ArrayOfTables(tests);
+    Ok(doc)
+}
+
+/// A single test as a `[[tests]]` entry, in the layout `render_toml` uses
+pub fn toml_test_table(test: &crate::types::AutoTest) -> Result<Table> {
+    let mut table = toml_edit::ser::to_document(test)?.as_table().clone();
+    inline_subtables(&mut table);
+    Ok(table)
+}
+
+fn inline_subtables(table: &mut Table) {
+    for (_, item) in table.iter_mut() {
+        if let Item::Table(sub) = item {
+            let inline = std::mem::take(sub).into_inline_table();
+            *item = Item::Value(inline.into());
+        }
+    }
+}
+
+/// `[settings.triggers]`-style headers for nested settings
+fn expand_subtables(table: &mut Table) {
+    for (_, item) in table.iter_mut() {
+        if let Some(inline) = item.as_inline_table_mut() {
+            let mut sub = std::mem::take(inline).into_table();
+            expand_subtables(&mut sub);
+            *item = Item::Table(sub);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+// `extends`: layer a config on top of shared base configs (e.g. a course-wide rubric).
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::config_format::ConfigFormat;
+use super::parse_autograder_config;
+use crate::types::config::AutograderConfig;
+
+/// Merge every base named in `config.extends` underneath it. Bases are applied left to right and
+/// may extend others. The project's own config (`path`, under `.autograder/`) names its bases
+/// relative to the project root; a base names its own bases relative to itself.
+pub fn resolve(config: &AutograderConfig, path: &Path) -> Result<AutograderConfig> {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let dir = match dir.file_name() {
+        Some(name) if name == ".autograder" => dir.parent().unwrap_or(dir),
+        _ => dir,
+    };
+    resolve_from(config, &path, dir, &mut Vec::new())
+}
+
+fn resolve_from(
+    config: &AutograderConfig,
+    path: &Path,
+    dir: &Path,
+    stack: &mut Vec<PathBuf>,
+) -> Result<AutograderConfig> {
+    stack.push(path.to_path_buf());
+
+    let mut merged = AutograderConfig::default();
+    for base in &config.extends {
+        let base_path = dir.join(base);
+        let base_path = base_path.canonicalize().with_context(|| {
+            format!(
+                "{} extends {}, which does not exist",
+                path.to_string_lossy(),
+                base_path.to_string_lossy()
+            )
+        })?;
+        if stack.contains(&base_path) {
+            anyhow::bail!(
+                "{} extends itself (through {})",
+                base_path.to_string_lossy(),
+                path.to_string_lossy()
+            );
+        }
+        let base_config = load_base(&base_path)?;
+        let base_dir = base_path.parent().unwrap_or(Path::new("."));
+        let base_config = resolve_from(&base_config, &base_path, base_dir, stack)?;
+        merged = merged.merged_with(&base_config)?;
+    }
+
+    stack.pop();
+    let local = AutograderConfig {
+        extends: Vec::new(),
+        ..config.clone()
+    };
+    Ok(merged.merged_with(&local)?)
+}
+
+fn load_base(path: &Path) -> Result<AutograderConfig> {
+    let format = ConfigFormat::from_path(path).with_context(|| {
+        format!(
+            "Can't tell the format of {}; use a .json, .toml or .yaml file",
+            path.to_string_lossy()
+        )
+    })?;
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.to_string_lossy()))?;
+    parse_autograder_config(&contents, format)
+        .with_context(|| format!("Failed to parse {}", path.to_string_lossy()))
+}
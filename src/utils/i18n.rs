@@ -0,0 +1,187 @@
+// Student-facing strings (generated descriptions, table headers, workflow step names) in the
+// language picked with `--lang` / `settings.lang`, optionally overridden by a translations file.
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+
+use super::closest;
+use super::config_format::ConfigFormat;
+use crate::types::config::Settings;
+
+pub const DEFAULT_LANG: &str = "en";
+
+/// Bundled catalogs; English is the fallback for any key another catalog leaves out
+const BUNDLED: &[(&str, &str)] = &[
+    ("en", include_str!("i18n/en.toml")),
+    ("es", include_str!("i18n/es.toml")),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Catalog {
+    entries: BTreeMap<String, String>,
+}
+
+impl Default for Catalog {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
+impl Catalog {
+    pub fn english() -> Self {
+        Catalog {
+            entries: bundled(DEFAULT_LANG).expect("the English catalog is bundled"),
+        }
+    }
+
+    pub fn languages() -> Vec<&'static str> {
+        BUNDLED.iter().map(|(lang, _)| *lang).collect()
+    }
+
+    /// A bundled language (English when None) with the keys from `translations` on top. A
+    /// language that isn't bundled needs a translations file; English fills in what it leaves out.
+    pub fn load(lang: Option<&str>, translations: Option<&Path>) -> Result<Self> {
+        let mut catalog = Self::english();
+        let lang = lang.unwrap_or(DEFAULT_LANG);
+        match bundled(lang) {
+            Some(entries) => catalog.entries.extend(entries),
+            None if translations.is_some() => {}
+            None => anyhow::bail!(
+                "No bundled translations for `{}` (bundled: {}); pass a file with --translations",
+                lang,
+                Self::languages().join(", ")
+            ),
+        }
+        if let Some(path) = translations {
+            catalog.overlay(path)?;
+        }
+        Ok(catalog)
+    }
+
+    /// Flags win over `settings.lang` / `settings.translations`; the file is relative to `root`
+    pub fn for_project(
+        root: &Path,
+        settings: &Settings,
+        lang: Option<&str>,
+        translations: Option<&Path>,
+    ) -> Result<Self> {
+        let lang = lang.or(settings.lang.as_deref());
+        let translations = translations
+            .map(Path::to_path_buf)
+            .or_else(|| settings.translations.as_ref().map(Into::into))
+            .map(|p| root.join(p));
+        Self::load(lang, translations.as_deref())
+    }
+
+    fn overlay(&mut self, path: &Path) -> Result<()> {
+        let format = ConfigFormat::from_path(path).with_context(|| {
+            format!(
+                "{} is not a .json, .toml or .yaml file",
+                path.to_string_lossy()
+            )
+        })?;
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.to_string_lossy()))?;
+        let value = format
+            .to_value(&contents)
+            .with_context(|| format!("Failed to parse {}", path.to_string_lossy()))?;
+
+        let mut entries = BTreeMap::new();
+        flatten("", &value, &mut entries)
+            .with_context(|| format!("In {}", path.to_string_lossy()))?;
+        let english = Self::english();
+        for (key, text) in entries {
+            let Some(original) = english.entries.get(&key) else {
+                let known: Vec<&str> = english.entries.keys().map(String::as_str).collect();
+                match closest(&key, &known) {
+                    Some(c) => anyhow::bail!(
+                        "Unknown key `{}` in {} (did you mean `{}`?)",
+                        key,
+                        path.to_string_lossy(),
+                        c
+                    ),
+                    None => anyhow::bail!("Unknown key `{}` in {}", key, path.to_string_lossy()),
+                }
+            };
+            let allowed = placeholders(original);
+            if let Some(p) = placeholders(&text).iter().find(|p| !allowed.contains(p)) {
+                anyhow::bail!(
+                    "`{}` in {} uses {{{}}}, which isn't available there (available: {})",
+                    key,
+                    path.to_string_lossy(),
+                    p,
+                    allowed
+                        .iter()
+                        .map(|p| format!("{{{p}}}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }
+            self.entries.insert(key, text);
+        }
+        Ok(())
+    }
+
+    /// The string for `key`, e.g. "table.points"
+    pub fn text<'a>(&'a self, key: &'a str) -> &'a str {
+        self.entries.get(key).map(String::as_str).unwrap_or(key)
+    }
+
+    /// The string for `key` with each `{name}` replaced
+    pub fn fmt(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        let mut out = self.text(key).to_string();
+        for (name, value) in args {
+            out = out.replace(&format!("{{{name}}}"), &value.to_string());
+        }
+        out
+    }
+}
+
+fn bundled(lang: &str) -> Option<BTreeMap<String, String>> {
+    let (_, source) = BUNDLED.iter().find(|(l, _)| *l == lang)?;
+    let value = ConfigFormat::Toml
+        .to_value(source)
+        .expect("bundled catalogs are valid TOML");
+    let mut entries = BTreeMap::new();
+    flatten("", &value, &mut entries).expect("bundled catalogs only hold strings");
+    Some(entries)
+}
+
+/// `{ "table": { "points": "..." } }` -> `table.points`
+fn flatten(prefix: &str, value: &Value, out: &mut BTreeMap<String, String>) -> Result<()> {
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map {
+                let key = match prefix {
+                    "" => k.clone(),
+                    p => format!("{p}.{k}"),
+                };
+                flatten(&key, v, out)?;
+            }
+        }
+        Value::String(s) => {
+            out.insert(prefix.to_string(), s.clone());
+        }
+        _ => anyhow::bail!("`{}` must be a string", prefix),
+    }
+    Ok(())
+}
+
+/// The `{name}` placeholders in a string
+fn placeholders(text: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        rest = &rest[start + 1..];
+        let Some(end) = rest.find('}') else { break };
+        let name = &rest[..end];
+        if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            found.push(name.to_string());
+        }
+        rest = &rest[end + 1..];
+    }
+    found
+}
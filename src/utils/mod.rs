@@ -1,10 +1,15 @@
-use crate::types::AutoTest;
+use crate::types::config::{AutograderConfig, CONFIG_VERSION};
 use anyhow::{Context, Result};
+use config_format::ConfigFormat;
 use std::fs;
-use std::io::BufReader;
 use std::path::{Path, PathBuf};
 
+pub mod config_format;
+pub mod extends;
+pub mod i18n;
 pub mod scripts;
+pub mod toolchain;
+pub mod yaml;
 
 //pub static DEFAULT_POINTS: u32 = 1;
 
@@ -21,6 +26,16 @@ impl RustFile {
             .with_context(|| format!("Failed to read {}", &self.path.to_string_lossy()))
     }
 }
+/// Cargo's `target/` next to a manifest, and hidden directories such as `.git`; neither holds
+/// sources. A `target` module under `src/` is still scanned.
+fn is_build_output(dir: &Path) -> bool {
+    let Some(name) = dir.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    name.starts_with('.')
+        || (name == "target" && dir.parent().is_some_and(|p| p.join("Cargo.toml").exists()))
+}
+
 fn recurse(dir: &Path, out: &mut Vec<RustFile>, current_manifest: Option<PathBuf>) -> Result<()> {
     // see if THIS dir has a Cargo.toml
     let manifest_here = {
@@ -38,6 +53,9 @@ fn recurse(dir: &Path, out: &mut Vec<RustFile>, current_manifest: Option<PathBuf
         let md = entry.metadata()?;
 
         if md.is_dir() {
+            if is_build_output(&p) {
+                continue;
+            }
             recurse(&p, out, manifest_here.clone())?;
         } else if md.is_file() && p.extension().map(|e| e == "rs").unwrap_or(false) {
             out.push(RustFile {
@@ -62,42 +80,155 @@ pub fn ensure_exists(tests_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-pub fn read_autograder_config(root: &Path) -> Result<Vec<AutoTest>> {
-    let path = root.join(".autograder").join("autograder.json");
-    ensure_exists(&path)?;
-    let file = fs::File::open(path)?;
-    let reader = BufReader::new(file);
-    let tests: Vec<AutoTest> = serde_json::from_reader(reader)?;
+/// Every config file present under `.autograder/`, in `ConfigFormat::ALL` order
+pub fn existing_config_paths(root: &Path) -> Vec<PathBuf> {
+    let dir = root.join(".autograder");
+    ConfigFormat::ALL
+        .iter()
+        .flat_map(|f| f.file_names())
+        .map(|name| dir.join(name))
+        .filter(|p| p.exists())
+        .collect()
+}
 
-    if tests.is_empty() {
+/// The config file in use: whichever of autograder.{json,toml,yaml} exists, else the JSON path
+pub fn autograder_config_path(root: &Path) -> PathBuf {
+    existing_config_paths(root)
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| {
+            root.join(".autograder")
+                .join(ConfigFormat::Json.file_name())
+        })
+}
+
+/// The format of the config file in use (JSON when there is none yet)
+pub fn autograder_config_format(root: &Path) -> ConfigFormat {
+    ConfigFormat::from_path(&autograder_config_path(root)).unwrap_or_default()
+}
+
+/// Read the config with its `extends` bases merged in, failing if it has no tests to build from
+pub fn read_autograder_config(root: &Path) -> Result<AutograderConfig> {
+    let config = load_autograder_config(root)?;
+    let config = extends::resolve(&config, &autograder_config_path(root))?;
+    if config.tests.is_empty() {
         anyhow::bail!("Autograder.json config not configured. Add tests using `auto-setup init`");
     }
+    Ok(config)
+}
+
+/// Parse the config as written, without merging `extends`, in whichever format it is stored.
+/// Accepts both the legacy bare array and the versioned object form.
+///
+/// Legacy files are upgraded in memory; `upgrade` rewrites them on disk.
+pub fn load_autograder_config(root: &Path) -> Result<AutograderConfig> {
+    let paths = existing_config_paths(root);
+    if paths.len() > 1 {
+        let names: Vec<_> = paths.iter().map(|p| p.to_string_lossy()).collect();
+        anyhow::bail!(
+            "Found more than one autograder config ({}); keep only one",
+            names.join(", ")
+        );
+    }
+    let path = autograder_config_path(root);
+    ensure_exists(&path)?;
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.to_string_lossy()))?;
+    let format = ConfigFormat::from_path(&path).unwrap_or_default();
+    parse_autograder_config(&contents, format)
+        .with_context(|| format!("Failed to parse {}", path.to_string_lossy()))
+}
+
+/// The schema version of a raw config document (1 for the legacy array)
+pub fn config_version(value: &serde_json::Value) -> u32 {
+    if value.is_array() {
+        return 1;
+    }
+    value
+        .get("version")
+        .and_then(serde_json::Value::as_u64)
+        .map_or(CONFIG_VERSION, |v| v as u32)
+}
+
+pub fn parse_autograder_config(contents: &str, format: ConfigFormat) -> Result<AutograderConfig> {
+    let value = format.to_value(contents)?;
+    let version = config_version(&value);
+    if version > CONFIG_VERSION {
+        anyhow::bail!(
+            "autograder.{} is version {}, but this autograder-setup only understands up to version {}. Upgrade autograder-setup.",
+            format.file_name().rsplit('.').next().unwrap_or_default(),
+            version,
+            CONFIG_VERSION
+        );
+    }
+    if value.is_array() {
+        return Ok(AutograderConfig::new(serde_json::from_value(value)?));
+    }
+    let mut config: AutograderConfig = serde_json::from_value(value)?;
+    config.version = CONFIG_VERSION;
+    Ok(config)
+}
+
+/// Write the config in the current versioned form, keeping the format of the existing file
+pub fn write_autograder_config(root: &Path, config: &AutograderConfig) -> Result<PathBuf> {
+    write_autograder_config_as(root, config, autograder_config_format(root))
+}
 
-    Ok(tests)
+/// Write the config as `format`. Files in other formats are left alone.
+pub fn write_autograder_config_as(
+    root: &Path,
+    config: &AutograderConfig,
+    format: ConfigFormat,
+) -> Result<PathBuf> {
+    let existing = autograder_config_path(root);
+    let path = if ConfigFormat::from_path(&existing) == Some(format) {
+        existing
+    } else {
+        root.join(".autograder").join(format.file_name())
+    };
+    let config = AutograderConfig {
+        version: CONFIG_VERSION,
+        ..config.clone()
+    };
+    fs::write(&path, format.render(&config)?)
+        .with_context(|| format!("Failed to write {}", path.to_string_lossy()))?;
+    Ok(path)
 }
 
-// Lowercase; spaces/non-alnum -> hyphens; collapse/trim hyphens.
+/// Mark a generated script executable (no-op off unix)
+pub fn make_executable(path: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+            .with_context(|| format!("Failed to mark {} executable", path.to_string_lossy()))?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+// Lowercase; spaces/non-alnum -> underscores; collapse/trim underscores. The reporter reads each
+// runner's result from `<ID>_RESULTS`, so ids stay valid identifiers: a leading digit gets `test_`.
 pub fn slug_id(name: &str) -> String {
     let mut s = String::new();
-    let mut last_dash = false;
+    let mut last_sep = true; // no leading separator
     for ch in name.chars() {
         let c = ch.to_ascii_lowercase();
         if c.is_ascii_alphanumeric() {
             s.push(c);
-            last_dash = false;
-        } else if !last_dash {
-            s.push('-');
-            last_dash = true;
+            last_sep = false;
+        } else if !last_sep {
+            s.push('_');
+            last_sep = true;
         }
     }
-    // trim leading/trailing dashes
-    while s.starts_with('-') {
-        s.remove(0);
-    }
-    while s.ends_with('-') {
+    while s.ends_with('_') {
         s.pop();
     }
-    // collapse multiple dashes already handled by last_dash flag
+    if s.starts_with(|c: char| c.is_ascii_digit()) {
+        s.insert_str(0, "test_");
+    }
     s
 }
 
@@ -116,10 +247,61 @@ pub fn yaml_quote(s: &str) -> String {
     out
 }
 
+/// Leave a value plain when YAML would read it back unchanged (e.g. step names), else quote it
+pub fn yaml_plain(s: &str) -> String {
+    let plain = s.chars().next().is_some_and(char::is_alphanumeric)
+        && s.trim_end() == s
+        && !s.ends_with(':')
+        && !s.contains(": ")
+        && !s.contains(" #")
+        && !s.contains(['\n', '"', '\'', '`']);
+    if plain { s.to_string() } else { yaml_quote(s) }
+}
+
+// Quote for POSIX shells: single-quote and splice in escaped single quotes
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// Keep `|` and `\` from breaking a Markdown table; newlines would end the row
+pub fn markdown_escape(s: &str) -> String {
+    s.replace('\\', r"\\")
+        .replace('|', r"\|")
+        .replace('\n', "<br>")
+}
+
 pub fn replace_double_hashtag(s: &str, num_commits: u32) -> String {
     s.replace("##", &num_commits.to_string())
 }
 
+/// The candidate within a typo's distance of `word`, for "did you mean" hints
+pub fn closest<'a>(word: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .map(|c| (strsim::damerau_levenshtein(word, c), *c))
+        .filter(|(d, c)| *d <= (c.len() / 3).max(2))
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c)
+}
+
+/// `[package] name` from a Cargo.toml; None for a virtual workspace manifest or an unreadable file
+pub fn package_name(manifest: &Path) -> Option<String> {
+    let table: toml::Table = fs::read_to_string(manifest).ok()?.parse().ok()?;
+    table
+        .get("package")?
+        .get("name")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Whether a Cargo.toml has a `[workspace]` table
+pub fn is_workspace_root(manifest: &Path) -> bool {
+    fs::read_to_string(manifest)
+        .ok()
+        .and_then(|s| s.parse::<toml::Table>().ok())
+        .is_some_and(|t| t.contains_key("workspace"))
+}
+
 // Convert absolute path under `root` into a clean, unix-style relative string for GH actions
 pub fn to_rel_unix_path(root: &Path, path: &Path) -> String {
     let rel = path.strip_prefix(root).unwrap_or(path).to_path_buf();
@@ -4,10 +4,12 @@
 pub struct ScriptNames {
     pub commit_count: &'static str,
     pub branch_count: &'static str,
+    pub grade: &'static str,
 }
 pub const SCRIPT_NAMES: ScriptNames = ScriptNames {
     commit_count: "commit_count.sh",
     branch_count: "branch_count.sh",
+    grade: "grade.sh",
 };
 
 // A shell script that ensures at least `n` commits exist in the git history
@@ -70,6 +70,26 @@ fn collect_rs_files_empty_dir_is_ok() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn collect_rs_files_skips_target_and_hidden_dirs() -> anyhow::Result<()> {
+    let tmp = tempdir()?;
+    let root = tmp.path();
+    fs::write(root.join("Cargo.toml"), "[package]\nname = \"p\"\n")?;
+    for dir in ["src/target", "target/debug/build/p/out", ".git/hooks"] {
+        fs::create_dir_all(root.join(dir))?;
+    }
+    fs::write(root.join("src/target/mod.rs"), "")?;
+    fs::write(root.join("target/debug/build/p/out/gen.rs"), "")?;
+    fs::write(root.join(".git/hooks/hook.rs"), "")?;
+
+    let files: Vec<PathBuf> = collect_rs_files_with_manifest(root)?
+        .into_iter()
+        .map(|f| f.path)
+        .collect();
+    assert_eq!(files, vec![root.join("src/target/mod.rs")]);
+    Ok(())
+}
+
 // -------- ensure_exists --------
 
 #[test]
@@ -98,31 +118,38 @@ fn ensure_exists_err_when_missing() {
 // -------- slug_id --------
 
 #[test]
-fn slug_id_basic_lowercases_and_replaces_non_alnum_with_single_dashes() {
+fn slug_id_basic_lowercases_and_replaces_non_alnum_with_single_underscores() {
     assert_eq!(slug_id("HelloWorld"), "helloworld");
-    assert_eq!(slug_id("Hello World"), "hello-world");
-    assert_eq!(slug_id("hello_world"), "hello-world"); // underscore → dash
-    assert_eq!(slug_id("hello---world"), "hello-world"); // collapse
-    assert_eq!(slug_id(" hello\tworld "), "hello-world"); // trim/collapse
-    assert_eq!(slug_id("A  B   C"), "a-b-c");
-    assert_eq!(slug_id("--Already-Slug--"), "already-slug"); // trims ends
+    assert_eq!(slug_id("Hello World"), "hello_world");
+    assert_eq!(slug_id("hello-world"), "hello_world"); // dash → underscore
+    assert_eq!(slug_id("hello___world"), "hello_world"); // collapse
+    assert_eq!(slug_id(" hello\tworld "), "hello_world"); // trim/collapse
+    assert_eq!(slug_id("A  B   C"), "a_b_c");
+    assert_eq!(slug_id("__Already_Slug__"), "already_slug"); // trims ends
 }
 
 #[test]
 fn slug_id_handles_unicode_by_treating_non_ascii_as_separators() {
-    // Non-ASCII letters aren’t considered ascii_alphanumeric → become dashes
-    assert_eq!(slug_id("naïve Café — test"), "na-ve-caf-test");
-    // Emojis / punctuation collapse to single dashes
-    assert_eq!(slug_id("ok✅?no!"), "ok-no");
+    // Non-ASCII letters aren’t considered ascii_alphanumeric → become underscores
+    assert_eq!(slug_id("naïve Café — test"), "na_ve_caf_test");
+    // Emojis / punctuation collapse to single underscores
+    assert_eq!(slug_id("ok✅?no!"), "ok_no");
 }
 
 #[test]
-fn slug_id_only_dashes_or_symbols_yields_empty_or_clean_slug() {
+fn slug_id_only_separators_or_symbols_yields_empty_or_clean_slug() {
     assert_eq!(slug_id("----"), "");
     assert_eq!(slug_id("   "), "");
     assert_eq!(slug_id("!!!abc???"), "abc");
 }
 
+#[test]
+fn slug_id_is_always_an_identifier() {
+    // `<ID>_RESULTS` has to be a valid environment variable name
+    assert_eq!(slug_id("1st test"), "test_1st_test");
+    assert_eq!(slug_id("test-a"), slug_id("test_a"));
+}
+
 // -------- yaml_quote --------
 
 #[test]
@@ -192,3 +219,134 @@ fn collect_rs_files_attaches_nearest_manifest() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+// -------- toolchain files --------
+
+#[test]
+fn parse_toolchain_file_accepts_toml_and_legacy_forms() -> anyhow::Result<()> {
+    use super::toolchain::parse_toolchain_file;
+    assert_eq!(
+        parse_toolchain_file("[toolchain]\nchannel = \"1.82.0\"\n")?,
+        Some("1.82.0".to_string())
+    );
+    assert_eq!(
+        parse_toolchain_file("nightly-2024-05-01\n")?,
+        Some("nightly-2024-05-01".into())
+    );
+    assert_eq!(
+        parse_toolchain_file("[toolchain]\ncomponents = [\"clippy\"]\n")?,
+        None
+    );
+    assert_eq!(parse_toolchain_file("")?, None);
+    Ok(())
+}
+
+#[test]
+fn is_action_revision_matches_dtolnay_branches() {
+    use super::toolchain::is_action_revision;
+    assert!(is_action_revision("stable"));
+    assert!(is_action_revision("1.82"));
+    assert!(is_action_revision("1.82.0"));
+    assert!(!is_action_revision("nightly-2024-05-01"));
+    assert!(!is_action_revision("1.82.0-x86_64-unknown-linux-gnu"));
+}
+
+// -------- i18n catalogs --------
+
+#[test]
+fn bundled_catalogs_translate_every_key_with_the_same_placeholders() {
+    use super::i18n::Catalog;
+    // key -> the `{...}` placeholders it uses
+    fn shape(source: &str) -> Vec<(String, Vec<String>)> {
+        let table: toml::Table = source.parse().unwrap();
+        let mut out = Vec::new();
+        for (section, entries) in table {
+            for (key, text) in entries.as_table().unwrap() {
+                let mut names: Vec<String> = text
+                    .as_str()
+                    .unwrap()
+                    .split('{')
+                    .skip(1)
+                    .filter_map(|s| s.split_once('}').map(|(n, _)| n.to_string()))
+                    .collect();
+                names.sort();
+                out.push((format!("{section}.{key}"), names));
+            }
+        }
+        out
+    }
+    let english = shape(include_str!("i18n/en.toml"));
+    assert_eq!(shape(include_str!("i18n/es.toml")), english);
+    assert_eq!(Catalog::languages(), ["en", "es"]);
+
+    let es = Catalog::load(Some("es"), None).unwrap();
+    assert_eq!(es.text("table.points"), "Puntos");
+    let err = Catalog::load(Some("fr"), None).unwrap_err().to_string();
+    assert!(err.contains("bundled: en, es"), "{err}");
+}
+
+#[test]
+fn translations_file_overrides_keys_and_rejects_unknown_ones() -> anyhow::Result<()> {
+    use super::i18n::Catalog;
+    let tmp = tempdir()?;
+    let file = tmp.path().join("fr.toml");
+    fs::write(
+        &file,
+        "[table]\npoints = \"Points\"\nname = \"Nom\"\n\n[init]\ncommit_count = \"Au moins {count} commits.\"\n",
+    )?;
+    // Not bundled, so English fills in the rest
+    let fr = Catalog::load(Some("fr"), Some(&file))?;
+    assert_eq!(fr.text("table.name"), "Nom");
+    assert_eq!(fr.text("table.description"), "Description");
+    assert_eq!(
+        fr.fmt("init.commit_count", &[("count", &2)]),
+        "Au moins 2 commits."
+    );
+
+    fs::write(&file, "[table]\npoint = \"Points\"\n")?;
+    let err = Catalog::load(None, Some(&file)).unwrap_err().to_string();
+    assert!(err.contains("did you mean `table.points`?"), "{err}");
+
+    fs::write(&file, "[init]\ncommit_count = \"{n} commits\"\n")?;
+    let err = Catalog::load(None, Some(&file)).unwrap_err().to_string();
+    assert!(err.contains("available: {count}"), "{err}");
+    Ok(())
+}
+
+#[test]
+fn yaml_plain_quotes_only_when_needed() {
+    assert_eq!(yaml_plain("Checkout code"), "Checkout code");
+    assert_eq!(yaml_plain("Obtener el código"), "Obtener el código");
+    assert_eq!(yaml_plain("Paso: compilar"), "\"Paso: compilar\"");
+    assert_eq!(yaml_plain("`cargo` step"), "\"`cargo` step\"");
+}
+
+// -------- closest --------
+
+#[test]
+fn closest_suggests_only_near_misses() {
+    let known = ["description", "points", "timeout"];
+    assert_eq!(closest("descripton", &known), Some("description"));
+    assert_eq!(closest("pionts", &known), Some("points"));
+    assert_eq!(closest("weight", &known), None);
+}
+
+// -------- yaml --------
+
+#[test]
+fn yaml_round_trips_ambiguous_scalars_in_order() -> anyhow::Result<()> {
+    let value = serde_json::json!({
+        "name": "yes",
+        "count": "10",
+        "version": 2,
+        "ratio": 1.5,
+        "doc": "first\n second",
+        "empty": "",
+        "list": [true, null],
+    });
+    let rendered = yaml::to_string(&value)?;
+    assert!(rendered.starts_with("name: \"yes\"\n"), "{rendered}");
+    assert!(!rendered.starts_with("---"), "{rendered}");
+    assert_eq!(yaml::from_str(&rendered)?, value);
+    Ok(())
+}
@@ -0,0 +1,59 @@
+// Toolchain detection from `rust-toolchain.toml` / `rust-toolchain` files.
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+pub const DEFAULT_TOOLCHAIN: &str = "stable";
+
+#[derive(Deserialize)]
+struct ToolchainFile {
+    toolchain: ToolchainSection,
+}
+
+#[derive(Deserialize)]
+struct ToolchainSection {
+    channel: Option<String>,
+}
+
+/// Look for `rust-toolchain.toml` (preferred) or `rust-toolchain` in `root` and return its channel.
+pub fn detect_toolchain(root: &Path) -> Result<Option<String>> {
+    for file_name in ["rust-toolchain.toml", "rust-toolchain"] {
+        let path = root.join(file_name);
+        if !path.is_file() {
+            continue;
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.to_string_lossy()))?;
+        let channel = parse_toolchain_file(&contents)
+            .with_context(|| format!("Failed to parse {}", path.to_string_lossy()))?;
+        return Ok(channel);
+    }
+    Ok(None)
+}
+
+/// Accepts either the TOML form (`[toolchain] channel = "..."`) or the legacy single-line form.
+pub fn parse_toolchain_file(contents: &str) -> Result<Option<String>> {
+    let trimmed = contents.trim();
+    if trimmed.is_empty() {
+        return Ok(None);
+    }
+    if !trimmed.contains('[') && !trimmed.contains('=') {
+        return Ok(Some(trimmed.to_string()));
+    }
+    let file: ToolchainFile = toml::from_str(trimmed)?;
+    Ok(file.toolchain.channel.map(|c| c.trim().to_string()))
+}
+
+/// `dtolnay/rust-toolchain` publishes a branch per channel and per `1.x` / `1.x.y` release.
+/// Anything else (dated nightlies, host-qualified names) has to go through `@master` + `toolchain:`.
+pub fn is_action_revision(toolchain: &str) -> bool {
+    if matches!(toolchain, "stable" | "beta" | "nightly") {
+        return true;
+    }
+    let parts: Vec<&str> = toolchain.split('.').collect();
+    (parts.len() == 2 || parts.len() == 3)
+        && parts
+            .iter()
+            .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()))
+}
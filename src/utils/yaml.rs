@@ -0,0 +1,88 @@
+// YAML in and out through `yaml-rust2`, bridged to serde via `serde_json::Value` so the config
+// types only need their JSON derives.
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+use yaml_rust2::yaml::Hash;
+use yaml_rust2::{Yaml, YamlEmitter, YamlLoader};
+
+/// Parse the first document of `contents` (an empty file is `null`)
+pub fn from_str(contents: &str) -> Result<Value> {
+    let docs = YamlLoader::load_from_str(contents)?;
+    match docs.into_iter().next() {
+        Some(doc) => to_json(doc),
+        None => Ok(Value::Null),
+    }
+}
+
+/// Render `value` as a block-style YAML document, without the leading `---`
+pub fn to_string<T: Serialize>(value: &T) -> Result<String> {
+    let doc = to_yaml(&serde_json::to_value(value)?);
+    let mut out = String::new();
+    let mut emitter = YamlEmitter::new(&mut out);
+    emitter.multiline_strings(true);
+    emitter.dump(&doc).context("Failed to render YAML")?;
+    let mut out = match out.strip_prefix("---\n") {
+        Some(body) => body.to_string(),
+        None => out.trim_start_matches("---").trim_start().to_string(),
+    };
+    out.push('\n');
+    Ok(out)
+}
+
+fn to_json(doc: Yaml) -> Result<Value> {
+    Ok(match doc {
+        Yaml::Null => Value::Null,
+        Yaml::Boolean(b) => Value::Bool(b),
+        Yaml::Integer(i) => Value::Number(i.into()),
+        Yaml::Real(r) => {
+            let n = r
+                .parse::<f64>()
+                .ok()
+                .and_then(Number::from_f64)
+                .ok_or_else(|| anyhow::anyhow!("`{}` isn't a finite number", r))?;
+            Value::Number(n)
+        }
+        Yaml::String(s) => Value::String(s),
+        Yaml::Array(items) => Value::Array(items.into_iter().map(to_json).collect::<Result<_>>()?),
+        Yaml::Hash(hash) => {
+            let mut map = Map::new();
+            for (key, value) in hash {
+                map.insert(key_string(key)?, to_json(value)?);
+            }
+            Value::Object(map)
+        }
+        Yaml::Alias(_) => anyhow::bail!("YAML aliases aren't supported"),
+        Yaml::BadValue => anyhow::bail!("Invalid YAML value"),
+    })
+}
+
+fn key_string(key: Yaml) -> Result<String> {
+    Ok(match key {
+        Yaml::String(s) | Yaml::Real(s) => s,
+        Yaml::Integer(i) => i.to_string(),
+        Yaml::Boolean(b) => b.to_string(),
+        Yaml::Null => "null".to_string(),
+        _ => anyhow::bail!("Mapping keys must be plain scalars"),
+    })
+}
+
+fn to_yaml(value: &Value) -> Yaml {
+    match value {
+        Value::Null => Yaml::Null,
+        Value::Bool(b) => Yaml::Boolean(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Yaml::Integer(i),
+            None => Yaml::Real(n.to_string()),
+        },
+        Value::String(s) => Yaml::String(s.clone()),
+        Value::Array(items) => Yaml::Array(items.iter().map(to_yaml).collect()),
+        Value::Object(map) => {
+            let mut hash = Hash::new();
+            for (key, value) in map {
+                hash.insert(Yaml::String(key.clone()), to_yaml(value));
+            }
+            Yaml::Hash(hash)
+        }
+    }
+}